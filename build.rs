@@ -0,0 +1,14 @@
+// grpc フィーチャーが有効な場合のみ、proto/timer.proto からサーバーのコードを生成する
+// protoc はビルド環境に依存しないよう、protoc-bin-vendored に同梱されたものを使う
+fn main() {
+    #[cfg(feature = "grpc")]
+    {
+        println!("cargo:rerun-if-changed=proto/timer.proto");
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("vendored protoc");
+        std::env::set_var("PROTOC", protoc);
+        tonic_build::configure()
+            .build_client(false)
+            .compile_protos(&["proto/timer.proto"], &["proto"])
+            .expect("compile proto/timer.proto");
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use iced::{Align, Column, Element, Length, Text};
+use serde::Deserialize;
+
+use crate::format::{format_duration, FormatOptions};
+use crate::style::Theme;
+use crate::{font, Message};
+
+// 操作がないまましばらく経った際に、画面全体に大きく表示する内容
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AmbientDisplay {
+    Clock,   // 現在時刻
+    Elapsed, // ストップウォッチの経過時間
+}
+
+// 現在時刻か経過時間を、通常の表示より大きく画面の中央に表示する
+pub fn view<'a>(
+    display: AmbientDisplay,
+    now: DateTime<Local>,
+    elapsed: Duration,
+    format: &FormatOptions,
+    theme: Theme,
+) -> Element<'a, Message> {
+    // 経過時間は桁数が多いため、ウィンドウの幅に収まるよう時計より小さくする
+    let (main, sub, size) = match display {
+        AmbientDisplay::Clock => (
+            now.format("%H:%M").to_string(),
+            now.format("%Y-%m-%d (%a)").to_string(),
+            theme.readout_size() * 2,
+        ),
+        AmbientDisplay::Elapsed => (
+            format_duration(elapsed, format),
+            now.format("%H:%M").to_string(),
+            theme.readout_size() * 3 / 2,
+        ),
+    };
+
+    Column::new()
+        .push(Text::new(main).font(font()).size(size))
+        .push(Text::new(sub).font(font()).color(theme.subtle()))
+        .spacing(10)
+        .width(Length::Fill)
+        .align_items(Align::Center)
+        .into()
+}
@@ -0,0 +1,152 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{value_parser, Arg, ArgAction, ArgMatches, Command};
+
+use crate::config::Config;
+use crate::env::Startup;
+use crate::format::parse_duration;
+use crate::mode::Mode;
+
+// コマンドライン引数の指定。解釈と --help の表示、不正な引数のエラーの出力は clap に任せる
+#[derive(Debug, Clone, Default)]
+pub struct Args {
+    // 状態遷移などの詳細なログを出力する
+    pub verbose: bool,
+    // 履歴を集計して出力する(期間は現在 week のみ)
+    pub report: bool,
+    // 集計をJSONで出力する
+    pub json: bool,
+    // 計時の精度を確かめる自己診断。長さを省略した場合は Some(None)
+    pub selftest: Option<Option<Duration>>,
+    // ウィンドウを開かずに端末でカウントダウンする長さ
+    pub no_gui: Option<Duration>,
+    // 端末でのカウントダウンで、残り時間の表示を省略する
+    pub quiet: bool,
+    // 設定ファイルを既定値に戻してから起動する
+    pub reset_config: bool,
+    // 設定ファイルによらずタッチ操作向けの表示にする
+    pub touch: bool,
+    // 起動時に読み込むカウントダウンの一覧のファイル
+    pub load: Option<PathBuf>,
+    // 起動時に追加するカウントダウンの長さ
+    pub countdown: Option<Duration>,
+    // ポモドーロの設定のインターバルで起動する
+    pub pomodoro: bool,
+    // 起動時のタイマーをすぐに開始する
+    pub autostart: bool,
+}
+
+impl Args {
+    // プロセスのコマンドライン引数を解釈する。--help の場合や不正な引数の場合は、clap が出力して終了する
+    pub fn parse() -> Args {
+        Args::from_matches(&command().get_matches())
+    }
+
+    fn from_matches(matches: &ArgMatches) -> Args {
+        Args {
+            verbose: matches.get_flag("verbose"),
+            report: matches.contains_id("report"),
+            json: matches.get_flag("json"),
+            selftest: matches
+                .contains_id("selftest")
+                .then(|| matches.get_one::<Duration>("selftest").copied()),
+            no_gui: matches.get_one::<Duration>("no-gui").copied(),
+            quiet: matches.get_flag("quiet"),
+            reset_config: matches.get_flag("reset-config"),
+            touch: matches.get_flag("touch"),
+            load: matches.get_one::<PathBuf>("load").cloned(),
+            countdown: matches.get_one::<Duration>("countdown").copied(),
+            pomodoro: matches.get_flag("pomodoro"),
+            autostart: matches.get_flag("autostart"),
+        }
+    }
+
+    // 起動時のモードとタイマーの指定を、環境変数と設定ファイルの内容より優先して反映する
+    pub fn apply(&self, config: &mut Config, startup: &mut Startup) {
+        if self.pomodoro {
+            startup.pomodoro = true;
+            startup.mode = Some(Mode::Interval);
+        }
+        if let Some(length) = self.countdown {
+            startup.mode = Some(Mode::Countdown);
+            startup.countdown = Some(length);
+        }
+        if let Some(mode) = startup.mode {
+            config.mode = mode;
+        }
+        if self.autostart {
+            startup.autostart = true;
+        }
+    }
+}
+
+fn command() -> Command {
+    Command::new("simple_timer")
+        .about("A small stopwatch, countdown and interval timer")
+        .arg(Arg::new("verbose")
+            .short('v')
+            .long("verbose")
+            .action(ArgAction::SetTrue)
+            .help("Log state transitions and other details"))
+        .arg(Arg::new("countdown")
+            .long("countdown")
+            .value_name("LENGTH")
+            .value_parser(parse_length)
+            .help("Open the Countdown mode with a countdown of this length (e.g. 10m, 1:30)"))
+        .arg(Arg::new("pomodoro")
+            .long("pomodoro")
+            .action(ArgAction::SetTrue)
+            .conflicts_with("countdown")
+            .help("Open the Interval mode with the Pomodoro plan ([pomodoro] in the config, 25m work and 5m breaks by default)"))
+        .arg(Arg::new("autostart")
+            .long("autostart")
+            .action(ArgAction::SetTrue)
+            .help("Start the countdown, interval or stopwatch right away"))
+        .arg(Arg::new("load")
+            .long("load")
+            .value_name("FILE")
+            .value_parser(value_parser!(PathBuf))
+            .help("Import the countdowns listed in this JSON file at startup"))
+        .arg(Arg::new("touch")
+            .long("touch")
+            .action(ArgAction::SetTrue)
+            .help("Use larger buttons and a larger window for touch screens"))
+        .arg(Arg::new("reset-config")
+            .long("reset-config")
+            .action(ArgAction::SetTrue)
+            .help("Restore the default settings before starting"))
+        .arg(Arg::new("no-gui")
+            .long("no-gui")
+            .value_name("LENGTH")
+            .value_parser(parse_length)
+            .help("Count down in the terminal without opening a window"))
+        .arg(Arg::new("quiet")
+            .short('q')
+            .long("quiet")
+            .action(ArgAction::SetTrue)
+            .help("With --no-gui, only ring the bell at the end"))
+        .arg(Arg::new("report")
+            .long("report")
+            .value_name("PERIOD")
+            .value_parser(["week"])
+            .help("Print the recorded time per day and exit"))
+        .arg(Arg::new("json")
+            .long("json")
+            .action(ArgAction::SetTrue)
+            .help("With --report, print JSON"))
+        .arg(Arg::new("selftest")
+            .long("selftest")
+            .value_name("LENGTH")
+            .num_args(0..=1)
+            .value_parser(parse_length)
+            .help("Measure the timing drift for a while (5s by default) and exit"))
+}
+
+// "10m"・"1:30" のような長さを解釈する。0や解釈できない値はエラーにする
+fn parse_length(text: &str) -> Result<Duration, String> {
+    match parse_duration(text) {
+        Some(length) if length > Duration::default() => Ok(length),
+        _ => Err(format!("invalid length {:?} (e.g. 10m, 90s, 1:30)", text)),
+    }
+}
@@ -0,0 +1,50 @@
+// 計測中に画面のスリープやスクリーンセーバーを抑止するための構造体
+//
+// 抑止は keep-awake フィーチャーを有効にしてビルドした場合のみ行われ、無効な場合は何もしない。
+// 保持している抑止オブジェクトは解放時(終了時やパニック時のアンワインド)にdropされ、抑止が解除される。
+// プロセスが強制終了した場合も、各OSはプロセスの終了とともに抑止を解除する。
+pub struct AwakeGuard {
+    #[cfg(feature = "keep-awake")]
+    inner: Option<keepawake::KeepAwake>,
+}
+
+impl AwakeGuard {
+    pub fn new() -> AwakeGuard {
+        AwakeGuard {
+            #[cfg(feature = "keep-awake")]
+            inner: None,
+        }
+    }
+
+    // 抑止の有効・無効を切り替える
+    #[cfg(feature = "keep-awake")]
+    pub fn set_active(&mut self, active: bool) {
+        if active == self.inner.is_some() {
+            return;
+        }
+
+        if active {
+            let result = keepawake::Builder::default()
+                .display(true)
+                .idle(true)
+                .reason("Timer is running")
+                .app_name("simple_timer")
+                .app_reverse_domain("io.github.radish-miyazaki.simple_timer")
+                .create();
+
+            match result {
+                Ok(inner) => {
+                    log::debug!("screen sleep inhibited");
+                    self.inner = Some(inner);
+                },
+                Err(err) => log::warn!("failed to inhibit screen sleep: {}", err),
+            }
+        } else {
+            log::debug!("screen sleep inhibitor released");
+            self.inner = None;
+        }
+    }
+
+    #[cfg(not(feature = "keep-awake"))]
+    pub fn set_active(&mut self, _active: bool) {}
+}
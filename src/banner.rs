@@ -0,0 +1,99 @@
+use std::time::Duration;
+
+use iced::{button, Align, Button, Column, Container, Element, HorizontalAlignment, Length, Text};
+use serde::Deserialize;
+
+use crate::format::deserialize_duration;
+use crate::focus::Focus;
+use crate::layout::ButtonSize;
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// カウントダウンやインターバルの終了を、確認するまで表示し続ける設定
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct BannerConfig {
+    pub enabled: bool,
+    // 確認するまでの間、この間隔で終了の音を鳴らし直す。0の場合は鳴らし直さない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub repeat_sound: Duration,
+}
+
+// 確認待ちの終了の表示
+pub struct Banner {
+    // 終了したタイマーの名前(確認までに終了した順)
+    labels: Vec<String>,
+    // 音を鳴らし直すか否か。消音にしたカウントダウンのみが終了した場合は鳴らさない
+    sound: bool,
+    // 前回音を鳴らしてからの経過時間
+    since_sound: Duration,
+    dismiss_button_state: button::State,
+}
+
+impl Banner {
+    pub fn new(label: &str, sound: bool) -> Banner {
+        Banner {
+            labels: vec![label.to_string()],
+            sound,
+            since_sound: Duration::default(),
+            dismiss_button_state: button::State::new(),
+        }
+    }
+
+    // 確認する前に別のタイマーが終了した場合は、同じ表示にまとめる
+    pub fn add(&mut self, label: &str, sound: bool) {
+        self.labels.push(label.to_string());
+        self.sound |= sound;
+    }
+
+    // 経過時間を進め、音を鳴らし直す時刻になった場合に true を返す
+    pub fn advance(&mut self, delta: Duration, repeat: Duration) -> bool {
+        if !self.sound || repeat == Duration::default() {
+            return false;
+        }
+
+        self.since_sound += delta;
+        if self.since_sound < repeat {
+            return false;
+        }
+        self.since_sound = Duration::default();
+        true
+    }
+
+    pub fn view(
+        &mut self,
+        focus: Option<Focus>,
+        theme: Theme,
+        spacing: u16,
+        padding: u16,
+        button_size: ButtonSize,
+    ) -> Element<'_, Message> {
+        let dismiss_button = Button::new(
+            &mut self.dismiss_button_state,
+            Text::new("Dismiss")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+                .size(button_size.text_size),
+        )
+            .min_width(button_size.min_width)
+            .padding(button_size.padding)
+            .on_press(Message::DismissBanner)
+            .style(style::Button { theme, focused: focus == Some(Focus::DismissBanner) });
+
+        let content = Column::new()
+            .push(Text::new("TIME'S UP").font(font()).size(theme.readout_size()).color(theme.flash()))
+            .push(Text::new(format!("{} finished", self.labels.join(", "))).font(font()))
+            .push(dismiss_button)
+            .spacing(spacing)
+            .padding(padding)
+            .width(Length::Fill)
+            .align_items(Align::Center);
+
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_y()
+            .style(style::Root(theme))
+            .into()
+    }
+}
@@ -0,0 +1,115 @@
+use std::fs;
+use std::path::Path;
+use std::time::Duration;
+
+use serde_json::Value;
+
+use crate::format::parse_duration;
+
+// 一括で追加するカウントダウンの1件分
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TimerSpec {
+    pub name: String,
+    pub duration: Duration,
+}
+
+// ファイルを読み込み、カウントダウンの一覧として解釈する
+pub fn load(path: &Path) -> Result<Vec<TimerSpec>, String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    parse_timer_batch(&text).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+// `[{"name": "Tea", "duration": "3m", "mode": "countdown"}, ...]` の形式のJSONを解釈する
+// duration は "90s"・"1:30" のような文字列か秒数、mode は省略するか "countdown" のみを受け付ける。
+// 途中までだけ追加されることがないよう、1件でも不正なものがあれば、不正なものをすべて挙げたエラーを返す
+pub fn parse_timer_batch(json: &str) -> Result<Vec<TimerSpec>, String> {
+    let entries: Vec<Value> = serde_json::from_str(json).map_err(|err| format!("invalid JSON: {}", err))?;
+
+    let mut timers = Vec::new();
+    let mut errors = Vec::new();
+    for (index, entry) in entries.iter().enumerate() {
+        match parse_entry(entry) {
+            Ok(timer) => timers.push(timer),
+            Err(err) => errors.push(format!("entry {}: {}", index + 1, err)),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(timers)
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+fn parse_entry(entry: &Value) -> Result<TimerSpec, String> {
+    let name = match entry.get("name") {
+        Some(Value::String(name)) => name.trim().to_string(),
+        None | Some(Value::Null) => String::new(),
+        Some(other) => return Err(format!("name must be a string, got {}", other)),
+    };
+
+    match entry.get("mode") {
+        None | Some(Value::Null) => {},
+        Some(Value::String(mode)) if mode == "countdown" => {},
+        Some(other) => return Err(format!("unsupported mode {}", other)),
+    }
+
+    let duration = match entry.get("duration") {
+        Some(Value::String(text)) => parse_duration(text).ok_or_else(|| format!("invalid duration {:?}", text))?,
+        Some(Value::Number(seconds)) => seconds
+            .as_f64()
+            .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+            .ok_or_else(|| format!("invalid duration {}", seconds))?,
+        Some(other) => return Err(format!("invalid duration {}", other)),
+        None => return Err(String::from("missing duration")),
+    };
+    if duration == Duration::default() {
+        return Err(String::from("duration must be positive"));
+    }
+
+    Ok(TimerSpec { name, duration })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_named_countdowns() {
+        let timers = parse_timer_batch(r#"[
+            { "name": " Tea ", "duration": "3m", "mode": "countdown" },
+            { "name": "Egg", "duration": 420 },
+            { "duration": 1.5 }
+        ]"#).unwrap();
+        assert_eq!(timers, vec![
+            TimerSpec { name: String::from("Tea"), duration: Duration::from_secs(180) },
+            TimerSpec { name: String::from("Egg"), duration: Duration::from_secs(420) },
+            TimerSpec { name: String::new(), duration: Duration::from_millis(1500) },
+        ]);
+        assert_eq!(parse_timer_batch("[]"), Ok(Vec::new()));
+    }
+
+    #[test]
+    fn reports_every_invalid_entry() {
+        let err = parse_timer_batch(r#"[
+            { "name": "Tea", "duration": "3m" },
+            { "name": "Egg", "duration": "soon" },
+            { "name": 1, "duration": "1m" },
+            { "duration": "1m", "mode": "interval" },
+            { "name": "Zero", "duration": 0 },
+            { "name": "Negative", "duration": -5 },
+            { "name": "Huge", "duration": 1e30 },
+            { "name": "Missing" }
+        ]"#).unwrap_err();
+        assert_eq!(err, [
+            "entry 2: invalid duration \"soon\"",
+            "entry 3: name must be a string, got 1",
+            "entry 4: unsupported mode \"interval\"",
+            "entry 5: duration must be positive",
+            "entry 6: invalid duration -5",
+            "entry 7: invalid duration 1e+30",
+            "entry 8: missing duration",
+        ].join("; "));
+        assert!(parse_timer_batch("{").unwrap_err().starts_with("invalid JSON"));
+    }
+}
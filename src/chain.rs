@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::format::deserialize_duration;
+
+// 読み込んだカウントダウンの一覧を、終わるたびに次のものを自動で始める連続のタイマーとして扱う設定
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct ChainConfig {
+    pub enabled: bool,
+    // 1つが終わってから次を始めるまでの間隔。0の場合はすぐに始める
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub pause: Duration,
+    // 最後のものが終わった後に、最初のものから繰り返すか否か
+    pub repeat: bool,
+}
+
+// 連続で動かすカウントダウンの並びと、次のものを始めるまでの待ち
+pub struct Chain {
+    // 始める順に並べたカウントダウンの番号
+    ids: Vec<usize>,
+    pause: Duration,
+    repeat: bool,
+    // 次に始めるカウントダウンの番号と、始めるまでの残り時間
+    pending: Option<(usize, Duration)>,
+}
+
+impl Chain {
+    pub fn new(ids: Vec<usize>, config: ChainConfig) -> Chain {
+        Chain { ids, pause: config.pause, repeat: config.repeat, pending: None }
+    }
+
+    pub fn contains(&self, id: usize) -> bool {
+        self.ids.contains(&id)
+    }
+
+    // 指定したカウントダウンの次に始めるもの。最後のものの後は、繰り返す設定の場合のみ最初のものを返す
+    pub fn next_after(&self, id: usize) -> Option<usize> {
+        let position = self.ids.iter().position(|&other| other == id)?;
+        match self.ids.get(position + 1) {
+            Some(&next) => Some(next),
+            None if self.repeat => self.ids.first().copied(),
+            None => None,
+        }
+    }
+
+    // 次に始めるカウントダウンの番号と、始めるまでの残り時間。待っていない場合はNone
+    pub fn pending(&self) -> Option<(usize, Duration)> {
+        self.pending
+    }
+
+    // 連続の一部のカウントダウンが終わった際に呼ぶ
+    // 間隔を空けない設定では次に始めるものを返し、そうでなければ間隔の後に始めるよう待ちに入れる
+    pub fn on_finished(&mut self, id: usize) -> Option<usize> {
+        let next = self.next_after(id)?;
+        if self.pause == Duration::default() {
+            return Some(next);
+        }
+        self.pending = Some((next, self.pause));
+        None
+    }
+
+    // 待ちの残り時間を進め、間隔が経ったら始めるカウントダウンの番号を返す
+    pub fn advance(&mut self, delta: Duration) -> Option<usize> {
+        let (next, remaining) = self.pending?;
+        match remaining.checked_sub(delta) {
+            Some(remaining) if remaining > Duration::default() => {
+                self.pending = Some((next, remaining));
+                None
+            },
+            _ => {
+                self.pending = None;
+                Some(next)
+            },
+        }
+    }
+
+    // 削除されたカウントダウンを並びから除く。待っていたものが削除された場合は、待ちも取り消す
+    pub fn remove(&mut self, id: usize) {
+        self.ids.retain(|&other| other != id);
+        if self.pending.map(|(next, _)| next) == Some(id) {
+            self.pending = None;
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
@@ -0,0 +1,202 @@
+use std::error::Error;
+use std::path::{Path, PathBuf};
+use std::sync::Once;
+use std::time::Duration;
+
+use plotters::prelude::*;
+use plotters::style::text_anchor::{HPos, Pos, VPos};
+
+use crate::FONT_BYTES;
+
+// 出力する画像の大きさ
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 400;
+
+// 共有用の画像に並べるラップの最大の行数。これを超える分は件数のみを表示する
+const CARD_LAPS: usize = 6;
+// 共有用の画像の配色
+const CARD_BACKGROUND: RGBColor = RGBColor(0x1E, 0x1E, 0x2E);
+const CARD_TEXT: RGBColor = RGBColor(0xCD, 0xD6, 0xF4);
+const CARD_SUBTLE: RGBColor = RGBColor(0x93, 0x99, 0xB2);
+const CARD_ACCENT: RGBColor = RGBColor(0xF3, 0x8B, 0xA8);
+
+// plottersの文字の描画に画面と同じフォントを使う
+// 既定のフォント名で登録しておくことで、軸のラベルなどもこのフォントで描画される
+fn register_font() {
+    static REGISTER: Once = Once::new();
+    REGISTER.call_once(|| {
+        if plotters::style::register_font("sans-serif", FontStyle::Normal, FONT_BYTES).is_err() {
+            log::warn!("failed to register the chart font");
+        }
+    });
+}
+
+// グラフの保存先(画像のディレクトリ、なければホームディレクトリ)。ファイル名には作成時刻を含める
+pub fn default_path() -> PathBuf {
+    picture_path("laps")
+}
+
+// 共有用の画像の保存先。グラフと同じディレクトリに、作成時刻を含む名前で保存する
+pub fn default_card_path() -> PathBuf {
+    picture_path("session")
+}
+
+fn picture_path(prefix: &str) -> PathBuf {
+    let dir = dirs::picture_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    dir.join(format!("{}-{}.png", prefix, chrono::Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+// 共有用の画像に載せる、1回の計測の要約。時間はいずれも表示用に整形した文字列
+pub struct SessionSummary {
+    pub label: String,
+    pub total: String,
+    pub date: String,
+    // ラップ番号と累計・スプリットを並べた行(記録した順)
+    pub laps: Vec<String>,
+    // スプリットの平均・最短・最長。ラップがない場合はNone
+    pub stats: Option<String>,
+}
+
+// ラップのグラフの1本の棒。label は棒の上に添えるスプリットの表示で、note は横軸のラップ番号に添えるメモ
+pub struct LapBar {
+    pub split: Duration,
+    pub label: String,
+    pub note: String,
+}
+
+// 計測の要約を、ラベル・合計時間・日付・ラップの一覧を並べたカードの画像にしてPNGファイルに書き出す
+// ラップは新しいものから CARD_LAPS 行までを載せ、それより前の分は件数のみを表示する
+pub fn render_summary_card(summary: &SessionSummary, path: &Path) -> Result<(), Box<dyn Error>> {
+    register_font();
+
+    let root = BitMapBackend::new(path, (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&CARD_BACKGROUND)?;
+    root.draw(&Rectangle::new([(0, 0), (WIDTH as i32, 6)], CARD_ACCENT.filled()))?;
+
+    let center = (WIDTH / 2) as i32;
+    let centered = Pos::new(HPos::Center, VPos::Top);
+    root.draw_text(&summary.label, &("sans-serif", 28).into_font().color(&CARD_ACCENT).pos(centered), (center, 28))?;
+    root.draw_text(&summary.total, &("sans-serif", 72).into_font().color(&CARD_TEXT).pos(centered), (center, 70))?;
+    root.draw_text(&summary.date, &("sans-serif", 16).into_font().color(&CARD_SUBTLE).pos(centered), (center, 156))?;
+
+    let mut y = 196;
+    if let Some(stats) = &summary.stats {
+        root.draw_text(stats, &("sans-serif", 18).into_font().color(&CARD_TEXT).pos(centered), (center, y))?;
+        y += 32;
+    }
+
+    let line_style = ("sans-serif", 16).into_font().color(&CARD_SUBTLE).pos(centered);
+    let hidden = summary.laps.len().saturating_sub(CARD_LAPS);
+    for line in summary.laps.iter().rev().take(CARD_LAPS) {
+        root.draw_text(line, &line_style, (center, y))?;
+        y += 22;
+    }
+    if hidden > 0 {
+        let more = format!("and {} earlier {}", hidden, if hidden == 1 { "lap" } else { "laps" });
+        root.draw_text(&more, &line_style, (center, y))?;
+    }
+
+    root.present()?;
+    Ok(())
+}
+
+// 各ラップのスプリットを棒グラフにしてPNGファイルに書き出す。横軸がラップ番号、縦軸がスプリットの秒数
+// 棒の高さは丸める前のスプリットとし、棒の上にはそれぞれの label を添える
+pub fn render_lap_chart(bars: &[LapBar], path: &Path) -> Result<(), Box<dyn Error>> {
+    if bars.is_empty() {
+        return Err("no laps to export".into());
+    }
+    register_font();
+
+    let root = BitMapBackend::new(path, (WIDTH, HEIGHT)).into_drawing_area();
+    root.fill(&WHITE)?;
+
+    let count = bars.len() as u32;
+    let longest = bars.iter().map(|bar| bar.split.as_secs_f64()).fold(0.0, f64::max).max(1.0);
+
+    let mut chart = ChartBuilder::on(&root)
+        .caption("Lap splits", ("sans-serif", 24))
+        .margin(10)
+        .x_label_area_size(40)
+        .y_label_area_size(50)
+        .build_cartesian_2d((1..count).into_segmented(), 0.0..longest * 1.1)?;
+
+    chart
+        .configure_mesh()
+        .disable_x_mesh()
+        .x_desc("Lap")
+        .x_label_formatter(&|value| {
+            let number = match value {
+                SegmentValue::Exact(number) | SegmentValue::CenterOf(number) => *number,
+                SegmentValue::Last => return String::new(),
+            };
+            match (number as usize).checked_sub(1).and_then(|index| bars.get(index)).filter(|bar| !bar.note.is_empty()) {
+                Some(bar) => format!("{} {}", number, bar.note),
+                None => number.to_string(),
+            }
+        })
+        .y_desc("Split (s)")
+        .draw()?;
+
+    chart.draw_series(
+        Histogram::vertical(&chart)
+            .style(BLUE.filled())
+            .margin(5)
+            .data(bars.iter().enumerate().map(|(index, bar)| (index as u32 + 1, bar.split.as_secs_f64()))),
+    )?;
+
+    let label_style = ("sans-serif", 12).into_font().color(&BLACK).pos(Pos::new(HPos::Center, VPos::Bottom));
+    chart.draw_series(bars.iter().enumerate().map(|(index, bar)| {
+        let position = (SegmentValue::CenterOf(index as u32 + 1), bar.split.as_secs_f64());
+        Text::new(bar.label.clone(), position, label_style.clone())
+    }))?;
+
+    root.present()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // テストごとに別の一時ファイルに書き出す
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("simple_timer-{}-{}.png", name, std::process::id()))
+    }
+
+    #[test]
+    fn renders_the_lap_chart_to_the_given_path() {
+        let path = temp_path("chart");
+        assert!(render_lap_chart(&[], &path).is_err());
+
+        let bars = vec![
+            LapBar { split: Duration::from_millis(10_400), label: String::from("10"), note: String::from("fell") },
+            LapBar { split: Duration::from_millis(9_600), label: String::from("10"), note: String::new() },
+        ];
+        render_lap_chart(&bars, &path).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn renders_the_summary_card_to_the_given_path() {
+        let path = temp_path("card");
+        let summary = SessionSummary {
+            label: String::from("Study"),
+            total: String::from("01:02:03.00"),
+            date: String::from("2026-10-14 09:00"),
+            // 載せきれない分は件数のみを表示する
+            laps: (1..=CARD_LAPS + 2).map(|number| format!("Lap {}  00:10:00.00  (+00:10:00.00)", number)).collect(),
+            stats: Some(String::from("Avg 00:10:00.00  Best 00:10:00.00  Worst 00:10:00.00")),
+        };
+        render_summary_card(&summary, &path).unwrap();
+        assert!(std::fs::metadata(&path).unwrap().len() > 0);
+        std::fs::remove_file(&path).unwrap();
+
+        // 保存できない場所を指定した場合はエラーを返す
+        let missing = std::env::temp_dir().join("simple_timer-missing-dir").join("card.png");
+        assert!(render_summary_card(&summary, &missing).is_err());
+    }
+}
@@ -0,0 +1,45 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+// 表示を書き直す間隔。1/100秒の桁が滑らかに変わって見える程度に短くする
+const REDRAW_INTERVAL: Duration = Duration::from_millis(10);
+const BELL: &str = "\x07";
+
+// GUIを使わずに端末でカウントダウンする場合の指定
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub length: Duration,
+    // 残り時間の表示を出さず、終了時のベルのみ鳴らす
+    pub quiet: bool,
+}
+
+// 終了時刻までの残り時間を、短い間隔で計算し直しながら1行で書き換えて表示する
+// 表示の更新に時間がかかっても、残り時間は毎回終了時刻から求めるためずれは蓄積しない
+pub fn run(options: Options) -> io::Result<()> {
+    let deadline = Instant::now() + options.length;
+    let mut stdout = io::stdout();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !options.quiet {
+            write!(stdout, "\r{}", format_remaining(remaining))?;
+            stdout.flush()?;
+        }
+        if remaining == Duration::default() {
+            break;
+        }
+        thread::sleep(remaining.min(REDRAW_INTERVAL));
+    }
+
+    if !options.quiet {
+        writeln!(stdout)?;
+    }
+    write!(stdout, "{}", BELL)?;
+    stdout.flush()
+}
+
+// 残り時間を MM:SS.cc の形式にする。1時間以上の場合も分の桁を増やして表す
+fn format_remaining(remaining: Duration) -> String {
+    let seconds = remaining.as_secs();
+    format!("{:02}:{:02}.{:02}", seconds / 60, seconds % 60, remaining.subsec_millis() / 10)
+}
@@ -0,0 +1,229 @@
+#[cfg(all(feature = "debug-clock", debug_assertions))]
+use std::cell::Cell;
+#[cfg(all(feature = "debug-clock", debug_assertions))]
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime};
+
+// 壁時計のずれをジャンプとみなすまでの許容量
+const JUMP_TOLERANCE: Duration = Duration::from_secs(2);
+
+// 時刻の取得元を抽象化するトレイト。差し替えることで任意の時刻を返せるようにする
+pub trait Clock {
+    // 単調増加する時刻。ストップウォッチの計測はこちらのみを使う
+    fn now(&self) -> Instant;
+
+    // 壁時計の時刻。アラームや終了予定時刻など、実際の時刻に紐づく機能で使う
+    fn system_now(&self) -> SystemTime;
+
+    // 壁時計の時刻をローカルの日時にしたもの。現在時刻の表示や日付の判定には直接 Local::now() を使わずにこちらを使う
+    #[cfg(feature = "chrono")]
+    fn now_local(&self) -> chrono::DateTime<chrono::Local> {
+        chrono::DateTime::from(self.system_now())
+    }
+}
+
+// OSの時計をそのまま使う実装
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        SystemTime::now()
+    }
+}
+
+// 開発時にスケジュールの切り替わりを待たずに確かめるため、時計を手動でずらす操作
+// debug-clock フィーチャーを有効にしたデバッグビルドでのみずらすことができ、それ以外では OS の時計をそのまま使う
+#[derive(Clone, Default)]
+pub struct ClockShift {
+    #[cfg(all(feature = "debug-clock", debug_assertions))]
+    offset: Rc<Cell<Offset>>,
+}
+
+// 手動でずらした量。単調増加する時刻は戻せないため、戻す操作は壁時計にのみ適用する
+#[cfg(all(feature = "debug-clock", debug_assertions))]
+#[derive(Debug, Clone, Copy, Default)]
+struct Offset {
+    forward: Duration,
+    rewound: Duration,
+}
+
+impl ClockShift {
+    // この操作でずらせる時計
+    pub fn clock(&self) -> Box<dyn Clock> {
+        #[cfg(all(feature = "debug-clock", debug_assertions))]
+        {
+            Box::new(ShiftedClock { offset: self.offset.clone() })
+        }
+        #[cfg(not(all(feature = "debug-clock", debug_assertions)))]
+        {
+            Box::new(SystemClock)
+        }
+    }
+
+    // 時計を step だけ進める。backwards の場合は壁時計を step だけ戻す
+    #[cfg(all(feature = "debug-clock", debug_assertions))]
+    pub fn shift(&self, step: Duration, backwards: bool) {
+        let mut offset = self.offset.get();
+        if backwards {
+            offset.rewound += step;
+        } else {
+            offset.forward += step;
+        }
+        self.offset.set(offset);
+    }
+}
+
+// OSの時計を手動でずらした量だけずらして返す実装
+#[cfg(all(feature = "debug-clock", debug_assertions))]
+struct ShiftedClock {
+    offset: Rc<Cell<Offset>>,
+}
+
+#[cfg(all(feature = "debug-clock", debug_assertions))]
+impl Clock for ShiftedClock {
+    fn now(&self) -> Instant {
+        Instant::now() + self.offset.get().forward
+    }
+
+    fn system_now(&self) -> SystemTime {
+        let offset = self.offset.get();
+        SystemTime::now() + offset.forward - offset.rewound
+    }
+}
+
+// 検出した壁時計のジャンプの向きと量
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClockJump {
+    Forward(Duration),
+    Backward(Duration),
+}
+
+impl ClockJump {
+    // ジャンプする前の壁時計で記録した時刻を、ジャンプした後の壁時計での時刻に直す
+    pub fn shift(self, time: SystemTime) -> SystemTime {
+        match self {
+            ClockJump::Forward(amount) => time.checked_add(amount),
+            ClockJump::Backward(amount) => time.checked_sub(amount),
+        }
+        .unwrap_or(time)
+    }
+}
+
+// サスペンドからの復帰やNTPによる補正で壁時計が飛んだことを検出するための構造体
+//
+// 前回の観測から Instant が進んだ量と壁時計が進んだ量を比べ、その差が許容量を超えたらジャンプとみなす。
+// Instant は単調増加なので、ジャンプが起きてもストップウォッチの計測値には影響しない。
+pub struct JumpDetector {
+    last_instant: Instant,
+    last_system: SystemTime,
+}
+
+impl JumpDetector {
+    pub fn new(clock: &dyn Clock) -> JumpDetector {
+        JumpDetector {
+            last_instant: clock.now(),
+            last_system: clock.system_now(),
+        }
+    }
+
+    // 現在時刻を観測し、前回の観測からジャンプがあればその内容を返す
+    pub fn check(&mut self, clock: &dyn Clock) -> Option<ClockJump> {
+        let now_instant = clock.now();
+        let now_system = clock.system_now();
+
+        let monotonic = now_instant - self.last_instant;
+        let jump = match now_system.duration_since(self.last_system) {
+            // 壁時計が Instant より大きく進んだ場合は前方へのジャンプ
+            Ok(wall) if wall > monotonic + JUMP_TOLERANCE => {
+                Some(ClockJump::Forward(wall - monotonic))
+            },
+            // 壁時計の進みが Instant より大きく遅れた場合は後方へのジャンプ
+            Ok(wall) if wall + JUMP_TOLERANCE < monotonic => {
+                Some(ClockJump::Backward(monotonic - wall))
+            },
+            Ok(_) => None,
+            // 壁時計が前回の観測より前に戻った場合も後方へのジャンプ
+            Err(err) => Some(ClockJump::Backward(monotonic + err.duration())),
+        };
+
+        self.last_instant = now_instant;
+        self.last_system = now_system;
+
+        jump
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    // Instant と壁時計を別々に進められる時計
+    struct StepClock {
+        start: Instant,
+        elapsed: Cell<Duration>,
+        wall: Cell<SystemTime>,
+    }
+
+    impl StepClock {
+        fn new() -> StepClock {
+            StepClock {
+                start: Instant::now(),
+                elapsed: Cell::new(Duration::default()),
+                wall: Cell::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+            }
+        }
+
+        fn advance(&self, monotonic: Duration, wall: Duration) {
+            self.elapsed.set(self.elapsed.get() + monotonic);
+            self.wall.set(self.wall.get() + wall);
+        }
+    }
+
+    impl Clock for StepClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+
+        fn system_now(&self) -> SystemTime {
+            self.wall.get()
+        }
+    }
+
+    #[test]
+    fn ignores_drift_within_the_tolerance() {
+        let clock = StepClock::new();
+        let mut detector = JumpDetector::new(&clock);
+        clock.advance(Duration::from_secs(10), Duration::from_secs(11));
+        assert_eq!(detector.check(&clock), None);
+        clock.advance(Duration::from_secs(10), Duration::from_secs(9));
+        assert_eq!(detector.check(&clock), None);
+    }
+
+    #[test]
+    fn detects_forward_and_backward_steps() {
+        let clock = StepClock::new();
+        let mut detector = JumpDetector::new(&clock);
+
+        // サスペンドからの復帰などで、壁時計だけが大きく進んだ場合
+        clock.advance(Duration::from_secs(1), Duration::from_secs(3601));
+        assert_eq!(detector.check(&clock), Some(ClockJump::Forward(Duration::from_secs(3600))));
+
+        // 壁時計の進みが Instant より遅れた場合
+        clock.advance(Duration::from_secs(10), Duration::from_secs(5));
+        assert_eq!(detector.check(&clock), Some(ClockJump::Backward(Duration::from_secs(5))));
+
+        // 壁時計が前回の観測より前に戻った場合
+        clock.advance(Duration::from_secs(1), Duration::default());
+        clock.wall.set(clock.wall.get() - Duration::from_secs(60));
+        assert_eq!(detector.check(&clock), Some(ClockJump::Backward(Duration::from_secs(61))));
+
+        // ジャンプの後は、新しい時刻を基準に判定し直す
+        clock.advance(Duration::from_secs(1), Duration::from_secs(1));
+        assert_eq!(detector.check(&clock), None);
+    }
+}
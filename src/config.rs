@@ -0,0 +1,441 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use iced::Color;
+use serde::{de, Deserialize, Deserializer};
+
+use crate::ambient::AmbientDisplay;
+use crate::banner::BannerConfig;
+use crate::chain::ChainConfig;
+use crate::countdown::{ResetTarget, ResumeConfig};
+use crate::format::{deserialize_duration, parse_duration, FormatOptions, Rounding};
+use crate::interval::{IntervalPlan, PomodoroPlan};
+use crate::layout::{ButtonLayout, ButtonSize};
+use crate::lead_in::LeadInConfig;
+use crate::mode::{Mode, StopAction};
+use crate::notify::ReminderConfig;
+use crate::preset::FormatPreset;
+use crate::refresh::RefreshStrategy;
+use crate::sound::{ActionSound, BeepSound};
+use crate::style::{parse_hex_color, Theme};
+use crate::talk::TalkConfig;
+
+// 既定値に戻した際に書き込む内容。書かれていない項目はすべて既定値になる
+const RESET_TEXT: &str = "# simple_timer の設定。書かれていない項目は既定値になります(項目は README の「設定」を参照)\n";
+
+// 間隔と余白の上限。これを超えるとウィンドウに要素が収まらなくなる
+const MAX_SPACING: u16 = 40;
+const MAX_PADDING: u16 = 60;
+
+// アプリケーションの設定値をまとめた構造体
+// 設定ファイルに書かれていない項目はデフォルト値を使う
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    // タイマーに付けるラベル
+    pub label: String,
+    // ウィンドウタイトルのテンプレート。{label}と{time}がそれぞれ置き換えられる
+    pub title_format: String,
+    // 計測中にウィンドウタイトルの先頭に付ける印。空の場合は付けない
+    pub running_marker: String,
+    // 経過時間を数字ではなく英語の文章で表示するか否か
+    pub in_words: bool,
+    // 1分未満の間、ストップウォッチの秒を大きく、1/10秒を小さく表示するか否か
+    pub racing: bool,
+    // 経過時間と時刻の表示の文字サイズを、ウィンドウの幅に合わせて自動で決めるか否か
+    pub auto_fit: bool,
+    // 自動で合わせる場合の文字サイズの上限
+    pub auto_fit_max: u16,
+    // 経過時間の表示形式(丸め方など)
+    pub format: FormatOptions,
+    // 表示形式のプリセット。custom 以外の場合は format と in_words より優先する
+    pub format_preset: FormatPreset,
+    // ラップの一覧の丸め方。指定しない場合は経過時間の表示と同じ丸め方にする
+    pub lap_rounding: Option<Rounding>,
+    // 計測中に画面のスリープを抑止するか否か(keep-awake フィーチャーが必要)
+    pub keep_awake: bool,
+    // ストップウォッチの目標時間。設定すると進捗バーが表示される
+    #[serde(deserialize_with = "deserialize_optional_duration")]
+    pub target: Option<Duration>,
+    // ストップウォッチがこの時間に達すると自動で停止する。0の場合は停止しない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub max_duration: Duration,
+    // ストップウォッチを0より前のこの時間から数え始める。0の場合は0から数える
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub start_offset: Duration,
+    // Resetボタンをこの時間以上押し続けた場合は、履歴も削除するか確認する
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub reset_hold: Duration,
+    // カウントダウンの終了後、自動でリセットするまでの時間。0の場合は自動リセットしない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub auto_reset_delay: Duration,
+    // カウントダウンやインターバルの区間が終わった後、次の動作に移るまで終了した状態を表示し続ける時間
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub completion_hold: Duration,
+    // カウントダウンのモードでリセットした際に、設定した長さ(full)と0(zero)のどちらに戻すか
+    pub countdown_reset: ResetTarget,
+    // ストップウォッチのモードで Stop ボタンを押した際に、一時停止する(pause)か計測を終えて記録する(finish)か
+    pub stop_action: StopAction,
+    // カウントダウンの残り時間に加えて経過時間も表示するか否か
+    pub split_view: bool,
+    // カウントダウンの残り時間がこれらの値を下回った際に、警告音を鳴らして表示を点滅させる
+    #[serde(deserialize_with = "deserialize_durations")]
+    pub warning_thresholds: Vec<Duration>,
+    // ストップウォッチの経過時間がそれぞれの時間に達した以降に使う、経過時間の文字色(時間の昇順)
+    #[serde(deserialize_with = "deserialize_heat")]
+    pub heat: Vec<(Duration, Color)>,
+    // 起動時の配色("light"・"dark"・"high_contrast"・"solarized"・"nord"・"mono"・"custom" のいずれか)
+    pub theme: Theme,
+    // Import ボタンで読み込むカウントダウンの一覧のファイル。相対パスは設定ファイルのディレクトリからの位置とみなす
+    pub timers_file: PathBuf,
+    // theme = "custom" で使う配色ファイル。相対パスは設定ファイルのディレクトリからの位置とみなす
+    pub theme_file: Option<PathBuf>,
+    // 画面の文字に使うフォント(.ttf・.otf)。相対パスは設定ファイルのディレクトリからの位置とみなし、省略した場合は同梱のフォントを使う
+    pub font_file: Option<PathBuf>,
+    // 起動時のウィンドウの大きさ([幅, 高さ])。省略した場合は通常の表示かタッチ操作向けの表示に合わせた大きさにする
+    pub window_size: Option<(u32, u32)>,
+    // Export CSV ボタンで履歴を書き出すファイル。省略した場合は書類のディレクトリに日時付きの名前で書き出す
+    pub history_export: Option<PathBuf>,
+    // ストップウォッチの画面に表示する最近の履歴の件数。0の場合は表示しない
+    pub recent_sessions: usize,
+    // すべてのタイマーを一時停止・再開するショートカットキー(hotkey フィーチャーが必要)
+    pub hotkey: String,
+    // ストップウォッチの計測中にラップを記録するキー。空の場合は割り当てない
+    pub lap_key: String,
+    // 計測をリセットするキー。空の場合は割り当てない
+    pub reset_key: String,
+    // ストップウォッチの経過時間がこの間隔の倍数に達するたびに、自動でラップを記録する。0の場合は記録しない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub auto_lap: Duration,
+    // gRPCのサーバーが待ち受けるアドレス(grpc フィーチャーが必要)
+    pub grpc_address: String,
+    // 計測していない状態で操作がないまま、この時間が経つと大きな時計の表示に切り替える。0の場合は切り替えない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub ambient_after: Duration,
+    // 大きな表示に切り替えた際に、現在時刻と経過時間のどちらを表示するか
+    pub ambient_display: AmbientDisplay,
+    // 操作ボタンを横と縦のどちらに並べるか
+    pub button_layout: ButtonLayout,
+    // 操作ボタンの最小の幅(ピクセル)
+    pub button_min_width: u32,
+    // タッチパネルで操作しやすいよう、ボタンと文字を大きくし、ウィンドウも大きくするか否か
+    pub touch_mode: bool,
+    // 画面の要素どうしの間隔と、ウィンドウの縁との余白(ピクセル)
+    spacing: u16,
+    padding: u16,
+    // 表示を更新する頻度の方針
+    pub refresh: RefreshStrategy,
+    // すべての効果音とカウントダウンの終了の通知を出さないか否か。カウントダウンごとの設定より優先する
+    pub mute: bool,
+    // ストップウォッチの計測中に音を鳴らす間隔。0の場合は鳴らさない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub beep_interval: Duration,
+    // 一定間隔で鳴らす音の種類
+    pub beep_sound: BeepSound,
+    // ストップウォッチを開始・停止した際に鳴らす音
+    pub start_sound: ActionSound,
+    pub stop_sound: ActionSound,
+    // ストップウォッチの計測中に1秒ごとに鳴らす、機械式の時計のような音
+    pub tick_sound: ActionSound,
+    // カウントダウンの残り時間が分の区切りを下回るごとに鳴らす音。警告音と同時の場合は鳴らさない
+    pub minute_chime: ActionSound,
+    // 起動時のモード。画面で切り替えると最後に使ったモードが書き込まれる
+    pub mode: Mode,
+    // 経過時間を数える起点の日時(RFC 3339 の形式)。画面で設定すると書き込まれる
+    pub since: Option<String>,
+    // インターバルモードの運動・休憩の長さとラウンド数
+    pub interval: IntervalPlan,
+    // インターバルのモードで Pomodoro を選んだ際の作業・休憩の長さと回数
+    pub pomodoro: PomodoroPlan,
+    // インターバルで運動が終わった際に、自動で休憩へ進まず、休憩を始めるか飛ばすかを選ぶまで待つか否か
+    pub manual_phase_advance: bool,
+    // カウントダウンやインターバルを始める前の秒読み
+    pub lead_in: LeadInConfig,
+    // 発表のモードの設定
+    pub talk: TalkConfig,
+    // カウントダウンやインターバルの終了を、確認するまで表示し続ける設定
+    pub finished_banner: BannerConfig,
+    // 読み込んだカウントダウンの一覧を、終わるたびに次のものを自動で始める設定
+    pub chain: ChainConfig,
+    // ストップウォッチの計測中に、止め忘れていないか一定間隔で知らせる設定
+    pub reminder: ReminderConfig,
+    // 前回の終了時に終わっていなかったカウントダウンを、起動時に復元するか否か
+    pub resume_countdowns: ResumeConfig,
+    // 前回の終了時のストップウォッチの計測を、起動時に続きから再開できるよう復元するか否か
+    pub resume_stopwatch: ResumeConfig,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            label: String::from("DEMO"),
+            title_format: String::from("{label} — {time}"),
+            running_marker: String::from("● "),
+            in_words: false,
+            racing: false,
+            auto_fit: false,
+            auto_fit_max: 160,
+            format: FormatOptions::default(),
+            format_preset: FormatPreset::Custom,
+            lap_rounding: None,
+            keep_awake: false,
+            target: None,
+            max_duration: Duration::default(),
+            start_offset: Duration::default(),
+            reset_hold: Duration::from_secs(2),
+            auto_reset_delay: Duration::default(),
+            completion_hold: Duration::from_secs(1),
+            manual_phase_advance: false,
+            countdown_reset: ResetTarget::Full,
+            stop_action: StopAction::Pause,
+            split_view: false,
+            warning_thresholds: Vec::new(),
+            heat: Vec::new(),
+            theme: Theme::Light,
+            theme_file: None,
+            font_file: None,
+            window_size: None,
+            history_export: None,
+            recent_sessions: 3,
+            timers_file: PathBuf::from("timers.json"),
+            hotkey: String::from("Ctrl+Shift+P"),
+            lap_key: String::from("L"),
+            reset_key: String::from("R"),
+            auto_lap: Duration::default(),
+            grpc_address: String::from("127.0.0.1:50051"),
+            ambient_after: Duration::default(),
+            ambient_display: AmbientDisplay::Clock,
+            button_layout: ButtonLayout::Horizontal,
+            button_min_width: 80,
+            touch_mode: false,
+            spacing: 10,
+            padding: 10,
+            refresh: RefreshStrategy::HighPrecision,
+            mute: false,
+            beep_interval: Duration::default(),
+            beep_sound: BeepSound::Tick,
+            start_sound: ActionSound::default(),
+            stop_sound: ActionSound::default(),
+            tick_sound: ActionSound::default(),
+            minute_chime: ActionSound::default(),
+            mode: Mode::Stopwatch,
+            since: None,
+            interval: IntervalPlan::default(),
+            pomodoro: PomodoroPlan::default(),
+            lead_in: LeadInConfig::default(),
+            talk: TalkConfig::default(),
+            finished_banner: BannerConfig::default(),
+            chain: ChainConfig::default(),
+            reminder: ReminderConfig::default(),
+            resume_countdowns: ResumeConfig::default(),
+            resume_stopwatch: ResumeConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    // 設定ファイルのパス(~/.config/simple_timer/config.toml など、OSごとの設定ディレクトリ配下)
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join("simple_timer").join("config.toml"))
+    }
+
+    // 要素どうしの間隔。大きすぎる値はウィンドウに収まるよう上限に抑える
+    pub fn spacing(&self) -> u16 {
+        self.spacing.min(MAX_SPACING)
+    }
+
+    // ウィンドウの縁との余白。大きすぎる値はウィンドウに収まるよう上限に抑える
+    pub fn padding(&self) -> u16 {
+        self.padding.min(MAX_PADDING)
+    }
+
+    // 操作ボタンの大きさ
+    pub fn button_size(&self) -> ButtonSize {
+        ButtonSize::new(self.button_min_width, self.touch_mode)
+    }
+
+    // 配色ファイルのパス
+    pub fn theme_path(&self) -> Option<PathBuf> {
+        self.theme_file.as_deref().map(Config::resolve)
+    }
+
+    // 画面の文字に使うフォントのパス。指定していない場合はNone
+    pub fn font_path(&self) -> Option<PathBuf> {
+        self.font_file.as_deref().map(Config::resolve)
+    }
+
+    // 履歴の書き出し先のパス。指定していない場合はNone
+    pub fn history_export_path(&self) -> Option<PathBuf> {
+        self.history_export.as_deref().map(Config::resolve)
+    }
+
+    // カウントダウンの一覧のファイルのパス
+    pub fn timers_path(&self) -> PathBuf {
+        Config::resolve(&self.timers_file)
+    }
+
+    // 相対パスを設定ファイルのディレクトリからの位置として解決する
+    fn resolve(file: &Path) -> PathBuf {
+        match Config::path().as_ref().and_then(|path| path.parent()) {
+            Some(dir) if file.is_relative() => dir.join(file),
+            _ => file.to_path_buf(),
+        }
+    }
+
+    // 設定ファイルを読み込む。ファイルがない場合や読み込めない場合はデフォルト値を使う
+    pub fn load() -> Config {
+        let path = match Config::path() {
+            Some(path) => path,
+            None => return Config::default(),
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => return Config::default(),
+        };
+
+        match toml::from_str(&text) {
+            Ok(config) => {
+                log::info!("loaded config from {}", path.display());
+                config
+            },
+            Err(err) => {
+                log::warn!("failed to parse {}: {}", path.display(), err);
+                Config::default()
+            },
+        }
+    }
+
+    // 不具合の報告用に、設定ファイルに書かれた項目を返す。ファイルの位置が分かる項目は除く
+    pub fn dump() -> toml::value::Table {
+        let mut table = Config::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .and_then(|text| match text.parse::<toml::Value>() {
+                Ok(toml::Value::Table(table)) => Some(table),
+                _ => None,
+            })
+            .unwrap_or_default();
+        for key in &["theme_file", "timers_file", "font_file", "history_export"] {
+            table.remove(*key);
+        }
+        table
+    }
+
+    // 設定ファイルを既定値に戻す。元のファイルは同じディレクトリの config.toml.bak に残し、その位置を返す
+    // 元のファイルがなかった場合はバックアップを作らずに None を返す
+    pub fn reset() -> Result<Option<PathBuf>, String> {
+        let path = Config::path().ok_or_else(|| String::from("no configuration directory"))?;
+        let backup = path.with_extension("toml.bak");
+        let backed_up = if path.exists() {
+            fs::copy(&path, &backup).map_err(|err| format!("failed to back up {}: {}", path.display(), err))?;
+            Some(backup)
+        } else {
+            None
+        };
+
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, RESET_TEXT))
+            .map_err(|err| format!("failed to write {}: {}", path.display(), err))?;
+        log::info!("reset {} to the defaults", path.display());
+        Ok(backed_up)
+    }
+
+    // 設定ファイルの1項目だけを書き換える。ほかの項目の値はそのまま残す
+    // (書き戻す際にコメントや項目の並びは保持されない)
+    pub fn store(key: &str, value: toml::Value) {
+        let path = match Config::path() {
+            Some(path) => path,
+            None => return,
+        };
+
+        let mut table = match fs::read_to_string(&path) {
+            Ok(text) => match text.parse::<toml::Value>() {
+                Ok(toml::Value::Table(table)) => table,
+                _ => {
+                    log::warn!("not overwriting unparsable {}", path.display());
+                    return;
+                },
+            },
+            Err(_) => toml::value::Table::new(),
+        };
+        table.insert(key.to_string(), value);
+
+        let result = toml::to_string(&table)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                path.parent()
+                    .map_or(Ok(()), fs::create_dir_all)
+                    .and_then(|_| fs::write(&path, text))
+                    .map_err(|err| err.to_string())
+            });
+        match result {
+            Ok(()) => log::debug!("stored {} in {}", key, path.display()),
+            Err(err) => log::warn!("failed to write {}: {}", path.display(), err),
+        }
+    }
+}
+
+fn deserialize_optional_duration<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserialize_duration(deserializer).map(Some)
+}
+
+fn deserialize_durations<'de, D>(deserializer: D) -> Result<Vec<Duration>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|text| {
+            parse_duration(text).ok_or_else(|| de::Error::custom(format!("invalid duration: {}", text)))
+        })
+        .collect()
+}
+
+// 経過時間の文字色の1段階分
+#[derive(Deserialize)]
+struct HeatStep {
+    from: String,
+    color: String,
+}
+
+// 書かれた順によらず判定できるよう、時間の昇順に並べ替えて読み込む
+fn deserialize_heat<'de, D>(deserializer: D) -> Result<Vec<(Duration, Color)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut steps = Vec::<HeatStep>::deserialize(deserializer)?
+        .iter()
+        .map(|step| {
+            let from = parse_duration(&step.from)
+                .ok_or_else(|| de::Error::custom(format!("invalid duration: {}", step.from)))?;
+            let color = parse_hex_color(&step.color).map_err(de::Error::custom)?;
+            Ok((from, color))
+        })
+        .collect::<Result<Vec<_>, D::Error>>()?;
+    steps.sort_by_key(|&(from, _)| from);
+    Ok(steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_heat_steps_in_time_order() {
+        let config: Config = toml::from_str(
+            "heat = [{ from = \"45m\", color = \"#C62828\" }, { from = \"0s\", color = \"#2E7D32\" }, { from = \"25m\", color = \"#F9A825\" }]",
+        )
+        .unwrap();
+        let froms: Vec<u64> = config.heat.iter().map(|(from, _)| from.as_secs() / 60).collect();
+        assert_eq!(froms, vec![0, 25, 45]);
+        assert_eq!(config.heat[2].1, parse_hex_color("#C62828").unwrap());
+
+        assert!(toml::from_str::<Config>("heat = [{ from = \"soon\", color = \"#C62828\" }]").is_err());
+        assert!(toml::from_str::<Config>("heat = [{ from = \"5m\", color = \"red\" }]").is_err());
+    }
+}
@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+use async_std::channel::{self, Receiver, Sender};
+use iced_futures::futures;
+
+// アプリケーションの外部(メニューバーなど)から送られる操作
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ControlCommand {
+    Start,
+    Stop,
+    Reset,
+    RestartCountdown(usize), // 終了したカウントダウンを最初からやり直す
+    AddCountdown { label: String, length: Duration }, // カウントダウンを追加して開始する
+    TogglePauseAll, // 動作中のタイマーをすべて一時停止する。一時停止中であれば、止めたものを再開する
+    Quit, // 計測の状態を保存・記録してアプリケーションを終了する
+}
+
+// 外部からの操作を受け渡すためのチャンネルを作成
+pub fn channel() -> (Sender<ControlCommand>, Receiver<ControlCommand>) {
+    channel::unbounded()
+}
+
+// チャンネルに届いた操作を、Subscriptionのイベントとして出力するためのRecipe
+pub struct Controls {
+    receiver: Receiver<ControlCommand>,
+}
+
+impl Controls {
+    pub fn new(receiver: Receiver<ControlCommand>) -> Controls {
+        Controls { receiver }
+    }
+}
+
+impl<H, E> iced_native::subscription::Recipe<H, E> for Controls where H: std::hash::Hasher {
+    type Output = ControlCommand;
+
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        // 受信側は常に1つなので、型のみでSubscriptionを識別する
+        std::any::TypeId::of::<Self>().hash(state);
+    }
+
+    fn stream(self: Box<Self>, _input: futures::stream::BoxStream<E>)
+        -> futures::stream::BoxStream<Self::Output> {
+            use futures::stream::StreamExt;
+
+            self.receiver.boxed()
+    }
+}
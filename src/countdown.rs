@@ -0,0 +1,296 @@
+use std::cmp::Reverse;
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Deserialize;
+
+use crate::clock::{Clock, ClockJump};
+use crate::stopwatch::Stopwatch;
+
+// 警告時に表示を点滅させるティック数
+const FLASH_TICKS: u64 = 15;
+
+const MINUTE_NANOS: u128 = 60_000_000_000;
+
+// 経過時間を進めた結果として起きた出来事
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountdownEvent {
+    Warning,  // 残り時間が警告の閾値を下回った
+    Minute,   // 残り時間が分の区切りに達した
+    Finished, // 残り時間が0になった
+}
+
+// カウントダウンのモードでリセットした際の戻し先
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResetTarget {
+    Full, // 設定した長さに戻し、すぐに開始できる状態にする
+    Zero, // 残り時間を0にした、終了と同じ状態にする
+}
+
+// 前回の終了時のカウントダウンを起動時に復元する設定
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct ResumeConfig {
+    pub enabled: bool,
+    // 終了時に動いていたものを、復元と同時に再開するか否か
+    pub auto_resume: bool,
+    // 終了していた間も動いていたものとして、その分だけ残り時間を減らすか否か
+    pub count_while_closed: bool,
+}
+
+// 同時に複数動かせるカウントダウンの1件分
+pub struct Countdown {
+    pub id: usize,
+    pub label: String,
+    length: Duration,
+    // 開始してからの経過時間を数える計時。ストップウォッチと同じものを使う
+    stopwatch: Stopwatch,
+    // 終了した時刻。終了していない場合と、自動リセットの時期を知らせた後はNone
+    finished_at: Option<Instant>,
+    // 警告による点滅の残りティック数
+    flash_ticks: u64,
+    // 警告音と終了の通知を出さないか否か
+    muted: bool,
+}
+
+impl Countdown {
+    // 作成と同時にカウントダウンを開始する
+    pub fn new(id: usize, label: String, length: Duration, clock: &dyn Clock) -> Countdown {
+        let mut stopwatch = Stopwatch::new(clock);
+        stopwatch.start(clock);
+        Countdown {
+            id,
+            label,
+            length,
+            stopwatch,
+            finished_at: None,
+            flash_ticks: 0,
+            muted: false,
+        }
+    }
+
+    pub fn length(&self) -> Duration {
+        self.length
+    }
+
+    // 開始してからの経過時間
+    pub fn elapsed(&self) -> Duration {
+        self.stopwatch.total_duration()
+    }
+
+    // 最初から開始した壁時計の時刻。一時停止・再開しても変わらず、リセットでNoneに戻す
+    pub fn started_at(&self) -> Option<SystemTime> {
+        self.stopwatch.started_at()
+    }
+
+    // 再開するまでに一時停止していた時間の合計
+    pub fn paused_duration(&self) -> Duration {
+        self.stopwatch.paused_duration()
+    }
+
+    // 壁時計が飛んだ際に、開始した壁時計の時刻を同じだけずらす
+    pub fn shift_wall_clock(&mut self, jump: ClockJump) {
+        self.stopwatch.shift_wall_clock(jump);
+    }
+
+    // 残り時間
+    pub fn remaining(&self) -> Duration {
+        self.length.checked_sub(self.elapsed()).unwrap_or_default()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.stopwatch.is_running()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.elapsed() >= self.length
+    }
+
+    // 停止と再開を切り替えた際に、最初から始めることになるか否か
+    pub fn starts_over(&self) -> bool {
+        self.is_finished() || (!self.is_running() && self.elapsed() == Duration::default())
+    }
+
+    pub fn is_muted(&self) -> bool {
+        self.muted
+    }
+
+    // 警告の直後に、残り時間の色を変えて点滅させる間の、色を変えるティックか否か
+    pub fn is_flashing(&self) -> bool {
+        self.flash_ticks % 2 == 1
+    }
+
+    pub fn toggle_mute(&mut self) {
+        self.muted = !self.muted;
+    }
+
+    // 停止と再開を切り替える。終了済みのものは最初からやり直す
+    pub fn toggle(&mut self, clock: &dyn Clock) {
+        if self.is_finished() {
+            self.reset(clock);
+            self.stopwatch.start(clock);
+        } else if self.is_running() {
+            self.stopwatch.pause(clock.now());
+        } else {
+            self.stopwatch.start(clock);
+        }
+    }
+
+    // 前回の終了時に保存した残り時間から再開できるよう、経過時間を設定する
+    pub fn set_remaining(&mut self, remaining: Duration, clock: &dyn Clock) {
+        self.stopwatch.set_elapsed(self.length.checked_sub(remaining).unwrap_or_default(), clock);
+    }
+
+    // 経過時間を0に戻し、停止した状態にする
+    pub fn reset(&mut self, clock: &dyn Clock) {
+        self.stopwatch.reset(clock);
+        self.finished_at = None;
+        self.flash_ticks = 0;
+    }
+
+    // 停止した状態にし、target にしたがって残り時間を設定した長さか0に戻す
+    pub fn reset_to(&mut self, target: ResetTarget, clock: &dyn Clock) {
+        self.reset(clock);
+        if target == ResetTarget::Zero {
+            self.stopwatch.set_elapsed(self.length, clock);
+        }
+    }
+
+    // 終了してから delay 以上経過したか否か。更新の間隔に関わらず時刻で判定し、終了1回につき1度だけ true を返す
+    pub fn take_auto_reset(&mut self, now: Instant, delay: Duration) -> bool {
+        match self.finished_at {
+            Some(finished_at) if now.saturating_duration_since(finished_at) >= delay => {
+                self.finished_at = None;
+                true
+            },
+            _ => false,
+        }
+    }
+
+    // 経過時間を進め、警告の閾値や分の区切りの通過、終了があればその出来事を起きた順に返す
+    // 閾値は残り時間が上から下へ通過したときのみ反応するため、1回の計測につき1度だけ警告される。
+    // 更新の間隔が空いて複数の閾値を一度に通過した場合も、閾値ごとに1回ずつ警告する
+    // 分の区切りも通過したものごとに1回ずつ知らせるが、同じ時刻に警告の閾値がある区切りは警告にまとめる
+    pub fn advance(&mut self, now: Instant, warning_thresholds: &[Duration]) -> Vec<CountdownEvent> {
+        self.flash_ticks = self.flash_ticks.saturating_sub(1);
+        if !self.is_running() {
+            return Vec::new();
+        }
+
+        let before = self.remaining();
+        self.stopwatch.advance(now);
+        let after = self.remaining();
+
+        // 残り0の閾値は終了と同時のため、終了の知らせにまとめる
+        let crossed: Vec<Duration> = warning_thresholds
+            .iter()
+            .copied()
+            .filter(|&threshold| threshold > Duration::default() && before > threshold && after <= threshold)
+            .collect();
+        if !crossed.is_empty() {
+            self.flash_ticks = FLASH_TICKS;
+        }
+
+        // 通過した時刻の順(残り時間の長い順)に並べる
+        let mut marks: Vec<(Duration, CountdownEvent)> =
+            crossed.iter().map(|&threshold| (threshold, CountdownEvent::Warning)).collect();
+        marks.extend(
+            minute_boundaries(before, after)
+                .into_iter()
+                .filter(|boundary| !crossed.contains(boundary))
+                .map(|boundary| (boundary, CountdownEvent::Minute)),
+        );
+        marks.sort_by_key(|&(remaining, _)| Reverse(remaining));
+        let mut events: Vec<CountdownEvent> = marks.into_iter().map(|(_, event)| event).collect();
+
+        if self.is_finished() {
+            self.stopwatch.pause(now);
+            self.finished_at = Some(now);
+            events.push(CountdownEvent::Finished);
+        }
+        events
+    }
+}
+
+// 残り時間が before から after に減った間に下回った分の区切り(before を含まず after を含む。残り0は除く)
+fn minute_boundaries(before: Duration, after: Duration) -> Vec<Duration> {
+    if after >= before {
+        return Vec::new();
+    }
+    let first = after.as_nanos().div_ceil(MINUTE_NANOS).max(1);
+    let last = (before.as_nanos() - 1) / MINUTE_NANOS;
+    (first..=last).map(|minutes| Duration::from_secs(minutes as u64 * 60)).collect()
+}
+
+// 動作中のカウントダウンのうち、次に終了するもの
+pub fn next_to_finish<'a>(countdowns: impl IntoIterator<Item = &'a Countdown>) -> Option<&'a Countdown> {
+    countdowns
+        .into_iter()
+        .filter(|countdown| countdown.is_running())
+        .min_by_key(|countdown| countdown.remaining())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::SystemTime;
+
+    use super::*;
+
+    // 常に同じ時刻を返す時計
+    struct FixedClock(Instant);
+
+    impl Clock for FixedClock {
+        fn now(&self) -> Instant {
+            self.0
+        }
+
+        fn system_now(&self) -> SystemTime {
+            SystemTime::UNIX_EPOCH
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn lists_each_minute_crossed_downward() {
+        assert_eq!(minute_boundaries(secs(200), secs(50)), vec![secs(60), secs(120), secs(180)]);
+        assert_eq!(minute_boundaries(secs(120), secs(60)), vec![secs(60)]);
+        assert_eq!(minute_boundaries(secs(59), secs(0)), vec![]);
+        assert_eq!(minute_boundaries(secs(60), secs(60)), vec![]);
+    }
+
+    #[test]
+    fn chimes_once_per_minute_and_merges_with_warnings() {
+        let start = Instant::now();
+        let mut countdown = Countdown::new(0, String::from("Tea"), secs(300), &FixedClock(start));
+
+        // 残り300秒から30秒まで一度に進めると、4分・3分・2分の区切りを順に知らせ、
+        // 1分ちょうどは警告の閾値と同じ時刻のため警告にまとめる
+        let events = countdown.advance(start + secs(270), &[secs(60), secs(30)]);
+        assert_eq!(events, vec![
+            CountdownEvent::Minute,
+            CountdownEvent::Minute,
+            CountdownEvent::Minute,
+            CountdownEvent::Warning,
+            CountdownEvent::Warning,
+        ]);
+
+        assert_eq!(countdown.advance(start + secs(300), &[secs(60), secs(30)]), vec![CountdownEvent::Finished]);
+    }
+
+
+    #[test]
+    fn auto_resets_by_the_time_since_finishing() {
+        let start = Instant::now();
+        let mut countdown = Countdown::new(0, String::from("Tea"), secs(60), &FixedClock(start));
+        assert!(!countdown.take_auto_reset(start + secs(120), secs(5)));
+
+        assert_eq!(countdown.advance(start + secs(61), &[]), vec![CountdownEvent::Finished]);
+        assert!(!countdown.take_auto_reset(start + secs(65), secs(5)));
+        // 更新の間隔が空いても、時刻が過ぎていれば1度だけ知らせる
+        assert!(countdown.take_auto_reset(start + secs(90), secs(5)));
+        assert!(!countdown.take_auto_reset(start + secs(91), secs(5)));
+    }
+}
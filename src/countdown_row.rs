@@ -0,0 +1,130 @@
+use std::ops::{Deref, DerefMut};
+
+use iced::{button, Align, Button, Column, Element, HorizontalAlignment, Length, Row, Text};
+
+use crate::countdown::Countdown;
+use crate::focus::Focus;
+use crate::format::{format_duration, FormatOptions};
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// 画面に並べるカウントダウンの1行。計時はライブラリの Countdown が行い、ここでは行のボタンの状態を持つ
+pub struct CountdownRow {
+    countdown: Countdown,
+    toggle_button_state: button::State,
+    mute_button_state: button::State,
+    remove_button_state: button::State,
+}
+
+impl CountdownRow {
+    pub fn new(countdown: Countdown) -> CountdownRow {
+        CountdownRow {
+            countdown,
+            toggle_button_state: button::State::new(),
+            mute_button_state: button::State::new(),
+            remove_button_state: button::State::new(),
+        }
+    }
+
+    pub fn countdown(&self) -> &Countdown {
+        &self.countdown
+    }
+
+    // split_view が有効な場合は、残り時間に加えて経過時間も2行で表示する
+    // focus はキーボードでフォーカスしているウィジェットで、この行のボタンであれば枠を強調する
+    pub fn view(
+        &mut self,
+        format: &FormatOptions,
+        split_view: bool,
+        focus: Option<Focus>,
+        theme: Theme,
+    ) -> Element<'_, Message> {
+        let is_finished = self.countdown.is_finished();
+        let remaining_text = if is_finished {
+            String::from("Done")
+        } else {
+            format_duration(self.countdown.remaining(), format)
+        };
+
+        // 警告の直後は残り時間の色を変えて点滅させる
+        let mut remaining_display = if split_view {
+            Text::new(format!("Remaining {}", remaining_text)).font(font())
+        } else {
+            Text::new(remaining_text).font(font())
+        };
+        if self.countdown.is_flashing() {
+            remaining_display = remaining_display.color(theme.flash());
+        }
+
+        let time_display: Element<'_, Message> = if split_view {
+            Column::new()
+                .push(Text::new(format!("Elapsed {}", format_duration(self.countdown.elapsed(), format))).font(font()))
+                .push(remaining_display)
+                .align_items(Align::End)
+                .into()
+        } else {
+            remaining_display.into()
+        };
+
+        let toggle_text = if is_finished {
+            "Restart"
+        } else if self.countdown.is_running() {
+            "Stop"
+        } else {
+            "Start"
+        };
+        let toggle_button = Button::new(
+            &mut self.toggle_button_state,
+            Text::new(toggle_text)
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font()),
+        )
+            .min_width(60)
+            .on_press(Message::ToggleCountdown(self.countdown.id))
+            .style(style::Button { theme, focused: focus == Some(Focus::CountdownToggle(self.countdown.id)) });
+
+        // 消音中は音符を控えめな色で表示する
+        let mut mute_text = Text::new("♪")
+            .horizontal_alignment(HorizontalAlignment::Center)
+            .font(font());
+        if self.countdown.is_muted() {
+            mute_text = mute_text.color(theme.subtle());
+        }
+        let mute_button = Button::new(&mut self.mute_button_state, mute_text)
+            .on_press(Message::ToggleCountdownMute(self.countdown.id))
+            .style(style::Button { theme, focused: focus == Some(Focus::CountdownMute(self.countdown.id)) });
+
+        let remove_button = Button::new(
+            &mut self.remove_button_state,
+            Text::new("×")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font()),
+        )
+            .on_press(Message::RemoveCountdown(self.countdown.id))
+            .style(style::Button { theme, focused: focus == Some(Focus::CountdownRemove(self.countdown.id)) });
+
+        Row::new()
+            .push(Text::new(self.countdown.label.clone()).font(font()).width(Length::Fill))
+            .push(time_display)
+            .push(toggle_button)
+            .push(mute_button)
+            .push(remove_button)
+            .spacing(10)
+            .align_items(Align::Center)
+            .into()
+    }
+}
+
+impl Deref for CountdownRow {
+    type Target = Countdown;
+
+    fn deref(&self) -> &Countdown {
+        &self.countdown
+    }
+}
+
+impl DerefMut for CountdownRow {
+    fn deref_mut(&mut self) -> &mut Countdown {
+        &mut self.countdown
+    }
+}
@@ -0,0 +1,138 @@
+use std::env;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+
+use crate::config::Config;
+use crate::format::parse_duration;
+use crate::mode::Mode;
+
+const COUNTDOWN: &str = "SIMPLE_TIMER_COUNTDOWN";
+const MODE: &str = "SIMPLE_TIMER_MODE";
+const THEME: &str = "SIMPLE_TIMER_THEME";
+const AUTOSTART: &str = "SIMPLE_TIMER_AUTOSTART";
+
+// 環境変数やコマンドライン引数で指定された、起動時に行う操作
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Startup {
+    // 指定された起動時のモード。復元したカウントダウンなどによるモードの切り替えより優先する
+    pub mode: Option<Mode>,
+    // 起動時に追加するカウントダウンの長さ
+    pub countdown: Option<Duration>,
+    // インターバルのモードで、設定ファイルのインターバルの代わりにポモドーロの設定を使うか否か
+    pub pomodoro: bool,
+    // 起動時のカウントダウン(指定がなければインターバルのモードではインターバル、それ以外ではストップウォッチ)をすぐに開始するか否か
+    pub autostart: bool,
+}
+
+// 環境変数の指定を設定ファイルの内容より優先して反映し、起動時に行う操作を返す
+// 解釈できない値は警告のログを出力して無視する
+pub fn apply(config: &mut Config) -> Startup {
+    let mode = read(MODE, from_name::<Mode>);
+    if let Some(mode) = mode {
+        config.mode = mode;
+    }
+    if let Some(theme) = read(THEME, from_name) {
+        config.theme = theme;
+    }
+
+    let countdown = read(COUNTDOWN, |text| parse_duration(text).filter(|length| *length > Duration::default()));
+    // カウントダウンを指定した場合は、モードの指定がなければカウントダウンの画面で起動する
+    let mode = match mode {
+        None if countdown.is_some() => Some(Mode::Countdown),
+        mode => mode,
+    };
+    if let Some(mode) = mode {
+        config.mode = mode;
+    }
+
+    let autostart = read(AUTOSTART, |text| match text.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    });
+
+    Startup {
+        mode,
+        countdown,
+        pomodoro: false,
+        autostart: autostart.unwrap_or(false),
+    }
+}
+
+// 環境変数を読み、parse で解釈する。未設定・空の場合は None を返す
+fn read<T, F: FnOnce(&str) -> Option<T>>(name: &str, parse: F) -> Option<T> {
+    let text = env::var(name).ok()?;
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    let value = parse(text);
+    if value.is_none() {
+        log::warn!("ignoring invalid {}={:?}", name, text);
+    }
+    value
+}
+
+// 設定ファイルと同じ名前("countdown"・"high_contrast" など)で列挙型の値を指定できるようにする
+fn from_name<T: DeserializeOwned>(text: &str) -> Option<T> {
+    toml::Value::String(text.to_ascii_lowercase()).try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::args::Args;
+    use crate::style::Theme;
+
+    // 環境変数はプロセス全体で共有するため、1つのテストの中で順に確かめる
+    #[test]
+    fn arguments_override_environment_overrides_config_file() {
+        let from_file = || toml::from_str::<Config>("mode = \"clock\"\ntheme = \"nord\"").unwrap();
+
+        // 環境変数がなければ設定ファイルの内容のまま
+        for name in &[MODE, THEME, COUNTDOWN, AUTOSTART] {
+            env::remove_var(name);
+        }
+        let mut config = from_file();
+        let startup = apply(&mut config);
+        assert_eq!((config.mode, config.theme), (Mode::Clock, Theme::Nord));
+        assert_eq!(startup.mode, None);
+        assert!(!startup.autostart);
+
+        // 環境変数は設定ファイルより優先し、解釈できない値は無視する
+        env::set_var(MODE, "Interval");
+        env::set_var(THEME, "purple");
+        env::set_var(AUTOSTART, "yes");
+        let mut config = from_file();
+        let mut startup = apply(&mut config);
+        assert_eq!((config.mode, config.theme), (Mode::Interval, Theme::Nord));
+        assert!(startup.autostart);
+
+        // コマンドライン引数は環境変数より優先する
+        let args = Args { countdown: Some(Duration::from_secs(90)), ..Args::default() };
+        args.apply(&mut config, &mut startup);
+        assert_eq!(config.mode, Mode::Countdown);
+        assert_eq!(startup.countdown, Some(Duration::from_secs(90)));
+
+        // カウントダウンの長さだけを指定した場合はカウントダウンの画面で起動する
+        env::remove_var(MODE);
+        env::set_var(THEME, "dark");
+        env::set_var(COUNTDOWN, "5m");
+        let mut config = from_file();
+        let startup = apply(&mut config);
+        assert_eq!((config.mode, config.theme), (Mode::Countdown, Theme::Dark));
+        assert_eq!(startup.countdown, Some(Duration::from_secs(300)));
+
+        // 0の長さは無視する
+        env::set_var(COUNTDOWN, "0s");
+        let mut config = from_file();
+        assert_eq!(apply(&mut config).countdown, None);
+        assert_eq!(config.mode, Mode::Clock);
+
+        for name in &[MODE, THEME, COUNTDOWN, AUTOSTART] {
+            env::remove_var(name);
+        }
+    }
+}
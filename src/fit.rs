@@ -0,0 +1,32 @@
+use ab_glyph::{Font as _, FontRef};
+
+use crate::FONT_BYTES;
+
+// 自動で合わせる文字サイズの下限
+const MIN_SIZE: u16 = 12;
+// フォントを解釈できない場合に使う、1文字あたりの幅の見積もり(文字サイズに対する割合)
+const FALLBACK_ADVANCE: f32 = 0.6;
+
+// text が width ピクセルの幅(左右の余白を除く)に収まる最大の文字サイズを求め、max で抑える
+// 文字の幅は同梱のフォントの送り幅から求める
+pub fn fit_size(text: &str, width: u32, padding: u16, max: u16) -> u16 {
+    let available = width.saturating_sub(u32::from(padding) * 2) as f32;
+    let ems = text_width_in_ems(text);
+    if ems <= 0.0 {
+        return max;
+    }
+
+    let size = (available / ems).floor();
+    (size.min(f32::from(max)) as u16).max(MIN_SIZE)
+}
+
+// 文字サイズを1としたときの文字列の幅
+fn text_width_in_ems(text: &str) -> f32 {
+    match FontRef::try_from_slice(FONT_BYTES) {
+        Ok(font) => {
+            let units_per_em = font.units_per_em().unwrap_or(1000.0);
+            text.chars().map(|c| font.h_advance_unscaled(font.glyph_id(c))).sum::<f32>() / units_per_em
+        },
+        Err(_) => text.chars().count() as f32 * FALLBACK_ADVANCE,
+    }
+}
@@ -0,0 +1,73 @@
+use crate::mode::Mode;
+
+// キーボードでフォーカスを移動できるウィジェット
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Focus {
+    Restore,
+    Discard,
+    Mode(Mode),
+    Tab(usize),
+    AddTab,
+    CloseTab,
+    StartStop,
+    Reset,
+    ClearHistory,
+    KeepHistory,
+    Lap,
+    LapNote,
+    ElapsedInput,
+    SetElapsed,
+    ExportChart,
+    ExportLaps,
+    Share,
+    ExportHistory,
+    Words,
+    Theme,
+    Refresh,
+    FormatPreset,
+    Repeat,
+    RestoreDefaults,
+    ConfirmDefaults,
+    CancelDefaults,
+    Quit,
+    SaveAndQuit,
+    QuitWithoutSaving,
+    CancelQuit,
+    CountdownToggle(usize),
+    CountdownMute(usize),
+    CountdownRemove(usize),
+    SkipLeadIn,
+    DismissBanner,
+    IntervalCycle(u32),
+    IntervalToggle,
+    IntervalReset,
+    StartBreak,
+    SkipBreak,
+    ManualAdvance,
+    Pomodoro,
+    SinceInput,
+    SetSince,
+    CountdownLabel,
+    CountdownLength,
+    AddCountdown,
+    ImportCountdowns,
+    MuteAll,
+}
+
+// 画面上の並び順にしたがって、フォーカスを次(backwardsの場合は前)のウィジェットへ移す
+// 現在どこにもフォーカスがない場合は、先頭(backwardsの場合は末尾)から始める
+pub fn next(order: &[Focus], current: Option<Focus>, backwards: bool) -> Option<Focus> {
+    if order.is_empty() {
+        return None;
+    }
+
+    let position = current.and_then(|focus| order.iter().position(|&f| f == focus));
+    let index = match (position, backwards) {
+        (None, false) => 0,
+        (None, true) => order.len() - 1,
+        (Some(position), false) => (position + 1) % order.len(),
+        (Some(position), true) => (position + order.len() - 1) % order.len(),
+    };
+
+    Some(order[index])
+}
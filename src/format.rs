@@ -0,0 +1,419 @@
+use std::time::Duration;
+
+use serde::{de, Deserialize, Deserializer};
+
+pub const MINUTE: u64 = 60;
+pub const HOUR: u64 = 60 * MINUTE;
+
+// 表示時の丸め方。保持している経過時間そのものは丸めず、表示する文字列にのみ適用する
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Rounding {
+    Off,    // 丸めずに precision で指定した桁まで表示する
+    Tenth,  // 1/10秒単位に四捨五入して表示する
+    Second, // 1秒単位に四捨五入して表示する
+}
+
+impl Rounding {
+    // 四捨五入となるよう、表示する最小単位の半分を足しておく(表示時に切り捨てられる)
+    fn apply(self, duration: Duration) -> Duration {
+        match self {
+            Rounding::Off => duration,
+            Rounding::Tenth => duration + Duration::from_millis(50),
+            Rounding::Second => duration + Duration::from_millis(500),
+        }
+    }
+}
+
+// 時・分・秒の欄の出し方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatStyle {
+    Fixed,     // 常に HH:MM:SS.cc の形で表示する
+    HideHours, // 1時間未満は時の欄のみを省き、05:07.35 と表示する
+    Adaptive,  // 先頭の0の欄を省き、1分未満は 7.35、1時間未満は 1:07.35 と表示する
+}
+
+// 秒と小数点以下の桁の区切り
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DecimalSeparator {
+    Dot,   // 01:07.35
+    Comma, // 01:07,35
+}
+
+impl DecimalSeparator {
+    fn as_char(self) -> char {
+        match self {
+            DecimalSeparator::Dot => '.',
+            DecimalSeparator::Comma => ',',
+        }
+    }
+}
+
+// 0より前の時間や超過した時間の、符号の付け方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignStyle {
+    Plus,        // +01:05 のように先頭に + を付ける
+    Minus,       // -01:05 のように先頭に - を付ける
+    Parentheses, // (01:05) のように括弧で囲む
+}
+
+// 経過時間の表示形式に関する設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct FormatOptions {
+    pub rounding: Rounding,
+    pub style: FormatStyle,
+    // 丸めない場合に表示する小数点以下の桁数(0〜3)
+    pub precision: u8,
+    // 秒と小数点以下の桁の区切り
+    pub decimal_separator: DecimalSeparator,
+    // 制限時間を超過した時間に付ける符号
+    pub overtime_sign: SignStyle,
+}
+
+impl FormatOptions {
+    // 実際に表示する小数点以下の桁数
+    pub fn digits(&self) -> u32 {
+        match self.rounding {
+            Rounding::Off => u32::from(self.precision.min(3)),
+            Rounding::Tenth => 1,
+            Rounding::Second => 0,
+        }
+    }
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            rounding: Rounding::Off,
+            style: FormatStyle::Fixed,
+            precision: 2,
+            decimal_separator: DecimalSeparator::Dot,
+            overtime_sign: SignStyle::Plus,
+        }
+    }
+}
+
+// 経過時間を HH:MM:SS.cc の形式の文字列に変換
+pub fn format_duration(duration: Duration, options: &FormatOptions) -> String {
+    // 欄の切り替わりは丸めた後の値で判定する(59.996秒を1秒単位に丸めると 1:00 になる)
+    let duration = options.rounding.apply(duration);
+    let seconds = duration.as_secs();
+    let (hours, minutes, secs) = (seconds / HOUR, (seconds % HOUR) / MINUTE, seconds % MINUTE);
+
+    let clock = match options.style {
+        FormatStyle::Adaptive if seconds < MINUTE => format!("{}", secs),
+        FormatStyle::Adaptive if seconds < HOUR => format!("{}:{:0>2}", minutes, secs),
+        FormatStyle::HideHours if seconds < HOUR => format!("{:0>2}:{:0>2}", minutes, secs),
+        _ => format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, secs),
+    };
+
+    // 桁数に応じてナノ秒から切り出すことで、どの桁数でも精度を落とさずに表示する
+    match options.digits() {
+        0 => clock,
+        digits => {
+            let fraction = duration.subsec_nanos() / 10u32.pow(9 - digits);
+            format!("{}{}{:0>width$}", clock, options.decimal_separator.as_char(), fraction, width = digits as usize)
+        },
+    }
+}
+
+// 1分未満の経過時間を、秒と1/10秒の欄に分けて "07" と ".3" のように返す。1分以上の場合は None
+// 競技の計時にならい、1/10秒未満は四捨五入せずに切り捨てる
+pub fn format_racing(duration: Duration) -> Option<(String, String)> {
+    if duration.as_secs() >= MINUTE {
+        return None;
+    }
+    Some((format!("{:0>2}", duration.as_secs()), format!(".{}", duration.subsec_millis() / 100)))
+}
+
+// 符号付きの時間を表示する。negative が真の場合は、0より前の時間や超過した時間として sign の符号を付ける
+// pad が真の場合は、符号を付けない値も符号と同じ幅の空白で囲み、符号の有無で表示の幅が変わらないようにする
+pub fn format_signed(duration: Duration, negative: bool, sign: SignStyle, pad: bool, options: &FormatOptions) -> String {
+    let text = format_duration(duration, options);
+    // 丸めた結果が0になる場合は符号を付けない
+    let negative = negative && text.chars().any(|c| c.is_ascii_digit() && c != '0');
+    match (negative, sign, pad) {
+        (true, SignStyle::Plus, _) => format!("+{}", text),
+        (true, SignStyle::Minus, _) => format!("-{}", text),
+        (true, SignStyle::Parentheses, _) => format!("({})", text),
+        (false, SignStyle::Parentheses, true) => format!(" {} ", text),
+        (false, _, true) => format!(" {}", text),
+        (false, _, false) => text,
+    }
+}
+
+// 0より前から数え始めた経過時間を、0に達するまでは先頭に "-" を付けて表示する
+// offset は開始時点の負の値の大きさで、duration は開始からの経過時間
+// 0より前から数える場合は、0を過ぎた後も "-" の分の幅を空けておく
+pub fn format_signed_duration(duration: Duration, offset: Duration, options: &FormatOptions) -> String {
+    let pad = offset > Duration::default();
+    match offset.checked_sub(duration) {
+        Some(before) => format_signed(before, true, SignStyle::Minus, pad, options),
+        None => format_signed(duration - offset, false, SignStyle::Minus, pad, options),
+    }
+}
+
+// "90"・"90s"・"5m"・"1h30m"・"1:30"・"1:02:03" のような文字列を Duration に変換
+// 単位のない数値は秒として扱い、解釈できない文字列や u64 の秒数に収まらない長さの場合は None を返す
+pub fn parse_duration(text: &str) -> Option<Duration> {
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    // コロン区切りの場合は、右から秒・分・時として扱う
+    if text.contains(':') {
+        let parts: Vec<&str> = text.split(':').collect();
+        if parts.len() > 3 {
+            return None;
+        }
+
+        let mut seconds: u64 = 0;
+        for part in parts {
+            seconds = seconds.checked_mul(MINUTE)?.checked_add(part.trim().parse::<u64>().ok()?)?;
+        }
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        if c.is_whitespace() {
+            continue;
+        }
+
+        let unit = match c {
+            'h' => HOUR,
+            'm' => MINUTE,
+            's' => 1,
+            _ => return None,
+        };
+        seconds = seconds.checked_add(number.parse::<u64>().ok()?.checked_mul(unit)?)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        seconds = seconds.checked_add(number.parse::<u64>().ok()?)?;
+    }
+
+    Some(Duration::from_secs(seconds))
+}
+
+// 設定ファイルなどに "25m" や "1:30" のような文字列で書かれた時間を読み込む
+pub fn deserialize_duration<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let text = String::deserialize(deserializer)?;
+    parse_duration(&text).ok_or_else(|| de::Error::custom(format!("invalid duration: {}", text)))
+}
+
+// 経過時間を ISO 8601 の期間の形式で "PT1H2M3S" のように表す
+// 値が0の単位は省略し、すべて0の場合は "PT0S" とする。1/1000秒の端数は "PT3.25S" のように秒に付ける
+pub fn iso8601_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, secs) = (seconds / HOUR, (seconds % HOUR) / MINUTE, seconds % MINUTE);
+    let millis = duration.subsec_millis();
+
+    let mut text = String::from("PT");
+    if hours > 0 {
+        text.push_str(&format!("{}H", hours));
+    }
+    if minutes > 0 {
+        text.push_str(&format!("{}M", minutes));
+    }
+    if millis > 0 {
+        let fraction = format!("{:0>3}", millis);
+        text.push_str(&format!("{}.{}S", secs, fraction.trim_end_matches('0')));
+    } else if secs > 0 || (hours == 0 && minutes == 0) {
+        text.push_str(&format!("{}S", secs));
+    }
+    text
+}
+
+// タイトルのテンプレートにラベルと経過時間を埋め込む
+pub fn format_title(template: &str, label: &str, time: &str) -> String {
+    template.replace("{label}", label).replace("{time}", time)
+}
+
+// 経過時間を "3 minutes 5 seconds" のような英語の文章に変換
+// 値が0の単位は省略し、すべて0の場合のみ "0 seconds" とする
+pub fn humanize_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let units = [
+        (seconds / HOUR, "hour"),
+        ((seconds % HOUR) / MINUTE, "minute"),
+        (seconds % MINUTE, "second"),
+    ];
+
+    let words: Vec<String> = units
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| pluralize(*value, unit))
+        .collect();
+
+    if words.is_empty() {
+        pluralize(0, "second")
+    } else {
+        words.join(" ")
+    }
+}
+
+// 数値に応じて単位を単数形・複数形に切り替える
+fn pluralize(value: u64, unit: &str) -> String {
+    if value == 1 {
+        format!("{} {}", value, unit)
+    } else {
+        format!("{} {}s", value, unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_rejects_overflowing_input() {
+        assert_eq!(parse_duration("99999999999999999999:0"), None);
+        assert_eq!(parse_duration("18446744073709551615:0"), None);
+        assert_eq!(parse_duration("9999999999999999999h"), None);
+        assert_eq!(parse_duration("18446744073709551615s 1s"), None);
+        assert_eq!(parse_duration("18446744073709551615"), Some(Duration::from_secs(u64::MAX)));
+    }
+
+    #[test]
+    fn humanizes_durations_in_words() {
+        assert_eq!(humanize_duration(Duration::from_secs(0)), "0 seconds");
+        assert_eq!(humanize_duration(Duration::from_secs(1)), "1 second");
+        assert_eq!(humanize_duration(Duration::from_secs(61)), "1 minute 1 second");
+        assert_eq!(humanize_duration(Duration::from_secs(2 * HOUR + 5)), "2 hours 5 seconds");
+        assert_eq!(humanize_duration(Duration::from_secs(HOUR + 2 * MINUTE)), "1 hour 2 minutes");
+    }
+
+    fn rounded(rounding: Rounding) -> FormatOptions {
+        FormatOptions { rounding, ..FormatOptions::default() }
+    }
+
+    #[test]
+    fn rounds_only_the_rendered_text() {
+        let second = rounded(Rounding::Second);
+        assert_eq!(format_duration(Duration::from_millis(3_470), &second), "00:00:03");
+        assert_eq!(format_duration(Duration::from_millis(3_490), &second), "00:00:03");
+        assert_eq!(format_duration(Duration::from_millis(3_500), &second), "00:00:04");
+        // 丸めた結果で欄が繰り上がる
+        assert_eq!(format_duration(Duration::from_millis(59_500), &second), "00:01:00");
+
+        let tenth = rounded(Rounding::Tenth);
+        assert_eq!(format_duration(Duration::from_millis(3_470), &tenth), "00:00:03.5");
+        assert_eq!(format_duration(Duration::from_millis(3_449), &tenth), "00:00:03.4");
+        assert_eq!(format_duration(Duration::from_millis(3_450), &tenth), "00:00:03.5");
+
+        assert_eq!(format_duration(Duration::from_millis(3_479), &rounded(Rounding::Off)), "00:00:03.47");
+    }
+
+    fn styled(style: FormatStyle) -> FormatOptions {
+        FormatOptions { style, ..FormatOptions::default() }
+    }
+
+    #[test]
+    fn adaptive_style_drops_leading_zero_fields() {
+        let adaptive = styled(FormatStyle::Adaptive);
+        assert_eq!(format_duration(Duration::from_millis(7_350), &adaptive), "7.35");
+        assert_eq!(format_duration(Duration::from_millis(59_990), &adaptive), "59.99");
+        assert_eq!(format_duration(Duration::from_secs(MINUTE), &adaptive), "1:00.00");
+        assert_eq!(format_duration(Duration::from_millis(67_350), &adaptive), "1:07.35");
+        assert_eq!(format_duration(Duration::from_secs(HOUR - 1), &adaptive), "59:59.00");
+        assert_eq!(format_duration(Duration::from_secs(HOUR), &adaptive), "01:00:00.00");
+    }
+
+    #[test]
+    fn renders_each_precision_without_losing_digits() {
+        let duration = Duration::from_nanos(3_456_789_000);
+        let expected = ["00:00:03", "00:00:03.4", "00:00:03.45", "00:00:03.456"];
+        for (precision, expected) in expected.iter().enumerate() {
+            let options = FormatOptions { precision: precision as u8, ..FormatOptions::default() };
+            assert_eq!(options.digits(), precision as u32);
+            assert_eq!(format_duration(duration, &options), *expected);
+        }
+        // 3桁を超える指定は3桁として扱う
+        let options = FormatOptions { precision: 6, ..FormatOptions::default() };
+        assert_eq!(format_duration(duration, &options), "00:00:03.456");
+    }
+
+    #[test]
+    fn shows_or_hides_the_hour_field() {
+        let fixed = styled(FormatStyle::Fixed);
+        let hide_hours = styled(FormatStyle::HideHours);
+        assert_eq!(format_duration(Duration::from_secs(5), &fixed), "00:00:05.00");
+        assert_eq!(format_duration(Duration::from_secs(5), &hide_hours), "00:05.00");
+        assert_eq!(format_duration(Duration::from_secs(2 * HOUR), &fixed), "02:00:00.00");
+        assert_eq!(format_duration(Duration::from_secs(2 * HOUR), &hide_hours), "02:00:00.00");
+        // 既定は常に時の欄を表示する
+        assert_eq!(FormatOptions::default().style, FormatStyle::Fixed);
+    }
+
+    #[test]
+    fn formats_iso8601_durations() {
+        assert_eq!(iso8601_duration(Duration::default()), "PT0S");
+        assert_eq!(iso8601_duration(Duration::from_secs(HOUR + 2 * MINUTE + 3)), "PT1H2M3S");
+        assert_eq!(iso8601_duration(Duration::from_secs(HOUR)), "PT1H");
+        assert_eq!(iso8601_duration(Duration::from_secs(2 * MINUTE)), "PT2M");
+        assert_eq!(iso8601_duration(Duration::from_millis(3_250)), "PT3.25S");
+        assert_eq!(iso8601_duration(Duration::from_millis(MINUTE * 1000 + 5)), "PT1M0.005S");
+    }
+
+    #[test]
+    fn formats_signs_across_precisions() {
+        let overrun = Duration::from_millis(65_250);
+        for precision in 0..=3u8 {
+            let options = FormatOptions { precision, style: FormatStyle::Adaptive, ..FormatOptions::default() };
+            let text = format_duration(overrun, &options);
+            assert_eq!(format_signed(overrun, true, SignStyle::Plus, false, &options), format!("+{}", text));
+            assert_eq!(format_signed(overrun, true, SignStyle::Minus, false, &options), format!("-{}", text));
+            assert_eq!(format_signed(overrun, true, SignStyle::Parentheses, false, &options), format!("({})", text));
+            assert_eq!(format_signed(overrun, false, SignStyle::Plus, false, &options), text);
+
+            // 0には符号を付けない
+            let zero = format_duration(Duration::default(), &options);
+            assert_eq!(format_signed(Duration::default(), true, SignStyle::Minus, false, &options), zero);
+
+            // pad を指定すると、符号の有無で幅が変わらない
+            for sign in [SignStyle::Plus, SignStyle::Minus, SignStyle::Parentheses].iter().copied() {
+                let signed = format_signed(overrun, true, sign, true, &options);
+                let unsigned = format_signed(overrun, false, sign, true, &options);
+                assert_eq!(signed.chars().count(), unsigned.chars().count());
+            }
+        }
+    }
+
+    #[test]
+    fn counts_up_from_a_negative_offset() {
+        let options = FormatOptions::default();
+        let offset = Duration::from_secs(10);
+        assert_eq!(format_signed_duration(Duration::from_secs(4), offset, &options), "-00:00:06.00");
+        assert_eq!(format_signed_duration(offset, offset, &options), " 00:00:00.00");
+        assert_eq!(format_signed_duration(Duration::from_secs(15), offset, &options), " 00:00:05.00");
+        assert_eq!(format_signed_duration(Duration::from_secs(15), Duration::default(), &options), "00:00:15.00");
+    }
+
+    #[test]
+    fn parses_units_and_colon_forms() {
+        assert_eq!(parse_duration("90"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("90s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(5 * MINUTE)));
+        assert_eq!(parse_duration(" 1h 30m "), Some(Duration::from_secs(HOUR + 30 * MINUTE)));
+        assert_eq!(parse_duration("1:30"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration("1:02:03"), Some(Duration::from_secs(HOUR + 2 * MINUTE + 3)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("1:2:3:4"), None);
+        assert_eq!(parse_duration("1:aa"), None);
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::mode::Mode;
+use crate::session;
+
+// 計測の履歴を、日付・ラベル・計測した秒数・一時停止していた秒数・計測を開始した日時・記録した日時・モードの7列のCSVとして記録する
+// 日付をまたいだ計測は日ごとに分けて記録するため、日付ごとに秒数を合計すればその日の累計になる
+// 開始日時は日付をまたいだ場合も最初に開始した日時とし、分からない場合は空にする
+const HEADER: &str = "date,label,seconds,paused_seconds,started,ended,mode";
+// 終了日時とモードの列を追加する前の形式。ストップウォッチの計測のみを記録していた
+const STARTED_HEADER: &str = "date,label,seconds,paused_seconds,started";
+// 開始日時の列を追加する前の形式
+const PAUSED_HEADER: &str = "date,label,seconds,paused_seconds";
+// 一時停止の列を追加する前の形式
+const LEGACY_HEADER: &str = "date,label,seconds";
+
+pub fn path() -> Option<PathBuf> {
+    session::data_dir().map(|dir| dir.join("history.csv"))
+}
+
+// 指定した日付の計測時間と一時停止していた時間を、開始日時・記録した日時(ended)・計測したモードとともに1行追記する
+pub fn record(
+    date: NaiveDate,
+    label: &str,
+    mode: Mode,
+    duration: Duration,
+    paused: Duration,
+    started: Option<DateTime<Local>>,
+    ended: DateTime<Local>,
+) {
+    if duration == Duration::default() && paused == Duration::default() {
+        return;
+    }
+    let (dir, path) = match (session::data_dir(), path()) {
+        (Some(dir), Some(path)) => (dir, path),
+        _ => return,
+    };
+
+    let started = started.map_or_else(String::new, format_time);
+    let line = format!(
+        "{},{},{:.3},{:.3},{},{},{}",
+        date,
+        escape(label),
+        duration.as_secs_f64(),
+        paused.as_secs_f64(),
+        started,
+        format_time(ended),
+        mode_name(mode)
+    );
+    let result = fs::create_dir_all(&dir).and_then(|_| {
+        migrate(&path)?;
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if is_new {
+            writeln!(file, "{}", HEADER)?;
+        }
+        writeln!(file, "{}", line)
+    });
+    match result {
+        Ok(()) => log::debug!("recorded {} to {}", line, path.display()),
+        Err(err) => log::warn!("failed to write {}: {}", path.display(), err),
+    }
+}
+
+// 履歴に記録した1件分の日付・ラベル・計測時間・モード
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub date: NaiveDate,
+    pub label: String,
+    pub duration: Duration,
+    pub mode: Mode,
+}
+
+// 1日分の計測時間の合計と記録の件数
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DaySummary {
+    pub total: Duration,
+    pub sessions: usize,
+}
+
+impl DaySummary {
+    // 1回の記録あたりの平均の長さ。記録がない日は0
+    pub fn average(&self) -> Duration {
+        if self.sessions == 0 {
+            Duration::default()
+        } else {
+            self.total / self.sessions as u32
+        }
+    }
+}
+
+// 古い形式のファイルは、足りない列(0秒の一時停止・不明な開始日時と終了日時・ストップウォッチのモード)を各行に補って新しい形式に書き換える
+fn migrate(path: &Path) -> io::Result<()> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    let missing = match text.lines().next() {
+        Some(LEGACY_HEADER) => ",0.000,,,stopwatch",
+        Some(PAUSED_HEADER) => ",,,stopwatch",
+        Some(STARTED_HEADER) => ",,stopwatch",
+        _ => return Ok(()),
+    };
+
+    let mut migrated = format!("{}\n", HEADER);
+    for line in text.lines().skip(1).filter(|line| !line.is_empty()) {
+        migrated.push_str(&format!("{}{}\n", line, missing));
+    }
+    fs::write(path, migrated)?;
+    log::info!("migrated {} to the current columns", path.display());
+    Ok(())
+}
+
+// ラベルより後ろにある列の数。ラベルにカンマが含まれうるため、ほかの列は末尾から数えて取り出す
+fn trailing_columns(header: Option<&str>) -> usize {
+    match header {
+        Some(LEGACY_HEADER) => 1,
+        Some(PAUSED_HEADER) => 2,
+        Some(STARTED_HEADER) => 3,
+        _ => 5,
+    }
+}
+
+// 記録を読み込み、記録した順に返す。ファイルがない場合は空
+fn entries() -> Vec<Entry> {
+    path().and_then(|path| fs::read_to_string(path).ok()).map_or_else(Vec::new, |text| parse(&text))
+}
+
+// CSVの各行を記録として解釈する。解釈できない行は読み飛ばす
+fn parse(text: &str) -> Vec<Entry> {
+    let columns = trailing_columns(text.lines().next());
+
+    text.lines()
+        .skip(1)
+        .filter_map(|line| {
+            // 日付の列と末尾の列を除いた残りがラベルにあたり、計測時間はラベルの直後の列にある
+            // モードの列がない古い形式は、ストップウォッチの計測のみを記録していた
+            let (date, rest) = line.split_once(',')?;
+            let fields: Vec<&str> = rest.rsplitn(columns + 1, ',').collect();
+            let label = fields.get(columns)?;
+            let seconds = fields[columns - 1].parse::<f64>().ok()?;
+            let mode = if columns == 5 { mode_from_name(fields[0])? } else { Mode::Stopwatch };
+            if !seconds.is_finite() || seconds < 0.0 {
+                return None;
+            }
+            Some(Entry {
+                date: date.parse::<NaiveDate>().ok()?,
+                label: unescape(label),
+                duration: Duration::from_secs_f64(seconds),
+                mode,
+            })
+        })
+        .collect()
+}
+
+// ストップウォッチの計測の記録。今日の合計や集計、最近の記録はストップウォッチの計測のみを対象にする
+fn stopwatch_entries() -> impl Iterator<Item = Entry> {
+    entries().into_iter().filter(|entry| entry.mode == Mode::Stopwatch)
+}
+
+// from から to までの(両端を含む)各日付について、記録を合計する
+// 記録のない日も0件として含める
+pub fn aggregate_by_day(from: NaiveDate, to: NaiveDate) -> BTreeMap<NaiveDate, DaySummary> {
+    let mut days: BTreeMap<NaiveDate, DaySummary> =
+        from.iter_days().take_while(|date| *date <= to).map(|date| (date, DaySummary::default())).collect();
+    for entry in stopwatch_entries() {
+        if let Some(summary) = days.get_mut(&entry.date) {
+            summary.total += entry.duration;
+            summary.sessions += 1;
+        }
+    }
+    days
+}
+
+// 指定した日付に記録された計測時間の合計
+pub fn total_for(date: NaiveDate) -> Duration {
+    aggregate_by_day(date, date).get(&date).map_or_else(Duration::default, |summary| summary.total)
+}
+
+// 最後に記録した計測のラベル。記録がない場合はNone
+pub fn last_label() -> Option<String> {
+    stopwatch_entries().last().map(|entry| entry.label)
+}
+
+// 最近記録した計測を、新しいものから最大 count 件返す
+pub fn recent(count: usize) -> Vec<Entry> {
+    let entries: Vec<Entry> = stopwatch_entries().collect();
+    entries.into_iter().rev().take(count).collect()
+}
+
+// Export CSV の書き出し先を指定していない場合のファイル。書類のディレクトリ(なければホームディレクトリ)に日時付きの名前で置く
+pub fn default_export_path() -> PathBuf {
+    let dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    dir.join(format!("history-{}.csv", Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+// 記録したすべての履歴を、現在の列の形式のCSVとして指定したファイルに書き出し、書き出した件数を返す
+pub fn export(to: &Path) -> io::Result<usize> {
+    let from = path().ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no data directory"))?;
+    migrate(&from)?;
+    let text = fs::read_to_string(&from)?;
+    if let Some(dir) = to.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(to, &text)?;
+    Ok(text.lines().skip(1).filter(|line| !line.is_empty()).count())
+}
+
+// 開始日時と終了日時の列の形式
+fn format_time(time: DateTime<Local>) -> String {
+    time.format("%Y-%m-%dT%H:%M:%S%:z").to_string()
+}
+
+// モードの列には、設定ファイルと同じ名前("stopwatch"・"countdown" など)を書く
+fn mode_name(mode: Mode) -> String {
+    toml::Value::try_from(mode).ok().and_then(|value| value.as_str().map(String::from)).unwrap_or_default()
+}
+
+fn mode_from_name(name: &str) -> Option<Mode> {
+    toml::Value::String(name.to_string()).try_into().ok()
+}
+
+// escape でダブルクォートで囲んだラベルを元に戻す
+fn unescape(field: &str) -> String {
+    match field.strip_prefix('"').and_then(|field| field.strip_suffix('"')) {
+        Some(quoted) => quoted.replace("\"\"", "\""),
+        None => field.to_string(),
+    }
+}
+
+// カンマやダブルクォートを含むラベルは、CSVとして読めるようダブルクォートで囲む
+fn escape(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\"").replace('\n', " "))
+    } else {
+        field.to_string()
+    }
+}
+
+// 記録したすべての履歴を削除する
+pub fn clear() {
+    if let Some(path) = path() {
+        match fs::remove_file(&path) {
+            Ok(()) => log::info!("cleared {}", path.display()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {},
+            Err(err) => log::warn!("failed to remove {}: {}", path.display(), err),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrates_every_old_format_to_the_current_columns() {
+        let dir = std::env::temp_dir().join(format!("simple_timer-history-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let old = [
+            (LEGACY_HEADER, "2024-01-02,Work,60.000"),
+            (PAUSED_HEADER, "2024-01-02,Work,60.000,5.000"),
+            (STARTED_HEADER, "2024-01-02,Work,60.000,5.000,2024-01-02T09:00:00+09:00"),
+        ];
+        for (index, (header, line)) in old.iter().enumerate() {
+            let path = dir.join(format!("history-{}.csv", index));
+            fs::write(&path, format!("{}\n{}\n", header, line)).unwrap();
+            migrate(&path).unwrap();
+
+            let text = fs::read_to_string(&path).unwrap();
+            let lines: Vec<&str> = text.lines().collect();
+            assert_eq!(lines[0], HEADER);
+            assert_eq!(lines[1].split(',').count(), 7, "{}", lines[1]);
+            assert!(lines[1].ends_with(",stopwatch"));
+            assert_eq!(parse(&text)[0].duration, Duration::from_secs(60));
+        }
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parses_the_mode_and_labels_with_commas() {
+        let text = format!(
+            "{}\n2024-01-02,\"Tea, green\",180.000,0.000,2024-01-02T09:00:00+09:00,2024-01-02T09:03:00+09:00,countdown\n\
+             2024-01-02,Work,60.000,5.000,,2024-01-02T10:00:00+09:00,stopwatch\n\
+             2024-01-02,Broken,60.000,5.000,,,lunch\n",
+            HEADER
+        );
+        let entries = parse(&text);
+        assert_eq!(entries.len(), 2);
+        assert_eq!((entries[0].label.as_str(), entries[0].mode), ("Tea, green", Mode::Countdown));
+        assert_eq!(entries[0].duration, Duration::from_secs(180));
+        assert_eq!((entries[1].label.as_str(), entries[1].mode), ("Work", Mode::Stopwatch));
+
+        // 古い形式のまま読んだ場合は、すべてストップウォッチの計測とみなす
+        let legacy = parse(&format!("{}\n2024-01-02,Work,60.000\n", LEGACY_HEADER));
+        assert_eq!(legacy[0].mode, Mode::Stopwatch);
+        assert_eq!(mode_name(Mode::Countdown), "countdown");
+    }
+}
@@ -0,0 +1,83 @@
+// どのアプリケーションを操作中でも反応するショートカットキーを登録し、押されたらすべてのタイマーの一時停止・再開を送るための構造体
+//
+// hotkey フィーチャーを有効にしてビルドした場合のみ登録し、無効な場合は何もしない。
+// 押されたことはメニューバーと同じくチャンネル経由でicedのランタイムへ送られ、
+// 構造体を破棄する(アプリケーションを終了する)際に登録を解除する。
+#[cfg(feature = "hotkey")]
+mod platform {
+    use async_std::channel::Sender;
+    use global_hotkey::hotkey::HotKey;
+    use global_hotkey::{GlobalHotKeyEvent, GlobalHotKeyManager, HotKeyState};
+
+    use crate::control::ControlCommand;
+
+    pub struct GlobalHotkey {
+        registered: Option<(GlobalHotKeyManager, HotKey)>,
+    }
+
+    impl GlobalHotkey {
+        // "Ctrl+Shift+P" のような文字列で指定したショートカットキーを登録する
+        pub fn register(sender: Sender<ControlCommand>, combo: &str) -> GlobalHotkey {
+            GlobalHotkey { registered: register(sender, combo) }
+        }
+    }
+
+    fn register(sender: Sender<ControlCommand>, combo: &str) -> Option<(GlobalHotKeyManager, HotKey)> {
+        let hotkey: HotKey = match combo.parse() {
+            Ok(hotkey) => hotkey,
+            Err(err) => {
+                log::warn!("invalid hotkey {:?}: {}", combo, err);
+                return None;
+            },
+        };
+        let manager = match GlobalHotKeyManager::new() {
+            Ok(manager) => manager,
+            Err(err) => {
+                log::warn!("failed to initialize global hotkeys: {}", err);
+                return None;
+            },
+        };
+        if let Err(err) = manager.register(hotkey) {
+            log::warn!("failed to register the hotkey {}: {}", combo, err);
+            return None;
+        }
+
+        // 押し続けた場合やキーを離した場合に何度も切り替わらないよう、押した時のみ送る
+        let id = hotkey.id();
+        GlobalHotKeyEvent::set_event_handler(Some(move |event: GlobalHotKeyEvent| {
+            if event.id() == id && event.state() == HotKeyState::Pressed {
+                let _ = sender.try_send(ControlCommand::TogglePauseAll);
+            }
+        }));
+
+        log::info!("registered the hotkey {}", combo);
+        Some((manager, hotkey))
+    }
+
+    impl Drop for GlobalHotkey {
+        fn drop(&mut self) {
+            if let Some((manager, hotkey)) = self.registered.take() {
+                if let Err(err) = manager.unregister(hotkey) {
+                    log::warn!("failed to unregister the hotkey: {}", err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "hotkey"))]
+mod platform {
+    use async_std::channel::Sender;
+
+    use crate::control::ControlCommand;
+
+    pub struct GlobalHotkey;
+
+    impl GlobalHotkey {
+        pub fn register(_sender: Sender<ControlCommand>, _combo: &str) -> GlobalHotkey {
+            GlobalHotkey
+        }
+    }
+}
+
+pub use platform::GlobalHotkey;
@@ -0,0 +1,478 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::format::deserialize_duration;
+
+// 運動と休憩を交互に繰り返すインターバルの設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct IntervalPlan {
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub work: Duration,
+    // 0の場合は休憩を挟まずに次のラウンドへ進む
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub rest: Duration,
+    pub rounds: u32,
+    // 1セットのラウンド数。0でない場合は、現在のセットで終えたラウンドを点の並びで表示する
+    pub set: u32,
+    // セットの最後のラウンドの後に取る長い休憩の長さ。0の場合は通常の休憩を取る
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub long_rest: Duration,
+}
+
+impl Default for IntervalPlan {
+    fn default() -> IntervalPlan {
+        IntervalPlan {
+            work: Duration::from_secs(40),
+            rest: Duration::from_secs(20),
+            rounds: 8,
+            set: 0,
+            long_rest: Duration::default(),
+        }
+    }
+}
+
+// ポモドーロの設定。省略した項目は、25分の作業と5分の休憩を8回繰り返し、4回ごとに15分の長い休憩を取る
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct PomodoroPlan {
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub work: Duration,
+    #[serde(rename = "break", deserialize_with = "deserialize_duration")]
+    pub rest: Duration,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub long_break: Duration,
+    // 長い休憩を取るまでの作業の回数
+    pub long_break_every: u32,
+    pub cycles: u32,
+}
+
+impl Default for PomodoroPlan {
+    fn default() -> PomodoroPlan {
+        PomodoroPlan {
+            work: Duration::from_secs(25 * 60),
+            rest: Duration::from_secs(5 * 60),
+            long_break: Duration::from_secs(15 * 60),
+            long_break_every: 4,
+            cycles: 8,
+        }
+    }
+}
+
+impl PomodoroPlan {
+    // インターバルの設定に直す。長い休憩を取るまでの回数を1セットとして、進み具合を点で表示する
+    pub fn plan(self) -> IntervalPlan {
+        IntervalPlan {
+            work: self.work,
+            rest: self.rest,
+            rounds: self.cycles,
+            set: self.long_break_every,
+            long_rest: self.long_break,
+        }
+    }
+}
+
+// インターバルの区間の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    Work,
+    Rest,
+    LongRest,
+}
+
+impl Phase {
+    pub fn name(self) -> &'static str {
+        match self {
+            Phase::Work => "Work",
+            Phase::Rest => "Rest",
+            Phase::LongRest => "Long break",
+        }
+    }
+}
+
+// 経過時間を進めた結果として起きた出来事
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntervalEvent {
+    PhaseChanged(Phase), // 区間が終わり、次の区間に切り替わる(保持時間がある場合はその後に切り替わる)
+    AwaitingBreak(Phase), // 運動が終わり、休憩を始めるか飛ばすかの選択を待っている
+    Finished,            // 最後のラウンドの運動が終わった
+}
+
+// 設定にしたがって運動と休憩を切り替えていくタイマー
+pub struct IntervalTimer {
+    plan: IntervalPlan,
+    // 現在のラウンド(1から数える)
+    round: u32,
+    phase: Phase,
+    // 現在の区間に入ってからの経過時間
+    phase_elapsed: Duration,
+    running: bool,
+    finished: bool,
+    // 区間が終わってから次の区間を始めるまで、終わった状態を表示し続ける時間
+    hold: Duration,
+    // 区間の終わりで待っている場合の、次の区間を始めるまでの残り時間
+    holding: Option<Duration>,
+    // 運動の後に自動で休憩へ進まず、休憩を始めるか飛ばすかを選ぶまで待つか否か
+    manual_advance: bool,
+    // 運動を終えて、休憩を始めるか飛ばすかの選択を待っているか否か
+    awaiting_break: bool,
+}
+
+impl IntervalTimer {
+    pub fn new(plan: IntervalPlan, hold: Duration, manual_advance: bool) -> IntervalTimer {
+        IntervalTimer {
+            plan,
+            round: 1,
+            phase: Phase::Work,
+            phase_elapsed: Duration::default(),
+            running: false,
+            finished: false,
+            hold,
+            holding: None,
+            manual_advance,
+            awaiting_break: false,
+        }
+    }
+
+    pub fn plan(&self) -> IntervalPlan {
+        self.plan
+    }
+
+    // 設定を置き換えて、最初のラウンドの運動の開始前に戻す
+    pub fn set_plan(&mut self, plan: IntervalPlan) {
+        self.plan = plan;
+        self.reset();
+    }
+
+    // 現在のラウンド(1から数える)と区間
+    pub fn round(&self) -> u32 {
+        self.round
+    }
+
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+
+    // 現在の区間に入ってからの経過時間
+    pub fn phase_elapsed(&self) -> Duration {
+        self.phase_elapsed
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+
+    // 区間が終わり、次の区間を始めるまで終わった状態を表示しているか否か
+    pub fn is_holding(&self) -> bool {
+        self.holding.is_some()
+    }
+
+    pub fn manual_advance(&self) -> bool {
+        self.manual_advance
+    }
+
+    pub fn is_awaiting_break(&self) -> bool {
+        self.awaiting_break
+    }
+
+    pub fn set_manual_advance(&mut self, manual_advance: bool) {
+        self.manual_advance = manual_advance;
+    }
+
+    // 停止と再開を切り替えた際に、最初のラウンドから始めることになるか否か
+    pub fn starts_over(&self) -> bool {
+        self.finished
+            || (!self.running && self.round == 1 && self.phase == Phase::Work && self.phase_elapsed == Duration::default())
+    }
+
+    // 画面に表示する状態の名前
+    pub fn state_name(&self) -> &'static str {
+        if self.finished {
+            "Finished"
+        } else if self.awaiting_break {
+            "Break?"
+        } else if self.running {
+            "Running"
+        } else if self.starts_over() {
+            "Ready"
+        } else {
+            "Paused"
+        }
+    }
+
+    // 現在の区間の長さ
+    fn phase_length(&self) -> Duration {
+        match self.phase {
+            Phase::Work => self.plan.work,
+            Phase::Rest => self.plan.rest,
+            Phase::LongRest => self.plan.long_rest,
+        }
+    }
+
+    // 現在の区間の残り時間
+    pub fn phase_remaining(&self) -> Duration {
+        self.phase_length().checked_sub(self.phase_elapsed).unwrap_or_default()
+    }
+
+    // 停止と再開を切り替える。終了済みのものは最初からやり直す
+    pub fn toggle(&mut self) {
+        if self.finished {
+            self.reset();
+            self.running = true;
+        } else {
+            self.running = !self.running;
+        }
+    }
+
+    // 最初のラウンドの運動の開始前に戻す
+    pub fn reset(&mut self) {
+        self.round = 1;
+        self.phase = Phase::Work;
+        self.phase_elapsed = Duration::default();
+        self.running = false;
+        self.finished = false;
+        self.holding = None;
+        self.awaiting_break = false;
+    }
+
+    // 現在のセットの最初のラウンドと、そのセットのラウンド数。セットを使わない設定ではNone
+    // 最後のセットは、全体のラウンド数に収まる分だけにする
+    pub fn current_set(&self) -> Option<(u32, u32)> {
+        if self.plan.set == 0 {
+            return None;
+        }
+        let first = self.round - (self.round - 1) % self.plan.set;
+        Some((first, self.plan.set.min(self.plan.rounds.saturating_sub(first) + 1)))
+    }
+
+    // 現在のセットで運動を終えたラウンドの数
+    pub fn completed_cycles(&self) -> u32 {
+        let (first, _) = match self.current_set() {
+            Some(set) => set,
+            None => return 0,
+        };
+        self.completed_rounds() + 1 - first
+    }
+
+    // 全体で運動を終えたラウンドの数
+    pub fn completed_rounds(&self) -> u32 {
+        let done = self.finished || self.phase != Phase::Work || self.holding.is_some() || self.awaiting_break;
+        self.round - 1 + done as u32
+    }
+
+    // 現在の区間の次のラウンドと区間。最後のラウンドの運動の後はNone
+    fn next_phase(&self) -> Option<(u32, Phase)> {
+        match self.phase {
+            Phase::Work if self.round >= self.plan.rounds => None,
+            Phase::Work if self.ends_set() => Some((self.round, Phase::LongRest)),
+            Phase::Work if self.plan.rest > Duration::default() => Some((self.round, Phase::Rest)),
+            Phase::Work | Phase::Rest | Phase::LongRest => Some((self.round + 1, Phase::Work)),
+        }
+    }
+
+    // 動作中であれば、次に起きる出来事の名前("Rest" などの次の区間か "Finish")と、それまでの時間を返す
+    pub fn upcoming(&self) -> Option<(&'static str, Duration)> {
+        if !self.running || self.awaiting_break || self.plan.work == Duration::default() {
+            return None;
+        }
+        let (name, remaining) = match (self.holding, self.next_phase()) {
+            (Some(holding), Some((_, phase))) => (phase.name(), holding),
+            (_, Some((_, phase))) => (phase.name(), self.phase_remaining() + self.hold),
+            (_, None) => ("Finish", self.phase_remaining()),
+        };
+        Some((name, remaining))
+    }
+
+    // 現在のラウンドの運動の後に、通常の休憩の代わりに長い休憩を取るか否か
+    fn ends_set(&self) -> bool {
+        self.plan.set > 0 && self.plan.long_rest > Duration::default() && self.round.is_multiple_of(self.plan.set)
+    }
+
+    // 指定したラウンドの運動の開始に移る。計測中か否かは変えない
+    pub fn jump_to(&mut self, round: u32) {
+        if round == 0 || round > self.plan.rounds {
+            return;
+        }
+        self.round = round;
+        self.phase = Phase::Work;
+        self.phase_elapsed = Duration::default();
+        self.finished = false;
+        self.holding = None;
+        self.awaiting_break = false;
+    }
+
+    // 選択を待っていた休憩を始める
+    pub fn start_break(&mut self) {
+        if !self.awaiting_break {
+            return;
+        }
+        self.awaiting_break = false;
+        if let Some((round, phase)) = self.next_phase() {
+            self.switch_to(round, phase);
+        }
+    }
+
+    // 選択を待っていた休憩を飛ばし、次のラウンドの運動を始める
+    pub fn skip_break(&mut self) {
+        if !self.awaiting_break {
+            return;
+        }
+        self.awaiting_break = false;
+        self.switch_to(self.round + 1, Phase::Work);
+    }
+
+    // 経過時間を進め、区間の切り替わりや終了があればその出来事を起きた順に返す
+    // 区間が終わると切り替わりを知らせ、保持時間だけ終わった状態で待ってから次の区間に切り替える。
+    // 1回の更新で区間の長さ(と保持時間)を超えた分は、次の区間の経過時間として引き継ぐ。
+    // 更新の間隔が空いて複数の区間が終わった場合も、切り替わりを1つずつすべて返す
+    // 休憩の前で選択を待つ設定では、運動が終わった時点で止まり、選ぶまで時間を進めない
+    pub fn advance(&mut self, delta: Duration) -> Vec<IntervalEvent> {
+        let mut events = Vec::new();
+        if !self.running || self.awaiting_break || self.plan.work == Duration::default() {
+            return events;
+        }
+
+        let mut delta = delta;
+        loop {
+            if let Some(holding) = self.holding {
+                if delta < holding {
+                    self.holding = Some(holding - delta);
+                    return events;
+                }
+                delta -= holding;
+                self.holding = None;
+                if let Some((round, phase)) = self.next_phase() {
+                    self.switch_to(round, phase);
+                }
+                continue;
+            }
+
+            let room = self.phase_remaining();
+            if delta < room {
+                self.phase_elapsed += delta;
+                return events;
+            }
+            delta -= room;
+            self.phase_elapsed = self.phase_length();
+
+            match self.next_phase() {
+                None => {
+                    self.running = false;
+                    self.finished = true;
+                    events.push(IntervalEvent::Finished);
+                    return events;
+                },
+                Some((_, phase)) if self.manual_advance && self.phase == Phase::Work && phase != Phase::Work => {
+                    self.awaiting_break = true;
+                    events.push(IntervalEvent::AwaitingBreak(phase));
+                    return events;
+                },
+                // 知らせる音は区間が終わった時点で鳴らし、保持時間の間に鳴り終わるようにする
+                Some((_, phase)) if self.hold > Duration::default() => {
+                    self.holding = Some(self.hold);
+                    events.push(IntervalEvent::PhaseChanged(phase));
+                },
+                Some((round, phase)) => {
+                    self.switch_to(round, phase);
+                    events.push(IntervalEvent::PhaseChanged(phase));
+                },
+            }
+        }
+    }
+
+    fn switch_to(&mut self, round: u32, phase: Phase) {
+        self.round = round;
+        self.phase = phase;
+        self.phase_elapsed = Duration::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn minutes(minutes: u64) -> Duration {
+        Duration::from_secs(minutes * 60)
+    }
+
+    #[test]
+    fn pomodoro_chains_work_and_breaks_and_counts_cycles() {
+        let mut timer = IntervalTimer::new(PomodoroPlan::default().plan(), Duration::default(), false);
+        timer.toggle();
+
+        assert_eq!(timer.advance(minutes(25)), vec![IntervalEvent::PhaseChanged(Phase::Rest)]);
+        assert_eq!(timer.completed_rounds(), 1);
+        assert_eq!(timer.advance(minutes(5)), vec![IntervalEvent::PhaseChanged(Phase::Work)]);
+        assert_eq!((timer.round(), timer.completed_rounds()), (2, 1));
+
+        // 4回目の作業の後は長い休憩になる
+        let events = timer.advance(minutes(25 + 5 + 25 + 5 + 25));
+        assert_eq!(events.last(), Some(&IntervalEvent::PhaseChanged(Phase::LongRest)));
+        assert_eq!(timer.completed_rounds(), 4);
+        assert_eq!(timer.completed_cycles(), 4);
+    }
+
+    #[test]
+    fn pomodoro_reads_partial_config() {
+        let plan: PomodoroPlan = toml::from_str("work = \"50m\"\nbreak = \"10m\"").unwrap();
+        let plan = plan.plan();
+        assert_eq!((plan.work, plan.rest, plan.long_rest), (minutes(50), minutes(10), minutes(15)));
+        assert_eq!((plan.rounds, plan.set), (8, 4));
+    }
+
+    #[test]
+    fn holds_before_switching_phases() {
+        let plan = IntervalPlan { work: Duration::from_secs(10), rest: Duration::from_secs(5), rounds: 2, ..IntervalPlan::default() };
+        let mut timer = IntervalTimer::new(plan, Duration::from_secs(1), false);
+        timer.toggle();
+
+        assert_eq!(timer.advance(Duration::from_secs(10)), vec![IntervalEvent::PhaseChanged(Phase::Rest)]);
+        assert!(timer.is_holding());
+        assert_eq!(timer.phase(), Phase::Work);
+        assert_eq!(timer.advance(Duration::from_secs(2)), vec![]);
+        assert_eq!((timer.phase(), timer.phase_elapsed()), (Phase::Rest, Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn takes_a_long_break_after_each_set() {
+        let plan = IntervalPlan { work: minutes(25), rest: minutes(5), rounds: 5, set: 2, long_rest: minutes(15) };
+        let mut timer = IntervalTimer::new(plan, Duration::default(), false);
+        timer.toggle();
+
+        // 1分ずつ進め、切り替わりをすべて集める
+        let mut events = Vec::new();
+        let mut sets = Vec::new();
+        while !timer.is_finished() {
+            for event in timer.advance(minutes(1)) {
+                events.push(event);
+                sets.push(timer.completed_cycles());
+            }
+        }
+        let (work, rest, long_rest) = (Phase::Work, Phase::Rest, Phase::LongRest);
+        assert_eq!(events, vec![
+            IntervalEvent::PhaseChanged(rest),
+            IntervalEvent::PhaseChanged(work),
+            IntervalEvent::PhaseChanged(long_rest),
+            IntervalEvent::PhaseChanged(work),
+            IntervalEvent::PhaseChanged(rest),
+            IntervalEvent::PhaseChanged(work),
+            IntervalEvent::PhaseChanged(long_rest),
+            IntervalEvent::PhaseChanged(work),
+            IntervalEvent::Finished,
+        ]);
+        // 長い休憩の後は、セットの中で数えた回数が0に戻る
+        assert_eq!(sets, vec![1, 1, 2, 0, 1, 1, 2, 0, 1]);
+        assert_eq!(timer.completed_rounds(), 5);
+
+        // 長い休憩の長さが0の場合は、通常の休憩を取る
+        let plan = IntervalPlan { long_rest: Duration::default(), ..plan };
+        let mut timer = IntervalTimer::new(plan, Duration::default(), false);
+        timer.toggle();
+        assert_eq!(timer.advance(minutes(25 + 5 + 25)).last(), Some(&IntervalEvent::PhaseChanged(rest)));
+    }
+}
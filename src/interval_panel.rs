@@ -0,0 +1,216 @@
+use std::ops::{Deref, DerefMut};
+use std::time::Duration;
+
+use iced::{button, Align, Button, Checkbox, Column, Element, HorizontalAlignment, Length, Row, Text};
+
+use crate::focus::Focus;
+use crate::format::{format_duration, FormatOptions};
+use crate::interval::{IntervalPlan, IntervalTimer, Phase};
+use crate::layout::{self, ButtonSize};
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// インターバルのモードの画面。切り替えはライブラリの IntervalTimer が行い、ここでは操作ボタンの状態を持つ
+pub struct IntervalPanel {
+    timer: IntervalTimer,
+    toggle_button_state: button::State,
+    start_break_button_state: button::State,
+    skip_break_button_state: button::State,
+    reset_button_state: button::State,
+    // セットのラウンドを表す点ごとのボタン。押すとそのラウンドの運動の開始に移る
+    cycle_button_states: Vec<button::State>,
+    // 設定ファイルのインターバルの代わりに、ポモドーロの設定で動かしているか否か
+    pomodoro: bool,
+}
+
+impl IntervalPanel {
+    pub fn new(plan: IntervalPlan, hold: Duration, manual_advance: bool) -> IntervalPanel {
+        IntervalPanel {
+            timer: IntervalTimer::new(plan, hold, manual_advance),
+            toggle_button_state: button::State::new(),
+            start_break_button_state: button::State::new(),
+            skip_break_button_state: button::State::new(),
+            reset_button_state: button::State::new(),
+            cycle_button_states: (0..plan.set).map(|_| button::State::new()).collect(),
+            pomodoro: false,
+        }
+    }
+
+    pub fn is_pomodoro(&self) -> bool {
+        self.pomodoro
+    }
+
+    // 設定を置き換えて最初に戻し、セットの点のボタンの数もそれに合わせる
+    pub fn use_plan(&mut self, plan: IntervalPlan, pomodoro: bool) {
+        self.timer.set_plan(plan);
+        self.cycle_button_states = (0..plan.set).map(|_| button::State::new()).collect();
+        self.pomodoro = pomodoro;
+    }
+
+    // 区間に入ってからの経過時間と残り時間を並べて表示する
+    // vertical が真の場合は、操作ボタンを縦に並べる
+    pub fn view(
+        &mut self,
+        format: &FormatOptions,
+        vertical: bool,
+        button_size: ButtonSize,
+        focus: Option<Focus>,
+        theme: Theme,
+    ) -> Element<'_, Message> {
+        let size = theme.readout_size() * 2 / 3;
+        let readout = |caption: &str, duration: Duration| {
+            Column::new()
+                .push(Text::new(caption.to_string()).font(font()))
+                .push(Text::new(format_duration(duration, format)).font(font()).size(size))
+                .align_items(Align::Center)
+                .width(Length::Fill)
+        };
+
+        let elapsed = readout("Elapsed", self.timer.phase_elapsed());
+        let remaining = readout("Remaining", self.timer.phase_remaining());
+
+        let (phase, round, rounds) = (self.timer.phase(), self.timer.round(), self.timer.plan().rounds);
+        // 区間の見出しは、運動(作業)中は赤、休憩中は緑で表示する
+        let phase_color = match phase {
+            Phase::Work => theme.slower(),
+            Phase::Rest | Phase::LongRest => theme.faster(),
+        };
+        let kind = if self.pomodoro { "Pomodoros" } else { "Rounds" };
+        let counter = format!("{} done: {}/{}", kind, self.timer.completed_rounds(), rounds);
+        let heading = if self.timer.is_finished() {
+            String::from("Done")
+        } else if self.timer.is_holding() || self.timer.is_awaiting_break() {
+            format!("{} complete  Round {}/{}", phase.name(), round, rounds)
+        } else {
+            format!("{}  Round {}/{}", phase.name(), round, rounds)
+        };
+
+        // 運動を終えたラウンドを塗りつぶした点、残りを白抜きの点で表す
+        let completed = self.timer.completed_cycles();
+        let set = self.timer.current_set();
+        let cycle_button_states = &mut self.cycle_button_states;
+        let cycles = set.map(move |(first, count)| {
+            cycle_button_states
+                .iter_mut()
+                .zip(0..count)
+                .fold(Row::new().spacing(5), |row, (state, index)| {
+                    let round = first + index;
+                    let mut text = Text::new(if index < completed { "●" } else { "○" }).font(font());
+                    if index >= completed {
+                        text = text.color(theme.subtle());
+                    }
+                    row.push(Button::new(state, text)
+                        .on_press(Message::JumpToRound(round))
+                        .style(style::Button { theme, focused: focus == Some(Focus::IntervalCycle(round)) }))
+                })
+        });
+
+        let toggle_text = if self.timer.is_finished() {
+            "Restart"
+        } else if self.timer.is_running() {
+            "Stop"
+        } else {
+            "Start"
+        };
+        let toggle_button = Button::new(
+            &mut self.toggle_button_state,
+            Text::new(toggle_text)
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+                .size(button_size.text_size),
+        )
+            .min_width(button_size.min_width)
+            .padding(button_size.padding)
+            .on_press(Message::ToggleInterval)
+            .style(style::Button { theme, focused: focus == Some(Focus::IntervalToggle) });
+
+        let reset_button = Button::new(
+            &mut self.reset_button_state,
+            Text::new("Reset")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+                .size(button_size.text_size),
+        )
+            .min_width(button_size.min_width)
+            .padding(button_size.padding)
+            .on_press(Message::ResetInterval)
+            .style(style::Button { theme, focused: focus == Some(Focus::IntervalReset) });
+
+        // 休憩の前で待っている間のみ、休憩を始めるか飛ばすかのボタンを出す
+        let break_buttons = if self.timer.is_awaiting_break() {
+            let start_break_button = Button::new(
+                &mut self.start_break_button_state,
+                Text::new("Start Break")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+                    .size(button_size.text_size),
+            )
+                .min_width(button_size.min_width)
+                .padding(button_size.padding)
+                .on_press(Message::StartBreak)
+                .style(style::Button { theme, focused: focus == Some(Focus::StartBreak) });
+            let skip_break_button = Button::new(
+                &mut self.skip_break_button_state,
+                Text::new("Skip Break")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+                    .size(button_size.text_size),
+            )
+                .min_width(button_size.min_width)
+                .padding(button_size.padding)
+                .on_press(Message::SkipBreak)
+                .style(style::Button { theme, focused: focus == Some(Focus::SkipBreak) });
+            Some(layout::buttons(vertical, vec![start_break_button.into(), skip_break_button.into()]))
+        } else {
+            None
+        };
+
+        let manual_checkbox = Checkbox::new(self.timer.manual_advance(), "Ask before breaks", Message::ToggleManualAdvance)
+            .style(style::Checkbox { theme, focused: focus == Some(Focus::ManualAdvance) });
+        let pomodoro_checkbox = Checkbox::new(self.pomodoro, "Pomodoro", Message::TogglePomodoro)
+            .style(style::Checkbox { theme, focused: focus == Some(Focus::Pomodoro) });
+
+        let mut heading = Text::new(heading).font(font());
+        if !self.timer.is_finished() {
+            heading = heading.color(phase_color);
+        }
+        let mut column = Column::new()
+            .push(heading)
+            .push(Text::new(counter).font(font()).color(theme.subtle()));
+        if let Some(cycles) = cycles {
+            column = column.push(cycles);
+        }
+        column = column.push(Row::new()
+            .push(elapsed)
+            .push(remaining)
+            .spacing(10)
+        );
+        if let Some(break_buttons) = break_buttons {
+            column = column.push(break_buttons);
+        }
+        column
+            .push(layout::buttons(vertical, vec![toggle_button.into(), reset_button.into()]))
+            .push(Row::new()
+                .push(pomodoro_checkbox)
+                .push(manual_checkbox)
+                .spacing(20)
+            )
+            .spacing(10)
+            .align_items(Align::Center)
+            .into()
+    }
+}
+
+impl Deref for IntervalPanel {
+    type Target = IntervalTimer;
+
+    fn deref(&self) -> &IntervalTimer {
+        &self.timer
+    }
+}
+
+impl DerefMut for IntervalPanel {
+    fn deref_mut(&mut self) -> &mut IntervalTimer {
+        &mut self.timer
+    }
+}
@@ -0,0 +1,361 @@
+use std::ops::Deref;
+use std::time::Duration;
+
+use crate::format::{format_duration, FormatOptions, FormatStyle};
+
+// スプリットの合計と経過時間の食い違いとみなさない差。ナノ秒単位の端数の扱いによる誤差を許容する
+const CONSISTENCY_EPSILON: Duration = Duration::from_millis(1);
+
+// 記録した1件のラップ。time は計測開始からの累計時間
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lap {
+    pub time: Duration,
+    // "pit stop" のような短いメモ。付けていない場合は空
+    pub note: String,
+}
+
+impl Lap {
+    pub fn new(time: Duration) -> Lap {
+        Lap { time, note: String::new() }
+    }
+}
+
+// 記録したラップの一覧。累計時間の順に並べて持つ
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Laps {
+    laps: Vec<Lap>,
+}
+
+// 一覧の1行に表示する値。number は1から数えたラップの番号
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LapRow {
+    pub number: usize,
+    pub time: Duration,
+    pub split: Duration,
+    pub delta: Option<LapDelta>,
+}
+
+impl Laps {
+    pub fn new(laps: Vec<Lap>) -> Laps {
+        let mut laps = laps;
+        laps.sort_by_key(|lap| lap.time);
+        Laps { laps }
+    }
+
+    // 累計時間 time のラップを記録し、入れた位置を返す
+    // 手動のラップの後に、それより前の区切りの自動のラップが来る場合もあるため、時刻順の位置に入れる
+    pub fn record(&mut self, time: Duration) -> usize {
+        let index = self.laps.partition_point(|lap| lap.time <= time);
+        self.laps.insert(index, Lap::new(time));
+        index
+    }
+
+    // リセットした際に、記録したラップをすべて消す
+    pub fn clear(&mut self) {
+        self.laps.clear();
+    }
+
+    // 経過時間を短く補正した際に、elapsed より後のラップを消す。消したラップがあればtrue
+    pub fn truncate(&mut self, elapsed: Duration) -> bool {
+        let kept = self.laps.partition_point(|lap| lap.time <= elapsed);
+        let removed = kept < self.laps.len();
+        self.laps.truncate(kept);
+        removed
+    }
+
+    pub fn set_note(&mut self, index: usize, note: String) {
+        if let Some(lap) = self.laps.get_mut(index) {
+            lap.note = note;
+        }
+    }
+
+    // 各ラップのスプリットと、前のラップとの差
+    pub fn rows(&self) -> Vec<LapRow> {
+        let cumulative = times(&self.laps);
+        splits(&cumulative)
+            .into_iter()
+            .zip(deltas(&cumulative))
+            .zip(&cumulative)
+            .enumerate()
+            .map(|(index, ((split, delta), &time))| LapRow { number: index + 1, time, split, delta })
+            .collect()
+    }
+}
+
+impl Deref for Laps {
+    type Target = [Lap];
+
+    fn deref(&self) -> &[Lap] {
+        &self.laps
+    }
+}
+
+// 各ラップの累計時間
+pub fn times(laps: &[Lap]) -> Vec<Duration> {
+    laps.iter().map(|lap| lap.time).collect()
+}
+
+// 経過時間が before から after に進んだ間に越えた、interval の倍数の時刻(before を含まず after を含む)
+// 1回の更新で複数の区切りを越えた場合も、区切りごとに1つずつ返す。interval が0の場合は空
+pub fn boundaries(before: Duration, after: Duration, interval: Duration) -> Vec<Duration> {
+    let interval = interval.as_nanos();
+    if interval == 0 || after <= before {
+        return Vec::new();
+    }
+
+    let first = before.as_nanos() / interval + 1;
+    let last = after.as_nanos() / interval;
+    (first..=last)
+        .map(|index| {
+            let nanos = index * interval;
+            Duration::new((nanos / 1_000_000_000) as u64, (nanos % 1_000_000_000) as u32)
+        })
+        .collect()
+}
+
+// 前のラップのスプリットとの差
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LapDelta {
+    Faster(Duration), // 前のラップより短かった
+    Slower(Duration), // 前のラップより長かった(同じ場合も含む)
+}
+
+impl LapDelta {
+    // 差は短い値になるため、先頭の0の欄を省いて "-0.42" や "+1.10" のように表す。丸めて0になる場合は符号を付けない
+    pub fn format(self, format: &FormatOptions) -> String {
+        let format = FormatOptions { style: FormatStyle::Adaptive, ..*format };
+        let (sign, amount) = match self {
+            LapDelta::Faster(amount) => ('-', amount),
+            LapDelta::Slower(amount) => ('+', amount),
+        };
+        let text = format_duration(amount, &format);
+        if text.chars().any(|c| c.is_ascii_digit() && c != '0') {
+            format!("{}{}", sign, text)
+        } else {
+            text
+        }
+    }
+}
+
+// 記録した累計時間から、各ラップのスプリット(そのラップだけにかかった時間)を求める
+pub fn splits(cumulative: &[Duration]) -> Vec<Duration> {
+    let mut previous = Duration::default();
+    cumulative
+        .iter()
+        .map(|&total| {
+            let split = total.checked_sub(previous).unwrap_or_default();
+            previous = total;
+            split
+        })
+        .collect()
+}
+
+// 各ラップのスプリットと、その前のラップのスプリットの差を求める。最初のラップは比較対象がないためNone
+pub fn deltas(cumulative: &[Duration]) -> Vec<Option<LapDelta>> {
+    let splits = splits(cumulative);
+    let mut previous: Option<Duration> = None;
+    splits
+        .iter()
+        .map(|&split| {
+            let delta = previous.map(|previous| match split.checked_sub(previous) {
+                Some(slower) => LapDelta::Slower(slower),
+                None => LapDelta::Faster(previous - split),
+            });
+            previous = Some(split);
+            delta
+        })
+        .collect()
+}
+
+// スプリットの合計に最後のラップからの経過時間 elapsed を足した値と、それとは別に数えた経過時間 reference との差
+// reference には累計経過時間とは別に求めた値(計測を始めた時刻から一時停止していた時間を除いたもの)を渡す
+// 差が CONSISTENCY_EPSILON 以内であればNone。足した値の方が長い場合は Slower、短い場合は Faster とする
+pub fn discrepancy(cumulative: &[Duration], elapsed: Duration, reference: Duration) -> Option<LapDelta> {
+    let last = cumulative.last().copied().unwrap_or_default();
+    let sum: Duration = splits(cumulative).iter().sum::<Duration>() + elapsed.checked_sub(last).unwrap_or_default();
+    match sum.checked_sub(reference) {
+        Some(longer) if longer > CONSISTENCY_EPSILON => Some(LapDelta::Slower(longer)),
+        Some(_) => None,
+        None if reference - sum > CONSISTENCY_EPSILON => Some(LapDelta::Faster(reference - sum)),
+        None => None,
+    }
+}
+
+// スプリットの平均・最短・最長
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LapStats {
+    pub average: Duration,
+    pub best: Duration,
+    pub worst: Duration,
+}
+
+// 丸める前のスプリットから統計を求める。ラップがない場合はNone
+pub fn stats(splits: &[Duration]) -> Option<LapStats> {
+    let best = *splits.iter().min()?;
+    let worst = *splits.iter().max()?;
+    let total: Duration = splits.iter().sum();
+    Some(LapStats {
+        average: total / splits.len() as u32,
+        best,
+        worst,
+    })
+}
+
+// ラップの一覧を、番号・累計・スプリット・前のラップとの差・メモの列のCSVにする
+// 時間は format の丸め方で書き出すが、差は丸める前の値から求める
+pub fn to_csv(laps: &Laps, format: &FormatOptions) -> String {
+    let mut text = String::from("lap,time,split,delta,note\n");
+    for (row, lap) in laps.rows().into_iter().zip(laps.iter()) {
+        text.push_str(&format!(
+            "{},{},{},{},{}\n",
+            row.number,
+            format_duration(row.time, format),
+            format_duration(row.split, format),
+            row.delta.map(|delta| delta.format(format)).unwrap_or_default(),
+            escape_csv(&lap.note)
+        ));
+    }
+    text
+}
+
+// ラップの一覧を、CSVと同じ列のMarkdownの表にする
+pub fn to_markdown(laps: &Laps, format: &FormatOptions) -> String {
+    let mut text = String::from("| Lap | Time | Split | Delta | Note |\n|---:|---:|---:|---:|:---|\n");
+    for (row, lap) in laps.rows().into_iter().zip(laps.iter()) {
+        text.push_str(&format!(
+            "| {} | {} | {} | {} | {} |\n",
+            row.number,
+            format_duration(row.time, format),
+            format_duration(row.split, format),
+            row.delta.map(|delta| delta.format(format)).unwrap_or_default(),
+            lap.note.replace('|', "\\|").replace('\n', " ")
+        ));
+    }
+    text
+}
+
+// カンマやダブルクォートを含むメモは、CSVとして読めるようダブルクォートで囲む
+fn escape_csv(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\"").replace('\n', " "))
+    } else {
+        field.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::format::Rounding;
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn records_laps_in_time_order_with_splits_and_deltas() {
+        let mut laps = Laps::default();
+        laps.record(secs(10));
+        laps.record(secs(30));
+        // 手動のラップの後に来た、それより前の自動のラップ
+        assert_eq!(laps.record(secs(18)), 1);
+
+        let rows = laps.rows();
+        assert_eq!(rows.iter().map(|row| row.split).collect::<Vec<_>>(), vec![secs(10), secs(8), secs(12)]);
+        assert_eq!(rows[0].delta, None);
+        assert_eq!(rows[1].delta, Some(LapDelta::Faster(secs(2))));
+        assert_eq!(rows[2].delta, Some(LapDelta::Slower(secs(4))));
+        assert_eq!(rows[2].number, 3);
+    }
+
+    #[test]
+    fn compares_splits_with_an_independent_total() {
+        let cumulative = [secs(10), secs(25)];
+        assert_eq!(discrepancy(&cumulative, secs(30), secs(30)), None);
+        // 1/1000秒以内の差は一致とみなす
+        assert_eq!(discrepancy(&cumulative, secs(30), secs(30) + Duration::from_micros(500)), None);
+        assert_eq!(discrepancy(&cumulative, secs(30), secs(28)), Some(LapDelta::Slower(secs(2))));
+        assert_eq!(discrepancy(&cumulative, secs(30), secs(31)), Some(LapDelta::Faster(secs(1))));
+        // 経過時間より後のラップが残っている場合も食い違いとして示す
+        assert_eq!(discrepancy(&cumulative, secs(20), secs(20)), Some(LapDelta::Slower(secs(5))));
+    }
+
+    #[test]
+    fn clears_and_truncates_laps() {
+        let mut laps = Laps::new(vec![Lap::new(secs(20)), Lap::new(secs(5))]);
+        assert_eq!(times(&laps), vec![secs(5), secs(20)]);
+
+        assert!(laps.truncate(secs(10)));
+        assert_eq!(times(&laps), vec![secs(5)]);
+        assert!(!laps.truncate(secs(10)));
+
+        laps.clear();
+        assert!(laps.is_empty());
+    }
+
+    #[test]
+    fn deltas_for_monotonic_and_mixed_paces() {
+        // だんだん遅くなる場合
+        let slowing = [secs(10), secs(21), secs(33)];
+        assert_eq!(splits(&slowing), vec![secs(10), secs(11), secs(12)]);
+        assert_eq!(deltas(&slowing), vec![None, Some(LapDelta::Slower(secs(1))), Some(LapDelta::Slower(secs(1)))]);
+
+        // 速くなったり遅くなったりする場合。同じスプリットは Slower(0) とする
+        let mixed = [secs(10), secs(18), secs(30), secs(42)];
+        assert_eq!(deltas(&mixed), vec![
+            None,
+            Some(LapDelta::Faster(secs(2))),
+            Some(LapDelta::Slower(secs(4))),
+            Some(LapDelta::Slower(secs(0))),
+        ]);
+        assert!(deltas(&[]).is_empty());
+    }
+
+    #[test]
+    fn exports_rounded_times_with_exact_deltas() {
+        let laps = Laps::new(vec![Lap::new(Duration::from_millis(10_400)), Lap::new(Duration::from_millis(20_600))]);
+        let format = FormatOptions { rounding: Rounding::Second, ..FormatOptions::default() };
+        assert_eq!(
+            to_csv(&laps, &format),
+            "lap,time,split,delta,note\n1,00:00:10,00:00:10,,\n2,00:00:21,00:00:10,0,\n"
+        );
+
+        let format = FormatOptions::default();
+        assert_eq!(
+            to_csv(&laps, &format),
+            "lap,time,split,delta,note\n1,00:00:10.40,00:00:10.40,,\n2,00:00:20.60,00:00:10.20,-0.20,\n"
+        );
+    }
+
+    #[test]
+    fn exports_notes_to_csv_and_markdown() {
+        let mut laps = Laps::new(vec![Lap::new(secs(10)), Lap::new(secs(25))]);
+        laps.set_note(0, String::from("fell, then \"ran\""));
+        laps.set_note(1, String::from("pit | stop"));
+        let format = FormatOptions::default();
+
+        assert_eq!(
+            to_csv(&laps, &format),
+            "lap,time,split,delta,note\n\
+             1,00:00:10.00,00:00:10.00,,\"fell, then \"\"ran\"\"\"\n\
+             2,00:00:25.00,00:00:15.00,+5.00,pit | stop\n"
+        );
+        assert_eq!(
+            to_markdown(&laps, &format),
+            "| Lap | Time | Split | Delta | Note |\n|---:|---:|---:|---:|:---|\n\
+             | 1 | 00:00:10.00 | 00:00:10.00 |  | fell, then \"ran\" |\n\
+             | 2 | 00:00:25.00 | 00:00:15.00 | +5.00 | pit \\| stop |\n"
+        );
+    }
+
+    #[test]
+    fn stats_use_exact_splits() {
+        let splits = [Duration::from_millis(10_400), Duration::from_millis(9_600), Duration::from_millis(10_450)];
+        let exact = stats(&splits).unwrap();
+        // 1秒単位に丸めた値(10・10・10)ではなく、丸める前の値から求める
+        assert_eq!(exact.average, Duration::from_millis(10_150));
+        assert_eq!(exact.best, Duration::from_millis(9_600));
+        assert_eq!(exact.worst, Duration::from_millis(10_450));
+        assert_eq!(stats(&[]), None);
+    }
+}
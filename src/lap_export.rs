@@ -0,0 +1,53 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+
+use crate::format::FormatOptions;
+use crate::lap::{self, Laps};
+
+// ラップの一覧の書き出し先。書類のディレクトリ(なければホームディレクトリ)に、作成時刻を含む名前で置く
+pub fn default_path() -> PathBuf {
+    let dir = dirs::document_dir()
+        .or_else(dirs::home_dir)
+        .unwrap_or_default();
+    dir.join(format!("laps-{}.csv", Local::now().format("%Y%m%d-%H%M%S")))
+}
+
+// ラップの一覧を、format の丸め方でメモとともにCSVのファイルに書き出す
+// 同じ内容のMarkdownの表も、拡張子を .md にした同じ名前のファイルに書き出し、そのパスを返す
+pub fn write(laps: &Laps, format: &FormatOptions, path: &Path) -> io::Result<PathBuf> {
+    if laps.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no laps to export"));
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let markdown_path = path.with_extension("md");
+    fs::write(path, lap::to_csv(laps, format))?;
+    fs::write(&markdown_path, lap::to_markdown(laps, format))?;
+    Ok(markdown_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn writes_laps_to_the_given_path() {
+        let dir = std::env::temp_dir().join(format!("simple_timer-laps-{}", std::process::id()));
+        let path = dir.join("laps.csv");
+        let mut laps = Laps::default();
+        assert!(write(&laps, &FormatOptions::default(), &path).is_err());
+
+        laps.record(Duration::from_secs(10));
+        laps.set_note(0, String::from("start"));
+        let markdown_path = write(&laps, &FormatOptions::default(), &path).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "lap,time,split,delta,note\n1,00:00:10.00,00:00:10.00,,start\n");
+        assert_eq!(markdown_path, dir.join("laps.md"));
+        assert!(fs::read_to_string(&markdown_path).unwrap().contains("| 1 | 00:00:10.00 | 00:00:10.00 |  | start |"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,154 @@
+use std::ops::Range;
+
+use iced::{button, scrollable, Button, Column, Element, HorizontalAlignment, Length, Row, Scrollable, Space, Text};
+use iced_native::Rectangle;
+
+use crate::format::{format_duration, FormatOptions, Rounding};
+use crate::lap::{splits, stats, times, LapDelta, Laps};
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// ラップ一覧の表示領域の最大の高さ。これを超える分はスクロールして表示する
+const LIST_HEIGHT: u32 = 100;
+// ラップの1行の高さと行の間隔。表示しない行の分を同じ高さの空白で埋めるため、行の高さは固定する
+const ROW_HEIGHT: u16 = 20;
+const ROW_SPACING: u16 = 2;
+// 見えている行の前後に余分に作っておく行数。スクロールした直後に空白が見えないようにする
+const BUFFER_ROWS: usize = 5;
+// 1つの空白で埋める最大の行数。高さの上限(u16)を超えないよう、これを超える分は空白を分ける
+const MAX_SPACER_ROWS: usize = 2000;
+
+// スクロールの位置から、行の一覧のうち作る範囲(上から数えた位置)を求める
+// 見えている行と前後の BUFFER_ROWS 行のみを作り、残りは空白で埋めることで、ラップが多くても表示を軽く保つ
+fn visible_range(state: &scrollable::State, count: usize) -> Range<usize> {
+    let stride = f32::from(ROW_HEIGHT + ROW_SPACING);
+    let content_height = (count as f32 * stride - f32::from(ROW_SPACING)).max(0.0);
+    let visible_height = content_height.min(LIST_HEIGHT as f32);
+    let bounds = |height| Rectangle { x: 0.0, y: 0.0, width: 0.0, height };
+    let offset = state.offset(bounds(visible_height), bounds(content_height)) as f32;
+
+    let first = (offset / stride) as usize;
+    let shown = (visible_height / stride).ceil() as usize + 1;
+    first.saturating_sub(BUFFER_ROWS)..(first + shown + BUFFER_ROWS).min(count)
+}
+
+// 作らない rows 行の分の高さの空白。行の間隔も含め、行を並べた場合と同じ高さになる
+fn spacers(rows: usize) -> Vec<Space> {
+    let stride = ROW_HEIGHT + ROW_SPACING;
+    let mut spacers = Vec::new();
+    let mut rest = rows;
+    while rest > 0 {
+        let chunk = rest.min(MAX_SPACER_ROWS);
+        spacers.push(Space::with_height(Length::Units(chunk as u16 * stride - ROW_SPACING)));
+        rest -= chunk;
+    }
+    spacers
+}
+
+// ラップの一覧。新しいラップほど上に表示する
+// 時間は rounding で指定した丸め方で表示するが、差や統計は丸める前の値から求める
+// 行を押すとそのラップのメモを編集する。row_states はラップと同じ数だけ渡す
+pub fn view<'a>(
+    state: &'a mut scrollable::State,
+    row_states: &'a mut [button::State],
+    laps: &Laps,
+    editing: Option<usize>,
+    rounding: Rounding,
+    format: &FormatOptions,
+    theme: Theme,
+) -> Element<'a, Message> {
+    let format = &FormatOptions { rounding, ..*format };
+    let cumulative = &times(laps)[..];
+    let range = visible_range(state, laps.len());
+    let (above, below) = (range.start, laps.len() - range.end);
+
+    let rows = laps
+        .rows()
+        .into_iter()
+        .zip(laps.iter())
+        .zip(row_states.iter_mut())
+        .rev()
+        .skip(range.start)
+        .take(range.len())
+        .map(|((row, lap), row_state)| {
+            let delta_text = match row.delta {
+                Some(delta @ LapDelta::Faster(_)) => Text::new(delta.format(format)).color(theme.faster()),
+                Some(delta @ LapDelta::Slower(_)) => Text::new(delta.format(format)).color(theme.slower()),
+                None => Text::new(""),
+            };
+
+            let mut name = format!("Lap {}", row.number);
+            if !lap.note.is_empty() {
+                name = format!("{}  {}", name, lap.note);
+            }
+            let index = row.number - 1;
+            let content = Row::new()
+                .push(Text::new(name).font(font()).width(Length::Fill))
+                .push(Text::new(format_duration(row.split, format)).font(font()))
+                .push(Text::new(format_duration(row.time, format)).font(font()))
+                .push(delta_text
+                    .font(font())
+                    .width(Length::Units(60))
+                    .horizontal_alignment(HorizontalAlignment::Right)
+                )
+                .spacing(10);
+            Button::new(row_state, content)
+                .width(Length::Fill)
+                .height(Length::Units(ROW_HEIGHT))
+                .padding(0)
+                .on_press(Message::EditLap(index))
+                .style(style::LapRow { theme, selected: editing == Some(index) })
+        });
+
+    let mut list = Scrollable::new(state).spacing(ROW_SPACING).max_height(LIST_HEIGHT).width(Length::Fill);
+    for spacer in spacers(above) {
+        list = list.push(spacer);
+    }
+    list = rows.fold(list, |list, row| list.push(row));
+    for spacer in spacers(below) {
+        list = list.push(spacer);
+    }
+
+    // 統計は2つ以上のラップがある場合のみ表示する
+    let mut column = Column::new().spacing(5).width(Length::Fill);
+    if let Some(stats) = stats(&splits(cumulative)).filter(|_| cumulative.len() >= 2) {
+        let summary = format!(
+            "Avg {}  Best {}  Worst {}",
+            format_duration(stats.average, format),
+            format_duration(stats.best, format),
+            format_duration(stats.worst, format),
+        );
+        column = column.push(Text::new(summary).font(font()).color(theme.subtle()));
+    }
+    column
+        .push(list)
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 行を count 行並べた一覧を、delta_y だけ下にスクロールした状態
+    fn scrolled(count: usize, delta_y: f32) -> scrollable::State {
+        let content_height = count as f32 * f32::from(ROW_HEIGHT + ROW_SPACING) - f32::from(ROW_SPACING);
+        let bounds = |height| Rectangle { x: 0.0, y: 0.0, width: 0.0, height };
+        let mut state = scrollable::State::new();
+        state.scroll(-delta_y, bounds(LIST_HEIGHT as f32), bounds(content_height));
+        state
+    }
+
+    #[test]
+    fn builds_only_the_visible_rows_and_a_buffer() {
+        // すべて収まる場合はすべての行を作る
+        assert_eq!(visible_range(&scrolled(3, 0.0), 3), 0..3);
+        assert_eq!(visible_range(&scrolled(0, 0.0), 0), 0..0);
+
+        // 先頭では、見えている5行とその次の1行に、後ろの余分の行を加える
+        assert_eq!(visible_range(&scrolled(1000, 0.0), 1000), 0..11);
+        // 100行分スクロールした位置では、前後に余分の行を加える
+        assert_eq!(visible_range(&scrolled(1000, 2200.0), 1000), 95..111);
+        // 末尾を超えてスクロールしても、行の数を超えない
+        assert_eq!(visible_range(&scrolled(1000, 1.0e9), 1000), 990..1000);
+    }
+}
@@ -0,0 +1,70 @@
+use iced::{Align, Column, Element, Row};
+use serde::Deserialize;
+
+use crate::Message;
+
+// 操作ボタンの並べ方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ButtonLayout {
+    Horizontal, // 横一列に並べる
+    Vertical,   // 縦一列に積み重ねる
+    Auto,       // ウィンドウが縦長の場合のみ縦に並べる
+}
+
+impl ButtonLayout {
+    // ウィンドウの大きさ(幅, 高さ)から、実際に縦に並べるか否かを決める
+    pub fn is_vertical(self, (width, height): (u32, u32)) -> bool {
+        match self {
+            ButtonLayout::Horizontal => false,
+            ButtonLayout::Vertical => true,
+            ButtonLayout::Auto => height > width,
+        }
+    }
+}
+
+// タッチ操作向けの表示で使う、ボタンの最小の幅・内側の余白・文字サイズ
+const TOUCH_MIN_WIDTH: u32 = 140;
+const TOUCH_PADDING: u16 = 16;
+const TOUCH_TEXT_SIZE: u16 = 30;
+// 通常の表示のボタンの内側の余白と文字サイズ(icedの既定値と同じ)
+const PADDING: u16 = 5;
+const TEXT_SIZE: u16 = 20;
+
+// 操作ボタンの大きさ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ButtonSize {
+    pub min_width: u32,
+    pub padding: u16,
+    pub text_size: u16,
+}
+
+impl ButtonSize {
+    // touch が真の場合は、指で押しやすいよう幅・余白・文字を大きくする
+    pub fn new(min_width: u32, touch: bool) -> ButtonSize {
+        if touch {
+            ButtonSize { min_width: min_width.max(TOUCH_MIN_WIDTH), padding: TOUCH_PADDING, text_size: TOUCH_TEXT_SIZE }
+        } else {
+            ButtonSize { min_width, padding: PADDING, text_size: TEXT_SIZE }
+        }
+    }
+}
+
+// ボタンを横一列か縦一列に並べ、どちらの場合も中央に揃える
+pub fn buttons<'a>(vertical: bool, buttons: Vec<Element<'a, Message>>) -> Element<'a, Message> {
+    if vertical {
+        buttons
+            .into_iter()
+            .fold(Column::new(), |column, button| column.push(button))
+            .spacing(5)
+            .align_items(Align::Center)
+            .into()
+    } else {
+        buttons
+            .into_iter()
+            .fold(Row::new(), |row, button| row.push(button))
+            .spacing(10)
+            .align_items(Align::Center)
+            .into()
+    }
+}
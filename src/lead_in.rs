@@ -0,0 +1,106 @@
+use std::time::Duration;
+
+use iced::{button, Align, Button, Element, HorizontalAlignment, Row, Text};
+use serde::Deserialize;
+
+use crate::format::deserialize_duration;
+use crate::focus::Focus;
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// カウントダウンやインターバルを始める前の秒読みの設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct LeadInConfig {
+    pub enabled: bool,
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub length: Duration,
+}
+
+impl Default for LeadInConfig {
+    fn default() -> LeadInConfig {
+        LeadInConfig {
+            enabled: false,
+            length: Duration::from_secs(3),
+        }
+    }
+}
+
+// 秒読みの後に開始するタイマー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeadInTarget {
+    Countdown(usize),
+    Interval,
+}
+
+// 経過時間を進めた結果として起きた出来事
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeadInEvent {
+    Count,    // 残り秒数が1つ減った
+    Finished, // 秒読みが終わり、タイマーを開始する
+}
+
+// 開始前の秒読みの状態
+pub struct LeadIn {
+    pub target: LeadInTarget,
+    remaining: Duration,
+    skip_button_state: button::State,
+}
+
+impl LeadIn {
+    pub fn new(target: LeadInTarget, length: Duration) -> LeadIn {
+        LeadIn {
+            target,
+            remaining: length,
+            skip_button_state: button::State::new(),
+        }
+    }
+
+    // 秒読みが終わるまでの時間
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    // 表示する残り秒数(切り上げ)
+    fn seconds_left(&self) -> u64 {
+        let seconds = self.remaining.as_secs();
+        if self.remaining.subsec_nanos() > 0 {
+            seconds + 1
+        } else {
+            seconds
+        }
+    }
+
+    // 残り時間を減らし、残り秒数が減った場合や秒読みが終わった場合にその出来事を返す
+    pub fn advance(&mut self, delta: Duration) -> Option<LeadInEvent> {
+        let before = self.seconds_left();
+        self.remaining = self.remaining.checked_sub(delta).unwrap_or_default();
+        match self.seconds_left() {
+            0 => Some(LeadInEvent::Finished),
+            after if after != before => Some(LeadInEvent::Count),
+            _ => None,
+        }
+    }
+
+    pub fn view(&mut self, focus: Option<Focus>, theme: Theme) -> Element<'_, Message> {
+        let countdown_text = Text::new(format!("Starting in {}", self.seconds_left()))
+            .font(font())
+            .color(theme.flash());
+
+        let skip_button = Button::new(
+            &mut self.skip_button_state,
+            Text::new("Skip")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font()),
+        )
+            .on_press(Message::SkipLeadIn)
+            .style(style::Button { theme, focused: focus == Some(Focus::SkipLeadIn) });
+
+        Row::new()
+            .push(countdown_text)
+            .push(skip_button)
+            .spacing(10)
+            .align_items(Align::Center)
+            .into()
+    }
+}
@@ -0,0 +1,11 @@
+// GUIに依存しない計時・表示形式の処理と、ストップウォッチ・カウントダウン・ラップ・インターバルの状態の管理
+// gui フィーチャー(既定で有効)を無効にしても、これらは serde のみに依存してビルドできる
+pub mod clock;
+pub mod countdown;
+pub mod format;
+pub mod interval;
+pub mod lap;
+pub mod stopwatch;
+// 画面を一定の間隔で更新するためのサブスクリプション。icedに依存するため gui フィーチャーでのみ使える
+#[cfg(feature = "gui")]
+pub mod timer;
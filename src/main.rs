@@ -1,30 +1,292 @@
+use std::path::Path;
+use std::sync::OnceLock;
 use std::time::{Duration, Instant};
 
 use iced::{
-    button, executor, Align, Application, Button, Column, Command, Element, Font,
-    HorizontalAlignment, Length, Row, Settings, Subscription, Text,
+    button, executor, Align, Application, Button, Checkbox, Column, Command, Container, Element, Font,
+    HorizontalAlignment, Length, ProgressBar, Radio, Row, Settings, Subscription, Text, TextInput, scrollable,
+    text_input,
 };
-use iced_futures::futures;
-use iced_native::Color;
+use async_std::channel::Receiver;
+use chrono::{DateTime, Local, NaiveDate};
+use iced_native::input::{keyboard, mouse, ButtonState};
+use iced_native::{window, Event};
+
+mod ambient;
+mod args;
+mod awake;
+mod banner;
+mod batch;
+mod chain;
+mod chart;
+mod cli;
+mod config;
+mod control;
+mod countdown_row;
+mod env;
+mod fit;
+mod focus;
+mod history;
+mod hotkey;
+mod interval_panel;
+mod keys;
+mod lap_export;
+mod lap_list;
+mod layout;
+mod lead_in;
+mod menubar;
+mod mode;
+mod notify;
+mod preset;
+mod refresh;
+mod repeat;
+mod report;
+mod rpc;
+mod schedule;
+mod selftest;
+mod session;
+mod since;
+mod sound;
+mod style;
+mod tabs;
+mod talk;
+
+// 計時と表示形式の処理はライブラリ側で定義する
+use simple_timer::{clock, countdown, format, interval, lap, stopwatch};
+use simple_timer::timer::Timer;
+use args::Args;
+use awake::AwakeGuard;
+use banner::Banner;
+use chain::Chain;
+use clock::{Clock, ClockJump, ClockShift, JumpDetector, SystemClock};
+use config::Config;
+use control::{ControlCommand, Controls};
+use countdown::{Countdown, CountdownEvent};
+use countdown_row::CountdownRow;
+use focus::Focus;
+use format::{
+    format_duration, format_racing, format_signed_duration, format_title, humanize_duration, iso8601_duration, parse_duration, FormatOptions, FormatStyle,
+    Rounding,
+};
+use hotkey::GlobalHotkey;
+use interval::{IntervalEvent, Phase};
+use interval_panel::IntervalPanel;
+use lap::{Lap, LapDelta, Laps};
+use lead_in::{LeadIn, LeadInEvent, LeadInTarget};
+use menubar::MenuBar;
+use mode::{Mode, StopAction};
+use notify::Notifier;
+use preset::FormatPreset;
+use refresh::RefreshStrategy;
+use repeat::LastSession;
+use rpc::{RpcServer, TimerState};
+use schedule::Upcoming;
+use session::{SavedCountdown, SavedCountdowns, SavedStopwatch, Snapshot};
+use since::Since;
+use sound::{ActionSound, Cue, Player};
+use stopwatch::{crossed_cues, CueIntervals, Stopwatch, StopwatchCue, TickState};
+use style::Theme;
+use tabs::{Measurement, Tabs};
+use talk::{Talk, TalkEvent};
 
 const FPS: u64 = 30;
+// レース表示の間の更新頻度
+const RACING_FPS: u64 = 10;
+// 1/1000秒まで表示する場合は、表示の更新が滑らかに見えるよう更新頻度を上げる
+const FINE_FPS: u64 = 60;
 const MILLISEC: u64 = 1000;
-const MINUTE: u64 = 60;
-const HOUR: u64 = 60 * MINUTE;
+// 計測中に状態を自動保存する間隔
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(10);
+// 開発用に時計を1回の操作でずらす量
+#[cfg(all(feature = "debug-clock", debug_assertions))]
+const DEBUG_CLOCK_STEP: Duration = Duration::from_secs(10);
+// 起動時のウィンドウの大きさ(幅, 高さ)
+const WINDOW_SIZE: (u32, u32) = (600, 460);
+// タッチ操作向けの表示では、大きくしたボタンが収まるようウィンドウも大きくする
+const TOUCH_WINDOW_SIZE: (u32, u32) = (860, 680);
 
 // 外部からダウンロードしてきたフォントファイル(.ttf)を適用
-const FONT: Font = Font::External {
-    name: "PixelMplus12-Regular",
-    bytes: include_bytes!("../rsc/PixelMplus12-Regular.ttf"),
-};
+const FONT_BYTES: &[u8] = include_bytes!("../rsc/PixelMplus12-Regular.ttf");
+
+// 画面の文字に使うフォント。起動時に font_file を読み込んだ場合はそのフォントを使う
+static FONT: OnceLock<Font> = OnceLock::new();
+
+// 同梱のフォントを読み込めない場合は、画面が崩れないようicedの標準のフォントを使う
+fn font() -> Font {
+    *FONT.get_or_init(|| select_font("PixelMplus12-Regular", FONT_BYTES))
+}
+
+// 設定ファイルで指定したフォントを、画面を描く前に読み込む。読み込めない場合は同梱のフォントのままにする
+fn load_font_file(path: &Path) {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            log::warn!("failed to read the font {}: {}", path.display(), err);
+            return;
+        },
+    };
+    // icedのフォントは 'static のデータを参照するため、終了するまで保持し続ける
+    let bytes: &'static [u8] = Box::leak(bytes.into_boxed_slice());
+    match ab_glyph::FontRef::try_from_slice(bytes) {
+        Ok(_) => {
+            log::info!("using the font {}", path.display());
+            let _ = FONT.set(Font::External { name: "font_file", bytes });
+        },
+        Err(err) => log::warn!("failed to load the font {}: {}", path.display(), err),
+    }
+}
+
+// 起動時のウィンドウの大きさ。設定ファイルで指定した大きさを優先する
+fn window_size(config: &Config) -> (u32, u32) {
+    match config.window_size {
+        Some(size) => size,
+        None if config.touch_mode => TOUCH_WINDOW_SIZE,
+        None => WINDOW_SIZE,
+    }
+}
+
+// フォントとして解釈できるデータであれば外部のフォントとして、そうでなければ標準のフォントとして返す
+fn select_font(name: &'static str, bytes: &'static [u8]) -> Font {
+    match ab_glyph::FontRef::try_from_slice(bytes) {
+        Ok(_) => Font::External { name, bytes },
+        Err(err) => {
+            log::warn!("failed to load the bundled font {}: {}; falling back to the default font", name, err);
+            Font::Default
+        },
+    }
+}
+
+// 起動時にアプリケーションへ渡す、設定ファイルの内容とコマンドライン引数
+struct Flags {
+    config: Config,
+    args: Args,
+}
 
 // 今回のアプリケーションを司る構造体
+#[allow(clippy::upper_case_acronyms)]
 struct GUI {
-    tick_state: TickState,
+    config: Config,
+    label: String,
+    // プリセットを反映した、実際に使う表示形式
+    format: FormatOptions,
+    in_words: bool,
+    format_preset: FormatPreset,
+    format_preset_button_state: button::State,
+    // すべての効果音と通知を止めているか否か
+    mute_all: bool,
+    mode: Mode,
+    theme: Theme,
+    theme_button_state: button::State,
+    refresh: RefreshStrategy,
+    refresh_button_state: button::State,
+    // 最後に終えた計測の設定と、それを繰り返すボタン
+    last_session: Option<LastSession>,
+    repeat_button_state: button::State,
     start_stop_button_state: button::State,
     reset_button_state: button::State,
-    last_update: Instant,
-    total_duration: Duration,
+    // マウスのボタンを押してからのティック数。Resetボタンの長押しの判定に使う
+    press_ticks: u64,
+    // 計測していない状態で、最後に操作してからのティック数。大きな時計の表示に切り替える判定に使う
+    idle_ticks: u64,
+    mouse_pressed: bool,
+    // マウスのカーソルがウィンドウの上にあるか否か。経過時間を別の単位で表示する判定に使う
+    hovered: bool,
+    // 長押しでリセットした後、履歴も削除するか確認しているか否か
+    confirm_clear_history: bool,
+    clear_history_button_state: button::State,
+    keep_history_button_state: button::State,
+    // 設定を既定値に戻すか確認しているか否か
+    confirm_restore_defaults: bool,
+    restore_defaults_button_state: button::State,
+    // 計測の途中で Quit を押した際に、保存してから終了するか確認しているか否か
+    confirm_quit: bool,
+    quit_button_state: button::State,
+    save_and_quit_button_state: button::State,
+    quit_without_saving_button_state: button::State,
+    cancel_quit_button_state: button::State,
+    confirm_defaults_button_state: button::State,
+    cancel_defaults_button_state: button::State,
+    lap_button_state: button::State,
+    export_chart_button_state: button::State,
+    export_laps_button_state: button::State,
+    share_button_state: button::State,
+    // 各ラップを記録した時点の累計経過時間
+    laps: Laps,
+    lap_list_state: scrollable::State,
+    // ラップの一覧の行ごとのボタン。laps と同じ数だけ持つ
+    lap_row_states: Vec<button::State>,
+    // メモを編集するラップ。None の場合は最新のラップ
+    editing_lap: Option<usize>,
+    lap_note_state: text_input::State,
+    // 経過時間を直接指定して補正する入力欄
+    elapsed_input: String,
+    elapsed_input_state: text_input::State,
+    set_elapsed_button_state: button::State,
+    // 表示中のタブのストップウォッチの計時。カウントダウンもそれぞれ同じ計時で経過時間を数える
+    stopwatch: Stopwatch,
+    // ストップウォッチのタブ。表示していないタブの計測を持つ
+    tabs: Tabs,
+    // 最近記録した計測。履歴に記録するたびに読み込み直す
+    recent_sessions: Vec<history::Entry>,
+    export_history_button_state: button::State,
+    // 計測中の日付と、その日に履歴へ記録済みの計測時間の合計
+    today: NaiveDate,
+    today_base: Duration,
+    // 日付が変わった時点の累計経過時間。今日の分はこれを差し引いた値になる
+    session_offset: Duration,
+    // 一時停止していた時間のうち、履歴に記録済みの分
+    paused_offset: Duration,
+    clock: Box<dyn Clock>,
+    // 開発時に時計を手動でずらす操作(debug-clock フィーチャーのデバッグビルドのみ)
+    clock_shift: ClockShift,
+    jump_detector: JumpDetector,
+    countdowns: Vec<CountdownRow>,
+    interval: IntervalPanel,
+    since: Since,
+    // カウントダウンやインターバルを始める前の秒読み
+    lead_in: Option<LeadIn>,
+    // 確認待ちの終了の表示
+    banner: Option<Banner>,
+    // カウントダウンの終了のアラームが鳴っているか否か。Dismiss かいずれかのキーで止めるまで鳴らし続ける
+    alarm: bool,
+    dismiss_alarm_button_state: button::State,
+    // 読み込んだ一覧を連続で動かす場合の、カウントダウンの並び
+    chain: Option<Chain>,
+    // 発表のモードの表示の状態
+    talk: Talk,
+    next_countdown_id: usize,
+    // 前回の更新の時刻。秒読み・連続の待ち時間・インターバル・終了の表示を進める差分に使う
+    last_tick: Instant,
+    countdown_label: String,
+    countdown_label_state: text_input::State,
+    countdown_length: String,
+    countdown_length_state: text_input::State,
+    add_countdown_button_state: button::State,
+    // Import ボタンで読み込むファイル。--load で指定した場合はそのファイル
+    timers_path: std::path::PathBuf,
+    import_button_state: button::State,
+    status: Option<String>,
+    awake_guard: AwakeGuard,
+    control_receiver: Receiver<ControlCommand>,
+    menu_bar: MenuBar,
+    rpc_server: RpcServer,
+    _hotkey: GlobalHotkey,
+    // ラップを記録するキー
+    lap_key: Option<keyboard::KeyCode>,
+    // 計測をリセットするキー
+    reset_key: Option<keyboard::KeyCode>,
+    // ショートカットキーで一時停止したタイマー。再び押した際にこれらだけを再開する
+    paused_all: Option<PausedTimers>,
+    notifier: Notifier,
+    player: Player,
+    focus: Option<Focus>,
+    // 現在のウィンドウの大きさ(幅, 高さ)。ボタンの並べ方を自動で決める場合に使う
+    window_size: (u32, u32),
+    // 前回が異常終了していた場合に、復元するか否かを確認している自動保存の状態
+    recovery: Option<Snapshot>,
+    restore_button_state: button::State,
+    discard_button_state: button::State,
+    last_autosave: Instant,
 }
 
 #[derive(Debug, Clone)]
@@ -32,187 +294,3282 @@ pub enum Message {
     Start,  // 時間の測定を開始するメッセージ
     Stop,   // 時間の測定を停止するメッセージ
     Reset,  // 測定した時間をリセットするメッセージ
+    ResetCountdowns, // すべてのカウントダウンを設定(countdown_reset)にしたがってリセットするメッセージ
+    ResetPressed, // Resetボタンが押されたメッセージ。押していた長さによって履歴の削除も確認する
+    ClearHistory, // 履歴を削除するメッセージ
+    KeepHistory,  // 履歴を削除せずに確認を閉じるメッセージ
+    RestoreDefaults,        // 設定を既定値に戻すか確認するメッセージ
+    ConfirmRestoreDefaults, // 設定を既定値に戻して読み込み直すメッセージ
+    CancelRestoreDefaults,  // 設定を戻さずに確認を閉じるメッセージ
+    Quit,              // 終了するメッセージ。計測の途中であれば、保存するか確認する
+    SaveAndQuit,       // 計測の状態を保存して履歴に記録してから終了するメッセージ
+    QuitWithoutSaving, // 計測の状態を保存せずに終了するメッセージ
+    CancelQuit,        // 終了せずに確認を閉じるメッセージ
+    Lap,    // その時点の経過時間をラップとして記録するメッセージ
+    AddTab,           // 新しいストップウォッチのタブを加えて表示するメッセージ
+    SelectTab(usize), // 指定したストップウォッチのタブを表示するメッセージ
+    CloseTab,         // 表示中のストップウォッチのタブを閉じるメッセージ
+    EditLap(usize),         // 指定したラップのメモを編集するメッセージ
+    LapNoteChanged(String), // 編集中のラップのメモの入力
+    ElapsedInputChanged(String), // 補正する経過時間の入力
+    SubmitElapsed,               // 入力した経過時間を解釈して計測に反映するメッセージ
+    SetElapsed(Duration),        // 計測の経過時間を指定した値に置き換え、そこから数え続けるメッセージ
+    ExportChart, // ラップのスプリットを棒グラフの画像として書き出すメッセージ
+    ExportLaps,  // ラップの一覧をCSVとMarkdownのファイルとして書き出すメッセージ
+    ShareSession, // 計測の要約を共有用の画像として書き出すメッセージ
+    ExportHistory, // 記録したすべての履歴をCSVのファイルに書き出すメッセージ
     Update, // 測定した時間を更新するメッセージ
+    ToggleWords(bool), // 経過時間の文章表示を切り替えるメッセージ
+    ToggleMuteAll(bool), // すべての効果音と通知の消音を切り替えるメッセージ
+    NextTheme,         // 配色を次のテーマに切り替えるメッセージ
+    NextRefresh,       // 表示の更新頻度を次の方針に切り替えるメッセージ
+    NextFormatPreset,  // 経過時間の表示形式を次のプリセットに切り替えるメッセージ
+    RepeatLastSession, // 最後に終えた計測と同じ設定を、開始前の状態で用意するメッセージ
+    SetMode(Mode),     // 表示するモードを切り替えるメッセージ
+    CountdownLabelChanged(String),  // 追加するカウントダウンのラベルの入力
+    CountdownLengthChanged(String), // 追加するカウントダウンの時間の入力
+    AddCountdown,           // 入力内容でカウントダウンを追加するメッセージ
+    ImportCountdowns,       // ファイルに書いた一覧からカウントダウンを追加するメッセージ
+    ToggleCountdown(usize), // 指定したカウントダウンを停止・再開するメッセージ
+    ResetCountdown(usize),  // 指定したカウントダウンをリセットするメッセージ
+    RemoveCountdown(usize), // 指定したカウントダウンを削除するメッセージ
+    ToggleCountdownMute(usize), // 指定したカウントダウンの消音を切り替えるメッセージ
+    SinceInputChanged(String), // 経過時間を数える起点の日時の入力
+    SetSince,                  // 入力した日時を起点にするメッセージ
+    SkipLeadIn,     // 開始前の秒読みを飛ばしてすぐに開始するメッセージ
+    DismissBanner,  // 終了の表示を確認して閉じるメッセージ
+    ToggleInterval, // インターバルを停止・再開するメッセージ
+    ResetInterval,  // インターバルを最初に戻すメッセージ
+    JumpToRound(u32), // インターバルの指定したラウンドに移るメッセージ
+    StartBreak,       // 運動の後で待っていた休憩を始めるメッセージ
+    SkipBreak,        // 運動の後で待っていた休憩を飛ばし、次のラウンドに移るメッセージ
+    ToggleManualAdvance(bool), // 運動の後に休憩を始めるか選ぶまで待つか否かを切り替えるメッセージ
+    TogglePomodoro(bool),      // インターバルの設定とポモドーロの設定を切り替え、最初に戻すメッセージ
+    PhaseChanged(Phase),       // インターバルの区間が切り替わったことを知らせるメッセージ
+    Control(ControlCommand), // メニューバーなど外部からの操作を受け取るメッセージ
+    Event(Event), // キーボード・マウスなどのイベントを受け取るメッセージ
+    DumpState,      // 内部の状態をJSONで書き出すメッセージ
+    #[cfg(all(feature = "debug-clock", debug_assertions))]
+    ShiftClock(bool), // 開発用に時計を進める(trueの場合は戻す)メッセージ
+    RestoreSession, // 異常終了前の状態を復元するメッセージ
+    DiscardSession, // 異常終了前の状態を破棄するメッセージ
 }
 
-// 測定中か否かを管理するための条件
-pub enum TickState {
-    Init,
-    Stopped,
-    Ticking,
+// ショートカットキーでまとめて一時停止したタイマー
+struct PausedTimers {
+    stopwatch: bool,
+    countdowns: Vec<usize>,
+    interval: bool,
 }
 
-pub struct Timer {
-    duration: Duration,
-}
+impl GUI {
+    // 時刻の取得元を指定してGUIを初期化するメソッド。newからは実時間の時計を渡して呼ばれる
+    fn with_clock(config: Config, clock: Box<dyn Clock>) -> GUI {
+        let jump_detector = JumpDetector::new(clock.as_ref());
+        let (control_sender, control_receiver) = control::channel();
+        let rpc_server = RpcServer::start(control_sender.clone(), &config.grpc_address);
+        let config_hotkey = config.hotkey.clone();
+        let timers_path = config.timers_path();
+        // 解釈できないキーの名前が書かれていた場合は、そのショートカットキーを無効にする
+        let lap_key = keys::parse_key_code(&config.lap_key);
+        let reset_key = keys::parse_key_code(&config.reset_key);
+        let (format, in_words) = config.format_preset.apply(config.format, config.in_words);
+        let initial_size = window_size(&config);
+        if lap_key.is_none() && !config.lap_key.is_empty() {
+            log::warn!("unsupported lap key: {:?}", config.lap_key);
+        }
+        if reset_key.is_none() && !config.reset_key.is_empty() {
+            log::warn!("unsupported reset key: {:?}", config.reset_key);
+        }
 
-impl Timer {
-    fn new(duration: Duration) -> Timer {
-        Timer {duration}
+        GUI {
+            label: config.label.clone(),
+            format,
+            in_words,
+            format_preset: config.format_preset,
+            format_preset_button_state: button::State::new(),
+            mute_all: config.mute,
+            mode: config.mode,
+            interval: IntervalPanel::new(config.interval, config.completion_hold, config.manual_phase_advance),
+            since: Since::new(config.since.as_deref()),
+            lead_in: None,
+            banner: None,
+            alarm: false,
+            dismiss_alarm_button_state: button::State::new(),
+            chain: None,
+            talk: Talk::new(),
+            theme: config.theme,
+            theme_button_state: button::State::new(),
+            refresh: config.refresh,
+            refresh_button_state: button::State::new(),
+            last_session: LastSession::from_history(),
+            repeat_button_state: button::State::new(),
+            config,
+            start_stop_button_state: button::State::new(),
+            reset_button_state: button::State::new(),
+            press_ticks: 0,
+            idle_ticks: 0,
+            mouse_pressed: false,
+            hovered: false,
+            confirm_clear_history: false,
+            clear_history_button_state: button::State::new(),
+            keep_history_button_state: button::State::new(),
+            confirm_restore_defaults: false,
+            restore_defaults_button_state: button::State::new(),
+            confirm_quit: false,
+            quit_button_state: button::State::new(),
+            save_and_quit_button_state: button::State::new(),
+            quit_without_saving_button_state: button::State::new(),
+            cancel_quit_button_state: button::State::new(),
+            confirm_defaults_button_state: button::State::new(),
+            cancel_defaults_button_state: button::State::new(),
+            lap_button_state: button::State::new(),
+            export_chart_button_state: button::State::new(),
+            export_laps_button_state: button::State::new(),
+            share_button_state: button::State::new(),
+            laps: Laps::default(),
+            lap_list_state: scrollable::State::new(),
+            lap_row_states: Vec::new(),
+            editing_lap: None,
+            lap_note_state: text_input::State::new(),
+            elapsed_input: String::new(),
+            elapsed_input_state: text_input::State::new(),
+            set_elapsed_button_state: button::State::new(),
+            stopwatch: Stopwatch::new(clock.as_ref()),
+            tabs: Tabs::new(),
+            today: local_date(clock.as_ref()),
+            recent_sessions: Vec::new(),
+            export_history_button_state: button::State::new(),
+            today_base: Duration::default(),
+            session_offset: Duration::default(),
+            paused_offset: Duration::default(),
+            countdowns: Vec::new(),
+            next_countdown_id: 0,
+            last_tick: clock.now(),
+            countdown_label: String::new(),
+            countdown_label_state: text_input::State::new(),
+            countdown_length: String::new(),
+            countdown_length_state: text_input::State::new(),
+            add_countdown_button_state: button::State::new(),
+            timers_path,
+            import_button_state: button::State::new(),
+            status: None,
+            awake_guard: AwakeGuard::new(),
+            control_receiver,
+            menu_bar: MenuBar::new(control_sender.clone()),
+            rpc_server,
+            _hotkey: GlobalHotkey::register(control_sender.clone(), &config_hotkey),
+            lap_key,
+            reset_key,
+            paused_all: None,
+            notifier: Notifier::new(control_sender),
+            player: Player::new(),
+            focus: None,
+            window_size: initial_size,
+            recovery: None,
+            restore_button_state: button::State::new(),
+            discard_button_state: button::State::new(),
+            last_autosave: clock.now(),
+            clock,
+            clock_shift: ClockShift::default(),
+            jump_detector,
+        }
     }
-}
 
-impl<H, E> iced_native::subscription::Recipe<H, E> for Timer where H: std::hash::Hasher {
-    // Streamから出力される型
-    type Output = Instant;
+    // 読み込み直した設定を反映する。計測中の状態や表示中のモードはそのまま残す
+    // ショートカットキーやgRPCのアドレスなど、起動時に登録するものは次回の起動から反映される
+    fn apply_config(&mut self, config: Config) {
+        self.theme = config.theme.or_available();
+        self.refresh = config.refresh;
+        self.format_preset = config.format_preset;
+        let (format, in_words) = config.format_preset.apply(config.format, config.in_words);
+        self.format = format;
+        self.in_words = in_words;
+        self.mute_all = config.mute;
+        self.lap_key = keys::parse_key_code(&config.lap_key);
+        self.reset_key = keys::parse_key_code(&config.reset_key);
+        self.recent_sessions = history::recent(config.recent_sessions);
+        self.timers_path = config.timers_path();
+        self.interval.set_manual_advance(config.manual_phase_advance);
+        self.config = config;
+    }
 
-    // それぞれのSubscriptionをハッシュで比較できるようにするためのメソッド
-    fn hash(&self, state: &mut H) {
-        use std::hash::Hash;
+    // 入力欄の内容からカウントダウンを追加するメソッド
+    fn add_countdown(&mut self) {
+        let length = match parse_duration(&self.countdown_length) {
+            Some(length) if length > Duration::default() => length,
+            _ => {
+                self.status = Some(format!("Invalid duration: {}", self.countdown_length));
+                return;
+            },
+        };
 
-        // ハッシュ計算用の値はなんでもよいので、今回の計測するdurationをセットしている
-        std::any::TypeId::of::<Self>().hash(state);
-        self.duration.hash(state)
+        let label = self.countdown_label.clone();
+        self.push_countdown(&label, length);
+        self.countdown_label.clear();
+        self.countdown_length.clear();
+        self.status = None;
     }
 
-    // Recipeを実行し、Subscriptionのイベントを出力するStreamを作り出すためのメソッド
-    fn stream(self: Box<Self>, _input: futures::stream::BoxStream<E>)
-        -> futures::stream::BoxStream<Self::Output> {
-            use futures::stream::StreamExt;
+    // ファイルに書いたカウントダウンの一覧を読み込み、すべて開始していない状態で追加する
+    // 不正な項目がある場合は1件も追加せず、不正な項目を状態の表示に挙げる
+    fn import_countdowns(&mut self) {
+        match batch::load(&self.timers_path) {
+            Ok(timers) => {
+                log::info!("imported {} countdowns from {}", timers.len(), self.timers_path.display());
+                let first_id = self.next_countdown_id;
+                for timer in &timers {
+                    self.push_ready_countdown(&timer.name, timer.duration);
+                }
+                // 連続で動かす設定では、最初のものを始めると残りが順に自動で始まる
+                if self.config.chain.enabled && !timers.is_empty() {
+                    self.chain = Some(Chain::new((first_id..self.next_countdown_id).collect(), self.config.chain));
+                }
+                self.status = Some(format!("Imported {} timers", timers.len()));
+            },
+            Err(err) => {
+                log::warn!("failed to import countdowns: {}", err);
+                self.status = Some(format!("Import failed: {}", err));
+            },
+        }
+    }
 
-            // 一定間隔で現在時刻を返す
-            async_std::stream::interval(self.duration)
-                .map(|_| Instant::now())
-                .boxed()
+    // カウントダウンを追加して開始する
+    fn push_countdown(&mut self, label: &str, length: Duration) {
+        let mut countdown = self.new_countdown(label, length);
+        // 秒読みをする場合は、停止した状態で追加して秒読みの後に開始する
+        if self.config.lead_in.enabled {
+            countdown.toggle(self.clock.as_ref());
+            self.begin_lead_in(LeadInTarget::Countdown(countdown.id));
+        }
+        self.countdowns.push(countdown);
     }
-}
 
-// 構造体GUIにApplicationトレイトを実装
-impl Application for GUI {
-    type Executor = executor::Default;
-    type Message = Message;
-    type Flags = ();
+    // カウントダウンを開始していない状態で追加する
+    fn push_ready_countdown(&mut self, label: &str, length: Duration) {
+        let mut countdown = self.new_countdown(label, length);
+        countdown.toggle(self.clock.as_ref());
+        self.countdowns.push(countdown);
+    }
 
-    // new runした際に、icedの内部で使われる初期化のためのメソッド
-    fn new(_flags: Self::Flags) -> (Self, Command<Self::Message>) {
-        (
-            GUI {
-                tick_state: TickState::Init,
-                start_stop_button_state: button::State::new(),
-                reset_button_state: button::State::new(),
-                last_update: Instant::now(),
-                total_duration: Duration::default(),
-            },
-            Command::none(),
-        )
+    // 次の番号でカウントダウンを作成する。ラベルが空の場合は番号から付ける
+    fn new_countdown(&mut self, label: &str, length: Duration) -> CountdownRow {
+        let label = if label.trim().is_empty() {
+            format!("Timer {}", self.next_countdown_id + 1)
+        } else {
+            label.trim().to_string()
+        };
+
+        log::debug!("countdown {} added: {} ({:?})", self.next_countdown_id, label, length);
+        let countdown = CountdownRow::new(Countdown::new(self.next_countdown_id, label, length, self.clock.as_ref()));
+        self.next_countdown_id += 1;
+        countdown
     }
 
-    // title ウィンドウのタイトル
-    fn title(&self) -> String {
-        String::from("DEMO")
+    // 終わっていないカウントダウンを、次回の起動時に復元できるよう保存する
+    fn save_countdowns(&self) {
+        let countdowns = self
+            .countdowns
+            .iter()
+            .filter(|countdown| !countdown.is_finished())
+            .map(|countdown| SavedCountdown {
+                label: countdown.label.clone(),
+                length_millis: countdown.length().as_millis() as u64,
+                remaining_millis: countdown.remaining().as_millis() as u64,
+                running: countdown.is_running(),
+            })
+            .collect();
+        session::save_countdowns(&SavedCountdowns { saved_at: unix_seconds(self.clock.system_now()), countdowns });
     }
 
-    // update ランタイムシステムからメッセージを受け取り、そのメッセージによってアプリケーションの状態を
-    // 更新するメソッド
-    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
-        match message {
-            Message::Start => {
-                // Startボタン押下時、状態をTickingに切り替え、最終更新時刻に現在時刻をセット
-                self.tick_state = TickState::Ticking;
-                self.last_update = Instant::now();
-            },
+    // 前回の終了時に保存したカウントダウンを、停止した状態(設定によっては動作中)で復元する
+    // 閉じていた間も数える設定では、その間に終わったはずのものは復元せずに知らせる
+    fn resume_countdowns(&mut self, saved: SavedCountdowns) {
+        let resume = self.config.resume_countdowns;
+        let closed = Duration::from_secs(unix_seconds(self.clock.system_now()).saturating_sub(saved.saved_at));
 
-            Message::Stop => {
-                // Stopボタン押下時、状態をStoppedに切り替え、累計経過時間に現在時刻と最終更新時刻の差分をセット
-                self.tick_state = TickState::Stopped;
-                self.total_duration += Instant::now() - self.last_update;
-            },
+        let mut finished = Vec::new();
+        for entry in saved.countdowns {
+            let mut remaining = Duration::from_millis(entry.remaining_millis);
+            if entry.running && resume.count_while_closed {
+                remaining = remaining.checked_sub(closed).unwrap_or_default();
+            }
+            if remaining == Duration::default() {
+                finished.push(entry.label);
+                continue;
+            }
 
-            Message::Reset => {
-                // Resetボタン押下時、最終更新時刻・累計経過時間をリセット
-                self.last_update = Instant::now();
-                self.total_duration = Duration::default();
-                self.tick_state = TickState::Init;
+            let mut countdown = self.new_countdown(&entry.label, Duration::from_millis(entry.length_millis));
+            countdown.set_remaining(remaining, self.clock.as_ref());
+            if !(entry.running && resume.auto_resume) {
+                countdown.toggle(self.clock.as_ref());
+            }
+            log::info!("resumed countdown {}: {:?} left", countdown.label, remaining);
+            self.countdowns.push(countdown);
+        }
+
+        if !finished.is_empty() {
+            self.status = Some(format!("Finished while closed: {}", finished.join(", ")));
+        }
+        if !self.countdowns.is_empty() || !finished.is_empty() {
+            self.mode = Mode::Countdown;
+        }
+    }
+
+    // 前回の実行で保存した状態を、設定にしたがって復元する
+    // 異常終了前の自動保存がある場合は復元するかを確認し、終了時に保存したストップウォッチの計測よりそちらを優先する
+    fn resume(&mut self, recovery: Option<Snapshot>, countdowns: Option<SavedCountdowns>, stopwatch: Option<SavedStopwatch>) {
+        self.recovery = recovery;
+        if let Some(saved) = countdowns.filter(|_| self.config.resume_countdowns.enabled) {
+            self.resume_countdowns(saved);
+        }
+        if let Some(saved) = stopwatch.filter(|_| self.config.resume_stopwatch.enabled && self.recovery.is_none()) {
+            self.resume_stopwatch(saved);
+        }
+    }
+
+    // ストップウォッチの計測を、次回の起動時に続きから再開できるよう保存する
+    fn save_stopwatch(&self) {
+        let started_at = self.stopwatch.started_at().map(unix_seconds);
+        session::save_stopwatch(&SavedStopwatch {
+            saved_at: unix_seconds(self.clock.system_now()),
+            running: self.stopwatch.is_running(),
+            snapshot: Snapshot::new(self.stopwatch.total_duration(), self.paused_total(), &self.laps, self.mode, started_at),
+        });
+    }
+
+    // 前回の終了時に保存したストップウォッチの計測を、停止した状態(設定によっては計測中)で復元する
+    // 閉じていた間も数える設定では、計測中に閉じていた場合にその間の分も経過時間に加える
+    // 終了時までの分は履歴に記録済みのため、ここから先の分のみを記録する
+    fn resume_stopwatch(&mut self, saved: SavedStopwatch) {
+        let resume = self.config.resume_stopwatch;
+        let closed = Duration::from_secs(unix_seconds(self.clock.system_now()).saturating_sub(saved.saved_at));
+
+        let snapshot = saved.snapshot;
+        self.session_offset = snapshot.total_duration();
+        let mut elapsed = snapshot.total_duration();
+        if saved.running && resume.count_while_closed {
+            elapsed += closed;
+        }
+        self.paused_offset = snapshot.paused_duration();
+        self.set_laps(snapshot.laps());
+        self.mode = snapshot.mode().unwrap_or(self.mode);
+        let started_at = snapshot.started_at().map(|secs| std::time::UNIX_EPOCH + Duration::from_secs(secs));
+        let running = saved.running && resume.auto_resume;
+        self.stopwatch.restore(elapsed, snapshot.paused_duration(), started_at, running, self.clock.as_ref());
+        log::info!("resumed stopwatch: {:?} (running: {})", self.stopwatch.total_duration(), saved.running);
+        self.status = Some(format!("Resumed at {}", format_duration(self.stopwatch.total_duration(), &self.format)));
+    }
+
+    // 最後に終えた計測と同じモード・ラベル・長さを、開始していない状態で用意する
+    fn repeat_last_session(&mut self) {
+        let session = match self.last_session.clone() {
+            Some(session) => session,
+            None => return,
+        };
+        log::debug!("repeat: {:?}", session);
+
+        self.update(Message::SetMode(session.mode));
+        match session.mode {
+            Mode::Stopwatch => {
+                if self.stopwatch.tick_state() != TickState::Init {
+                    self.update(Message::Reset);
+                }
+                self.label = session.label.clone();
             },
+            Mode::Countdown => {
+                if let Some(length) = session.length {
+                    self.push_ready_countdown(&session.label, length);
+                }
+            },
+            Mode::Interval => self.interval.reset(),
+            Mode::Since | Mode::Clock | Mode::Talk => {},
+        }
+        self.status = Some(format!("Ready: {}", session.label));
+    }
 
-            Message::Update => match self.tick_state {
-                // 時間計測時、状態がTickingの場合のみ、
-                // 累計経過時間を現在時刻と最終更新時刻の差分をセットした後、最終更新時刻を現在時刻に更新
-                TickState::Ticking => {
-                    let now_update = Instant::now();
-                    self.total_duration += now_update - self.last_update;
-                    self.last_update = now_update;
-                },
-                _ => {}
+    // 開始前の秒読みを始める。別の秒読みの途中であれば、そちらはすぐに開始する
+    fn begin_lead_in(&mut self, target: LeadInTarget) {
+        if let Some(previous) = self.lead_in.take() {
+            self.start_lead_in_target(previous.target);
+        }
+        log::debug!("lead-in for {:?}", target);
+        self.lead_in = Some(LeadIn::new(target, self.config.lead_in.length));
+        self.play(Cue::Count);
+    }
+
+    // 秒読みの対象のタイマーを開始する。秒読みの間に削除されたり開始されたりしていれば何もしない
+    fn start_lead_in_target(&mut self, target: LeadInTarget) {
+        match target {
+            LeadInTarget::Countdown(id) => {
+                if let Some(countdown) = self.countdowns.iter_mut().find(|c| c.id == id && !c.is_running()) {
+                    countdown.toggle(self.clock.as_ref());
+                }
+            },
+            LeadInTarget::Interval => {
+                if !self.interval.is_running() {
+                    self.interval.toggle();
+                }
             },
         }
+    }
 
-        Command::none()
+    // 秒読みをせずにすぐに開始する
+    fn skip_lead_in(&mut self) {
+        if let Some(lead_in) = self.lead_in.take() {
+            log::debug!("lead-in skipped for {:?}", lead_in.target);
+            self.start_lead_in_target(lead_in.target);
+        }
     }
 
-    // view ウィンドウに表示するウィジェットを設定するためのメソッド
-    fn view(&mut self) -> Element<'_, Self::Message> {
-        let seconds = self.total_duration.as_secs();
+    // 終了する際に、計測の状態を保存して履歴に記録する。復元の確認中は前回の状態を残すため何もしない
+    fn save_on_exit(&mut self) {
+        if self.recovery.is_some() {
+            return;
+        }
 
-        // display texts
-        let duration_text = format!(
-            "{:0>2}:{:0>2}:{:0>2}.{:0>2}",
-            seconds / HOUR,
-            (seconds % HOUR) / MINUTE,
-            seconds % HOUR,
-            self.total_duration.subsec_millis() / 10
-        );
+        self.autosave();
+        if self.config.resume_countdowns.enabled {
+            self.save_countdowns();
+        }
+        // 計測中に閉じた場合は、直前の更新から閉じるまでの分も含めて記録する
+        self.stopwatch.advance(self.clock.now());
+        if self.config.resume_stopwatch.enabled {
+            self.save_stopwatch();
+        }
+        self.record_session();
+        session::mark_clean();
+    }
 
-        let start_stop_text = match self.tick_state {
-            TickState::Init => Text::new("Start")
-                .horizontal_alignment(HorizontalAlignment::Center)
-                .font(FONT),
-            TickState::Stopped => Text::new("Restart")
-                .horizontal_alignment(HorizontalAlignment::Center)
-                .font(FONT),
-            TickState::Ticking => Text::new("Stop")
-                .horizontal_alignment(HorizontalAlignment::Center)
-                .font(FONT),
+    // 終了する前に確認する、計測中か経過時間のある計測があるか否か
+    fn has_unsaved_measurement(&self) -> bool {
+        self.stopwatch.is_running()
+            || self.stopwatch.total_duration() > Duration::default()
+            || self.tabs.any_running()
+            || self.countdowns.iter().any(|countdown| countdown.is_running())
+    }
+
+    // 現在の状態を自動保存する。復元の確認中は、前回の状態を上書きしないよう保存しない
+    fn autosave(&mut self) {
+        if self.recovery.is_some() {
+            return;
+        }
+
+        let elapsed = self.stopwatch.elapsed(self.clock.as_ref());
+        let started_at = self.stopwatch.started_at().map(unix_seconds);
+        session::save(&Snapshot::new(elapsed, self.paused_total(), &self.laps, self.mode, started_at));
+        self.last_autosave = self.clock.now();
+    }
+
+    // Tabキーでフォーカスを移動する順番。画面上の並びと一致させる
+    fn focus_order(&self) -> Vec<Focus> {
+        if self.recovery.is_some() {
+            return vec![Focus::Restore, Focus::Discard];
+        }
+        if self.banner.is_some() {
+            return vec![Focus::DismissBanner];
+        }
+
+        let mut order: Vec<Focus> = Mode::ALL.iter().map(|&mode| Focus::Mode(mode)).collect();
+        if self.alarm {
+            order.push(Focus::DismissBanner);
+        }
+        if self.lead_in.is_some() {
+            order.push(Focus::SkipLeadIn);
+        }
+        match self.mode {
+            Mode::Stopwatch => {
+                order.extend((0..self.tabs.len()).map(Focus::Tab));
+                order.push(Focus::AddTab);
+                if self.tabs.len() > 1 {
+                    order.push(Focus::CloseTab);
+                }
+                order.extend_from_slice(&[Focus::StartStop, Focus::Reset]);
+                if self.confirm_clear_history {
+                    order.extend_from_slice(&[Focus::ClearHistory, Focus::KeepHistory]);
+                }
+                order.extend_from_slice(&[Focus::Lap, Focus::ExportChart, Focus::ExportLaps, Focus::Share]);
+                if !self.recent_sessions.is_empty() {
+                    order.push(Focus::ExportHistory);
+                }
+                if self.format_preset == FormatPreset::Custom {
+                    order.push(Focus::Words);
+                }
+                order.extend_from_slice(&[Focus::ElapsedInput, Focus::SetElapsed]);
+                if !self.laps.is_empty() {
+                    order.push(Focus::LapNote);
+                }
+            },
+            Mode::Countdown => {
+                for countdown in &self.countdowns {
+                    order.push(Focus::CountdownToggle(countdown.id));
+                    order.push(Focus::CountdownMute(countdown.id));
+                    order.push(Focus::CountdownRemove(countdown.id));
+                }
+                order.extend_from_slice(&[Focus::CountdownLabel, Focus::CountdownLength, Focus::AddCountdown]);
+                order.push(Focus::ImportCountdowns);
+                order.push(Focus::MuteAll);
+            },
+            Mode::Interval => {
+                if let Some((first, count)) = self.interval.current_set() {
+                    order.extend((first..first + count).map(Focus::IntervalCycle));
+                }
+                if self.interval.is_awaiting_break() {
+                    order.extend_from_slice(&[Focus::StartBreak, Focus::SkipBreak]);
+                }
+                order.extend_from_slice(&[Focus::IntervalToggle, Focus::IntervalReset, Focus::Pomodoro, Focus::ManualAdvance]);
+            },
+            Mode::Since => order.extend_from_slice(&[Focus::SinceInput, Focus::SetSince]),
+            Mode::Talk => order.extend_from_slice(&[Focus::StartStop, Focus::Reset]),
+            Mode::Clock => {},
+        }
+        order.extend_from_slice(&[Focus::Theme, Focus::Refresh, Focus::FormatPreset]);
+        if self.last_session.is_some() {
+            order.push(Focus::Repeat);
+        }
+        order.extend_from_slice(&[Focus::RestoreDefaults, Focus::Quit]);
+        if self.confirm_restore_defaults {
+            order.extend_from_slice(&[Focus::ConfirmDefaults, Focus::CancelDefaults]);
+        }
+        if self.confirm_quit {
+            order.extend_from_slice(&[Focus::SaveAndQuit, Focus::QuitWithoutSaving, Focus::CancelQuit]);
+        }
+        order
+    }
+
+    // 現在フォーカスしているウィジェット。入力欄はマウスでもフォーカスされるため、その状態も考慮する
+    fn current_focus(&self) -> Option<Focus> {
+        if self.countdown_label_state.is_focused() {
+            Some(Focus::CountdownLabel)
+        } else if self.countdown_length_state.is_focused() {
+            Some(Focus::CountdownLength)
+        } else if self.since.input_state.is_focused() {
+            Some(Focus::SinceInput)
+        } else if self.lap_note_state.is_focused() {
+            Some(Focus::LapNote)
+        } else if self.elapsed_input_state.is_focused() {
+            Some(Focus::ElapsedInput)
+        } else {
+            self.focus
+        }
+    }
+
+    // 入力欄のいずれかにフォーカスがあるか否か
+    fn is_typing(&self) -> bool {
+        matches!(
+            self.current_focus(),
+            Some(Focus::CountdownLabel)
+                | Some(Focus::CountdownLength)
+                | Some(Focus::SinceInput)
+                | Some(Focus::LapNote)
+                | Some(Focus::ElapsedInput)
+        )
+    }
+
+    // フォーカスを移し、入力欄のフォーカス状態もそれに合わせる
+    fn set_focus(&mut self, focus: Option<Focus>) {
+        self.focus = focus;
+        self.countdown_label_state = if focus == Some(Focus::CountdownLabel) {
+            text_input::State::focused()
+        } else {
+            text_input::State::new()
+        };
+        self.countdown_length_state = if focus == Some(Focus::CountdownLength) {
+            text_input::State::focused()
+        } else {
+            text_input::State::new()
         };
+        self.since.input_state = if focus == Some(Focus::SinceInput) {
+            text_input::State::focused()
+        } else {
+            text_input::State::new()
+        };
+        self.lap_note_state = if focus == Some(Focus::LapNote) {
+            text_input::State::focused()
+        } else {
+            text_input::State::new()
+        };
+        self.elapsed_input_state = if focus == Some(Focus::ElapsedInput) {
+            text_input::State::focused()
+        } else {
+            text_input::State::new()
+        };
+    }
+
+    // ラップの一覧を置き換え、行ごとのボタンの数もそれに合わせる
+    fn set_laps(&mut self, laps: Vec<Lap>) {
+        self.lap_row_states = laps.iter().map(|_| button::State::new()).collect();
+        self.laps = Laps::new(laps);
+        self.editing_lap = None;
+    }
+
+    // メモを編集しているラップの位置
+    fn editing_lap(&self) -> Option<usize> {
+        self.editing_lap
+            .filter(|&index| index < self.laps.len())
+            .or_else(|| self.laps.len().checked_sub(1))
+    }
 
-        let start_stop_message = match self.tick_state {
+    // ストップウォッチ・発表のモードの Start/Stop ボタンを押した際のメッセージ
+    fn start_stop_message(&self) -> Message {
+        match self.stopwatch.tick_state() {
             TickState::Init | TickState::Stopped => Message::Start,
             TickState::Ticking => Message::Stop,
+        }
+    }
+
+    // Space キーで開始・停止する計測。ボタンが1つにまとまらないカウントダウンなどのモードでは何もしない
+    fn toggle_shortcut(&self) -> Option<Message> {
+        match self.mode {
+            Mode::Stopwatch | Mode::Talk => Some(self.start_stop_message()),
+            Mode::Interval => Some(Message::ToggleInterval),
+            Mode::Countdown | Mode::Since | Mode::Clock => None,
+        }
+    }
+
+    // リセットのキーで戻す計測。リセットするものがないモードでは何もしない
+    fn reset_shortcut(&self) -> Option<Message> {
+        match self.mode {
+            Mode::Stopwatch | Mode::Talk => Some(Message::Reset),
+            Mode::Countdown => Some(Message::ResetCountdowns),
+            Mode::Interval => Some(Message::ResetInterval),
+            Mode::Since | Mode::Clock => None,
+        }
+    }
+
+    // フォーカス中のウィジェットをEnter/Spaceで操作した際のメッセージ
+    // 入力欄はEnterで追加(on_submit)、Spaceで空白の入力となるため、ここでは扱わない
+    fn focused_message(&self) -> Option<Message> {
+        match self.current_focus()? {
+            Focus::Restore => Some(Message::RestoreSession),
+            Focus::Discard => Some(Message::DiscardSession),
+            Focus::Mode(mode) => Some(Message::SetMode(mode)),
+            Focus::Tab(index) => Some(Message::SelectTab(index)),
+            Focus::AddTab => Some(Message::AddTab),
+            Focus::CloseTab => Some(Message::CloseTab),
+            Focus::StartStop => Some(self.start_stop_message()),
+            Focus::Reset => Some(Message::Reset),
+            Focus::ClearHistory => Some(Message::ClearHistory),
+            Focus::KeepHistory => Some(Message::KeepHistory),
+            Focus::RestoreDefaults => Some(Message::RestoreDefaults),
+            Focus::ConfirmDefaults => Some(Message::ConfirmRestoreDefaults),
+            Focus::CancelDefaults => Some(Message::CancelRestoreDefaults),
+            Focus::Quit => Some(Message::Quit),
+            Focus::SaveAndQuit => Some(Message::SaveAndQuit),
+            Focus::QuitWithoutSaving => Some(Message::QuitWithoutSaving),
+            Focus::CancelQuit => Some(Message::CancelQuit),
+            Focus::Lap => Some(Message::Lap),
+            Focus::ExportChart => Some(Message::ExportChart),
+            Focus::ExportLaps => Some(Message::ExportLaps),
+            Focus::Share => Some(Message::ShareSession),
+            Focus::ExportHistory => Some(Message::ExportHistory),
+            Focus::Words => Some(Message::ToggleWords(!self.in_words)),
+            Focus::Theme => Some(Message::NextTheme),
+            Focus::Refresh => Some(Message::NextRefresh),
+            Focus::FormatPreset => Some(Message::NextFormatPreset),
+            Focus::Repeat => Some(Message::RepeatLastSession),
+            Focus::CountdownToggle(id) => Some(Message::ToggleCountdown(id)),
+            Focus::CountdownMute(id) => Some(Message::ToggleCountdownMute(id)),
+            Focus::MuteAll => Some(Message::ToggleMuteAll(!self.mute_all)),
+            Focus::CountdownRemove(id) => Some(Message::RemoveCountdown(id)),
+            Focus::AddCountdown => Some(Message::AddCountdown),
+            Focus::ImportCountdowns => Some(Message::ImportCountdowns),
+            Focus::SkipLeadIn => Some(Message::SkipLeadIn),
+            Focus::DismissBanner => Some(Message::DismissBanner),
+            Focus::IntervalToggle => Some(Message::ToggleInterval),
+            Focus::IntervalReset => Some(Message::ResetInterval),
+            Focus::StartBreak => Some(Message::StartBreak),
+            Focus::SkipBreak => Some(Message::SkipBreak),
+            Focus::ManualAdvance => Some(Message::ToggleManualAdvance(!self.config.manual_phase_advance)),
+            Focus::Pomodoro => Some(Message::TogglePomodoro(!self.interval.is_pomodoro())),
+            Focus::IntervalCycle(round) => Some(Message::JumpToRound(round)),
+            Focus::SetSince => Some(Message::SetSince),
+            Focus::SetElapsed => Some(Message::SubmitElapsed),
+            Focus::CountdownLabel | Focus::CountdownLength | Focus::SinceInput | Focus::LapNote | Focus::ElapsedInput => None,
+        }
+    }
+
+    // キーボード・マウスのイベントを処理し、必要であればボタン操作と同じメッセージを返す
+    fn handle_event(&mut self, event: Event) -> Option<Message> {
+        // 大きな時計の表示中の操作は、元の表示に戻すだけでボタンなどの操作としては扱わない
+        if let Event::Keyboard(_) | Event::Mouse(_) = event {
+            let was_ambient = self.is_ambient();
+            self.idle_ticks = 0;
+            if was_ambient {
+                return None;
+            }
+        }
+
+        // 終了の表示中やアラームが鳴っている間は、どのキーを押しても Dismiss ボタンと同じく確認したものとして閉じる
+        if let Event::Keyboard(keyboard::Event::Input { state: ButtonState::Pressed, .. }) = event {
+            if self.banner.is_some() || self.alarm {
+                return Some(Message::DismissBanner);
+            }
+        }
+
+        match event {
+            Event::Keyboard(keyboard::Event::Input {
+                state: ButtonState::Pressed,
+                key_code,
+                modifiers,
+            }) => match key_code {
+                keyboard::KeyCode::Tab => {
+                    let next = focus::next(&self.focus_order(), self.current_focus(), modifiers.shift);
+                    self.set_focus(next);
+                    None
+                },
+                keyboard::KeyCode::Enter | keyboard::KeyCode::NumpadEnter => self.focused_message(),
+                // どこにもフォーカスがない場合は、表示中のモードの計測を開始・停止する
+                keyboard::KeyCode::Space if self.current_focus().is_none() => self.toggle_shortcut(),
+                keyboard::KeyCode::Space => self.focused_message(),
+                keyboard::KeyCode::Escape => {
+                    self.set_focus(None);
+                    None
+                },
+                // 不具合の報告用に、内部の状態を標準エラー出力に書き出す
+                keyboard::KeyCode::F12 => Some(Message::DumpState),
+                // 開発用に、時計を戻す・進める
+                #[cfg(all(feature = "debug-clock", debug_assertions))]
+                keyboard::KeyCode::F9 => Some(Message::ShiftClock(true)),
+                #[cfg(all(feature = "debug-clock", debug_assertions))]
+                keyboard::KeyCode::F10 => Some(Message::ShiftClock(false)),
+                // 文字のキーは入力欄への入力と重なるため、入力欄にフォーカスがない場合のみ扱う
+                // 計測中でなければ Lap ボタンと同じく何もしない
+                code if Some(code) == self.lap_key && self.mode == Mode::Stopwatch && !self.is_typing() => {
+                    Some(Message::Lap)
+                },
+                code if Some(code) == self.reset_key && !self.is_typing() => self.reset_shortcut(),
+                _ => None,
+            },
+
+            // マウスで操作した場合はキーボードのフォーカス表示を消す
+            // 入力欄のフォーカスはicedがクリック位置に応じて切り替えるため、ここでは変更しない
+            // 押している長さはUpdateごとに数え、離した後も次に押すまで残しておく
+            // (Resetボタンのメッセージとこのイベントのどちらが先に処理されても判定できるようにするため)
+            Event::Mouse(mouse::Event::Input { state: ButtonState::Pressed, .. }) => {
+                self.focus = None;
+                self.mouse_pressed = true;
+                self.press_ticks = 0;
+                None
+            },
+
+            Event::Mouse(mouse::Event::Input { state: ButtonState::Released, .. }) => {
+                self.mouse_pressed = false;
+                None
+            },
+
+            Event::Mouse(mouse::Event::CursorEntered) | Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                self.hovered = true;
+                None
+            },
+
+            Event::Mouse(mouse::Event::CursorLeft) => {
+                self.hovered = false;
+                None
+            },
+
+            Event::Window(window::Event::Resized { width, height }) => {
+                self.window_size = (width, height);
+                None
+            },
+
+            _ => None,
+        }
+    }
+
+    // カウントダウンが終了した際に、終了したもののラベルとともに呼ばれるメソッド
+    fn on_countdown_finished(&mut self, id: usize, label: &str) {
+        log::debug!("countdown finished: {}", label);
+        self.status = Some(format!("{} finished", label));
+        let length = self.countdowns.iter().find(|countdown| countdown.id == id).map(|countdown| countdown.length());
+        self.record_countdown(id, true);
+        self.last_session = Some(LastSession {
+            mode: Mode::Countdown,
+            label: label.to_string(),
+            length,
+        });
+        let muted = self.is_countdown_muted(id);
+        if !muted {
+            self.notifier.notify_finished(id, label);
+            self.start_alarm();
+        }
+        // アラームを確認するまで鳴らし続けるため、終了の表示からは音を鳴らし直さない
+        self.show_banner(label, false);
+
+        if let Some(next) = self.chain.as_mut().and_then(|chain| chain.on_finished(id)) {
+            self.start_chained(next);
+        }
+    }
+
+    // 連続で動かしている一覧の、次に始まるカウントダウンの表示。動いているものも待っているものもない場合はNone
+    fn chain_text(&self, format: &FormatOptions) -> Option<String> {
+        let chain = self.chain.as_ref()?;
+        let label = |id: usize| self.countdowns.iter().find(|countdown| countdown.id == id).map(|c| c.label.clone());
+        if let Some((next, remaining)) = chain.pending() {
+            return Some(format!("Next: {} in {}", label(next)?, format_duration(remaining, format)));
+        }
+        let current = self.countdowns.iter().find(|countdown| countdown.is_running() && chain.contains(countdown.id))?;
+        Some(match chain.next_after(current.id).and_then(label) {
+            Some(next) => format!("Next: {}", next),
+            None => String::from("Last timer in the chain"),
+        })
+    }
+
+    // 連続で動かしている一覧の、次のカウントダウンを始める
+    fn start_chained(&mut self, id: usize) {
+        let stopped = self.countdowns.iter().any(|countdown| countdown.id == id && !countdown.is_running());
+        if stopped {
+            log::debug!("chain: starting countdown {}", id);
+            self.update(Message::ToggleCountdown(id));
+        }
+    }
+
+    // 終了を確認するまで表示し続ける設定であれば、終了の表示を出す(すでに出ていればまとめる)
+    fn show_banner(&mut self, label: &str, sound: bool) {
+        if !self.config.finished_banner.enabled {
+            return;
+        }
+        match self.banner.as_mut() {
+            Some(banner) => banner.add(label, sound),
+            None => self.banner = Some(Banner::new(label, sound)),
+        }
+    }
+
+    // 動作中のタイマーをすべて一時停止する。一時停止中であれば、その際に止めたもののうち残っているものを再開する
+    fn toggle_pause_all(&mut self) {
+        match self.paused_all.take() {
+            Some(paused) => {
+                log::debug!("resume all");
+                if paused.stopwatch && self.stopwatch.tick_state() == TickState::Stopped {
+                    self.update(Message::Start);
+                }
+                for countdown in &mut self.countdowns {
+                    if paused.countdowns.contains(&countdown.id) && !countdown.is_running() {
+                        countdown.toggle(self.clock.as_ref());
+                    }
+                }
+                if paused.interval && !self.interval.is_running() {
+                    self.interval.toggle();
+                }
+            },
+            None => {
+                let paused = PausedTimers {
+                    stopwatch: self.stopwatch.is_running(),
+                    countdowns: self
+                        .countdowns
+                        .iter()
+                        .filter(|countdown| countdown.is_running())
+                        .map(|countdown| countdown.id)
+                        .collect(),
+                    interval: self.interval.is_running(),
+                };
+                // 何も動いていなければ、次に押した際に再開するものもないため記録しない
+                if !paused.stopwatch && paused.countdowns.is_empty() && !paused.interval {
+                    return;
+                }
+
+                log::debug!("pause all");
+                // 計測を終える設定でも、まとめて止めた場合は再開できるよう一時停止にとどめる
+                if paused.stopwatch {
+                    self.pause_stopwatch();
+                    self.autosave();
+                }
+                for countdown in &mut self.countdowns {
+                    if paused.countdowns.contains(&countdown.id) {
+                        countdown.toggle(self.clock.as_ref());
+                    }
+                }
+                if paused.interval {
+                    self.interval.toggle();
+                }
+                self.paused_all = Some(paused);
+            },
+        }
+    }
+
+    // 全体の消音はカウントダウンごとの設定より優先する
+    fn is_countdown_muted(&self, id: usize) -> bool {
+        self.mute_all || self.countdowns.iter().any(|countdown| countdown.id == id && countdown.is_muted())
+    }
+
+    // 全体で消音していなければ効果音を鳴らす
+    fn play(&self, cue: Cue) {
+        if !self.mute_all {
+            self.player.play(cue);
+        }
+    }
+
+    // カウントダウンの終了のアラームを鳴らし始める。すべての音を止めている場合は鳴らさない
+    fn start_alarm(&mut self) {
+        if !self.mute_all {
+            self.player.start_alarm();
+            self.alarm = true;
+        }
+    }
+
+    fn stop_alarm(&mut self) {
+        if self.alarm {
+            self.player.stop_alarm();
+            self.alarm = false;
+        }
+    }
+
+    // 開始・停止の操作の音を、設定で有効にしている場合のみ鳴らす
+    fn play_action(&self, cue: Cue, sound: ActionSound) {
+        if sound.enabled && !self.mute_all {
+            self.player.play_action(cue, sound.volume);
+        }
+    }
+
+    // 累計経過時間に最終更新時刻からの差分を加え、最終更新時刻を更新する
+    fn advance_stopwatch(&mut self, now: Instant) {
+        let (before, after) = self.stopwatch.advance(now);
+        self.on_stopwatch_advanced(before, after);
+    }
+
+    // ストップウォッチの経過時間が before から after に進んだ際の、一定間隔の音や自動のラップ
+    // 一定間隔の音は、経過時間が間隔の倍数をまたぐたびに、またいだ倍数ごとに1度ずつ鳴らす。
+    // 判定は更新の頻度によらず前後の経過時間だけで行うため、同じ境界で2度鳴ることはない
+    fn on_stopwatch_advanced(&mut self, before: Duration, after: Duration) {
+        let tick = self.config.tick_sound;
+        let reminder = self.config.reminder;
+        let intervals = CueIntervals {
+            tick: if tick.enabled { Duration::from_secs(1) } else { Duration::default() },
+            beep: self.config.beep_interval,
+            reminder: reminder.interval,
+            auto_lap: self.config.auto_lap,
         };
 
-        // Base widgets
-        let tick_text = Text::new(duration_text).font(FONT).size(60);
+        // 更新の間隔が空いて複数の区切りを越えた場合も、区切りごとに時刻の順に1回ずつ知らせる
+        let mut lapped = false;
+        for (time, cue) in crossed_cues(before, after, &intervals) {
+            match cue {
+                StopwatchCue::Tick => {
+                    if !self.mute_all {
+                        self.player.play_tick(tick.volume);
+                    }
+                },
+                StopwatchCue::Beep => self.play(Cue::Beep(self.config.beep_sound)),
+                // 計測中のまま区切りを越えるたびに、止め忘れていないか知らせる。停止した瞬間の更新では知らせない
+                StopwatchCue::Reminder if self.stopwatch.is_running() => {
+                    let elapsed = format_duration(time, &FormatOptions { rounding: Rounding::Second, ..self.format });
+                    log::info!("timer still running: {}", elapsed);
+                    if reminder.channel.notifies() && !self.mute_all {
+                        self.notifier.notify_still_running(&elapsed);
+                    }
+                    if reminder.channel.sounds() {
+                        self.play(Cue::Chime);
+                    }
+                },
+                StopwatchCue::Reminder => {},
+                // 自動のラップは区切りちょうどの時刻で記録する
+                StopwatchCue::AutoLap => {
+                    self.push_lap(time);
+                    lapped = true;
+                },
+            }
+        }
+        if lapped {
+            self.autosave();
+        }
 
-        let start_stop_button = Button::new(
-            &mut self.start_stop_button_state, start_stop_text
-        )
-            .min_width(80)
-            .on_press(start_stop_message);
+        // 発表のモードでは、ストップウォッチの経過時間を制限時間と比べて警告する
+        if self.mode == Mode::Talk {
+            let limit = self.config.talk.limit;
+            for event in self.talk.advance(before, after, limit, &self.config.warning_thresholds) {
+                match event {
+                    TalkEvent::Warning => self.play(Cue::Warning),
+                    TalkEvent::Overtime => {
+                        log::debug!("talk overtime: limit={:?}", limit);
+                        self.status = Some(String::from("Overtime"));
+                        self.play(Cue::Warning);
+                    },
+                }
+            }
+        }
+    }
 
-        let reset_button = Button::new(
-            &mut self.reset_button_state,
-            Text::new("Reset")
-                .horizontal_alignment(HorizontalAlignment::Center)
-                .font(FONT)
-        )
-            .min_width(80)
-            .on_press(Message::Reset);
+    // ラップの一覧や書き出しに使う表示形式。lap_rounding を指定した場合は経過時間の表示とは別に丸める
+    fn lap_format(&self) -> FormatOptions {
+        FormatOptions { rounding: self.config.lap_rounding.unwrap_or(self.format.rounding), ..self.format }
+    }
 
-        // Layout widgets
-        Column::new()
-            .push(tick_text)
-            .push(Row::new()
-                .push(start_stop_button)
-                .push(reset_button)
-                .spacing(10)
+    // ラップのグラフの棒。棒の高さは丸める前のスプリットとし、上に添える値はラップの一覧と同じ丸め方で表示する
+    fn lap_bars(&self) -> Vec<chart::LapBar> {
+        let format = self.lap_format();
+        lap::splits(&lap::times(&self.laps))
+            .into_iter()
+            .zip(self.laps.iter())
+            .map(|(split, lap)| chart::LapBar { split, label: format_duration(split, &format), note: lap.note.clone() })
+            .collect()
+    }
+
+    // 共有用の画像に載せる、現在の計測の要約。ラップと統計はラップの一覧と同じ丸め方で表示する
+    fn session_summary(&self) -> chart::SessionSummary {
+        let format = self.lap_format();
+        let cumulative = lap::times(&self.laps);
+        let splits = lap::splits(&cumulative);
+        let laps = cumulative
+            .iter()
+            .zip(&splits)
+            .zip(self.laps.iter())
+            .enumerate()
+            .map(|(index, ((total, split), lap))| {
+                let line = format!("Lap {}  {}  (+{})", index + 1, format_duration(*total, &format), format_duration(*split, &format));
+                if lap.note.is_empty() { line } else { format!("{}  {}", line, lap.note) }
+            })
+            .collect();
+        let stats = lap::stats(&splits).map(|stats| {
+            format!(
+                "Avg {}  Best {}  Worst {}",
+                format_duration(stats.average, &format),
+                format_duration(stats.best, &format),
+                format_duration(stats.worst, &format)
             )
-            .spacing(10)
-            .padding(10)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .align_items(Align::Center)
-            .into()
+        });
+        let started = self.stopwatch.started_at().map(DateTime::<Local>::from).unwrap_or_else(|| self.clock.now_local());
+
+        chart::SessionSummary {
+            label: self.label.clone(),
+            total: format_duration(self.stopwatch.total_duration(), &self.format),
+            date: started.format("%Y-%m-%d %H:%M").to_string(),
+            laps,
+            stats,
+        }
     }
 
-    fn subscription(&self) -> Subscription<Self::Message> {
-        let timer = Timer::new(Duration::from_millis(MILLISEC / FPS));
-        iced::Subscription::from_recipe(timer).map(|_| Message::Update)
+    // 表示中のタブの計測を measurement と入れ替え、それまでの計測を返す
+    fn take_measurement(&mut self, measurement: Measurement) -> Measurement {
+        let laps = std::mem::replace(&mut self.laps, measurement.laps);
+        self.lap_row_states = self.laps.iter().map(|_| button::State::new()).collect();
+        self.editing_lap = None;
+        self.confirm_clear_history = false;
+        Measurement {
+            stopwatch: std::mem::replace(&mut self.stopwatch, measurement.stopwatch),
+            laps,
+            session_offset: std::mem::replace(&mut self.session_offset, measurement.session_offset),
+            paused_offset: std::mem::replace(&mut self.paused_offset, measurement.paused_offset),
+        }
     }
-}
 
-fn main() {
-    let mut settings = Settings::default();
-    settings.window.size = (400, 120); // ウィンドウサイズを固定
+    // ラップを記録し、一覧の行のボタンを1つ増やす
+    fn push_lap(&mut self, time: Duration) {
+        log::debug!("lap {}: {:?}", self.laps.len() + 1, time);
+        self.laps.record(time);
+        self.lap_row_states.push(button::State::new());
+        self.editing_lap = None;
+    }
 
-    GUI::run(settings);
+    // ストップウォッチが上限の時間ちょうどで停止した際に、カウントダウンの終了と同じく通知と終了の表示で知らせる
+    fn on_max_duration(&mut self) {
+        log::info!("stopwatch reached the maximum duration {:?}", self.config.max_duration);
+        let limit = format_duration(self.config.max_duration, &self.format);
+        self.status = Some(format!("Stopped at the maximum of {}", limit));
+        let label = if self.label.is_empty() { String::from("Stopwatch") } else { self.label.clone() };
+        if !self.mute_all {
+            self.notifier.notify_stopped(&label, &limit);
+        }
+        self.show_banner(&label, !self.mute_all);
+        self.play(Cue::Warning);
+        self.autosave();
+    }
+
+    // 今日の計測時間の合計(記録済みの分と、今回の計測のうち今日の分)
+    fn today_total(&self) -> Duration {
+        self.today_base + self.stopwatch.total_duration().checked_sub(self.session_offset).unwrap_or_default()
+    }
+
+    // Stopボタン押下時、状態をStoppedに切り替え、累計経過時間に現在時刻と最終更新時刻の差分をセット
+    fn pause_stopwatch(&mut self) {
+        let (before, after) = self.stopwatch.pause(self.clock.now());
+        self.on_stopwatch_advanced(before, after);
+        log::debug!("stop: total_duration={:?}", self.stopwatch.total_duration());
+        self.play_action(Cue::Stop, self.config.stop_sound);
+    }
+
+    // 計測の経過時間を指定した値に置き換え、その時点から数え続ける
+    // 計測前に指定した場合は、その時間だけ前に始めて一時停止している状態とみなす
+    // 指定した値より後のラップは取り除き、記録済みの分より短くした場合はその分を記録済みとみなす
+    fn set_elapsed(&mut self, elapsed: Duration) {
+        log::info!("set elapsed: {:?} -> {:?}", self.stopwatch.total_duration(), elapsed);
+        self.stopwatch.set_elapsed(elapsed, self.clock.as_ref());
+        self.session_offset = self.session_offset.min(elapsed);
+        if self.laps.truncate(elapsed) {
+            self.lap_row_states.truncate(self.laps.len());
+            self.editing_lap = None;
+        }
+        self.autosave();
+    }
+
+    // Stop ボタンで計測を終えて記録するか否か。発表のモードでは常に一時停止にする
+    fn finishes_on_stop(&self) -> bool {
+        self.mode == Mode::Stopwatch && self.config.stop_action == StopAction::Finish
+    }
+
+    // 今回の計測のうち、まだ履歴に記録していない分を今日の日付で記録する
+    // 一時停止していた時間は、記録した時点の日付にまとめて記録する
+    fn record_session(&mut self) {
+        let unrecorded = self.stopwatch.total_duration().checked_sub(self.session_offset).unwrap_or_default();
+        let unrecorded_paused = self.stopwatch.paused_duration().checked_sub(self.paused_offset).unwrap_or_default();
+        history::record(
+            self.today,
+            &self.label,
+            Mode::Stopwatch,
+            unrecorded,
+            unrecorded_paused,
+            self.stopwatch.started_at().map(DateTime::from),
+            DateTime::from(self.clock.system_now()),
+        );
+        self.today_base += unrecorded;
+        self.session_offset = self.stopwatch.total_duration();
+        self.paused_offset = self.stopwatch.paused_duration();
+        self.recent_sessions = history::recent(self.config.recent_sessions);
+    }
+
+    // カウントダウンの計測を履歴に記録する。終了したものは終了した時点で記録済みのため、リセットや削除の際には記録しない
+    fn record_countdown(&self, id: usize, finished: bool) {
+        if let Some(countdown) = self.countdowns.iter().find(|countdown| countdown.id == id) {
+            if finished || !countdown.is_finished() {
+                history::record(
+                    self.today,
+                    &countdown.label,
+                    Mode::Countdown,
+                    countdown.elapsed().min(countdown.length()),
+                    countdown.paused_duration(),
+                    countdown.started_at().map(DateTime::from),
+                    DateTime::from(self.clock.system_now()),
+                );
+            }
+        }
+    }
+
+    // 一時停止していた時間の合計。一時停止中であれば、停止してから現在までの分も含める
+    fn paused_total(&self) -> Duration {
+        self.stopwatch.paused_total(self.clock.as_ref())
+    }
+
+    // 日付が変わった際に、前日までの分を記録して今日の合計を0から数え直す
+    fn on_new_day(&mut self, date: NaiveDate) {
+        log::info!("date changed: {} -> {}", self.today, date);
+        self.record_session();
+        self.today = date;
+        self.today_base = history::total_for(date);
+    }
+
+    // 動いているタイマーがなく、確認などの操作待ちでもない状態か否か
+    fn is_idle(&self) -> bool {
+        !self.stopwatch.is_running()
+            && !self.countdowns.iter().any(|countdown| countdown.is_running())
+            && !self.interval.is_running()
+            && self.lead_in.is_none()
+            && self.recovery.is_none()
+            && !self.confirm_clear_history
+    }
+
+    // 操作がないまま設定した時間が経ち、大きな時計を表示しているか否か
+    fn is_ambient(&self) -> bool {
+        let after = ticks_for(self.config.ambient_after, self.fps());
+        after > 0 && self.idle_ticks >= after
+    }
+
+    // ストップウォッチの表示。開始時の負の値を設定した場合は、0に達するまで "-" を付けて残りを表示する
+    fn stopwatch_text(&self) -> String {
+        format_signed_duration(self.stopwatch.total_duration(), self.config.start_offset, &self.format)
+    }
+
+    // レース表示を使う場合の、秒と1/10秒の欄。1分を過ぎた後や0より前は通常の表示に戻す
+    fn racing_parts(&self) -> Option<(String, String)> {
+        if !self.config.racing || self.in_words || self.format_preset == FormatPreset::Clock {
+            return None;
+        }
+        let elapsed = self.stopwatch.total_duration().checked_sub(self.config.start_offset)?;
+        format_racing(elapsed)
+    }
+
+    // 経過時間や時刻の表示の文字サイズ。自動で合わせる設定では、ウィンドウの幅いっぱいに収まる大きさにする
+    fn readout_size(&self, text: &str, theme: Theme) -> u16 {
+        if self.config.auto_fit {
+            fit::fit_size(text, self.window_size.0, self.config.padding(), self.config.auto_fit_max)
+        } else {
+            theme.readout_size()
+        }
+    }
+
+    // 表示を更新する1秒あたりの回数
+    // レース表示の間は、1/10秒の桁が変わる頻度に合わせて10Hzで更新する
+    fn fps(&self) -> u64 {
+        if self.racing_parts().is_some() {
+            return RACING_FPS;
+        }
+        self.refresh.fps(self.format.digits() >= 3)
+    }
+
+    // 壁時計のジャンプを検出した際に呼ばれるメソッド
+    // 経過時間は Instant で計測しているため、累計経過時間には手を加えない。
+    // 壁時計で記録した時刻(計測やカウントダウンを始めた日時・Since の起点)はジャンプ量だけずらし、経過時間と食い違わないようにする。
+    // 日付が変わった場合は、ジャンプまでの分をジャンプ前の日付で記録してから今日の合計を数え直す
+    fn on_clock_jump(&mut self, jump: ClockJump) {
+        match jump {
+            ClockJump::Forward(amount) => log::warn!("system clock jumped forward by {:?}", amount),
+            ClockJump::Backward(amount) => log::warn!("system clock jumped backward by {:?}", amount),
+        }
+        self.stopwatch.shift_wall_clock(jump);
+        self.tabs.shift_wall_clock(jump);
+        for countdown in &mut self.countdowns {
+            countdown.shift_wall_clock(jump);
+        }
+        if let Some(anchor) = self.since.shift(jump) {
+            Config::store("since", toml::Value::String(anchor));
+        }
+
+        let date = local_date(self.clock.as_ref());
+        if date != self.today {
+            self.on_new_day(date);
+        }
+        // 自動保存に残っているずらす前の開始日時を書き換える
+        self.autosave();
+    }
+}
+
+// 構造体GUIにApplicationトレイトを実装
+impl Application for GUI {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Flags = Flags;
+
+    // new runした際に、icedの内部で使われる初期化のためのメソッド
+    fn new(flags: Self::Flags) -> (Self, Command<Self::Message>) {
+        // 優先順位はコマンドライン引数、環境変数、設定ファイルの順
+        let Flags { mut config, args } = flags;
+        let mut startup = env::apply(&mut config);
+        args.apply(&mut config, &mut startup);
+        // 配色ファイルが読めない場合は、理由を表示して組み込みの配色を使う
+        let theme_error = config.theme_path().and_then(|path| style::load_custom(&path).err());
+        let clock_shift = ClockShift::default();
+        let mut gui = GUI::with_clock(config, clock_shift.clock());
+        gui.clock_shift = clock_shift;
+        if let Some(err) = theme_error {
+            log::warn!("failed to load theme: {}", err);
+            gui.status = Some(format!("Invalid theme file: {}", err));
+        }
+        gui.theme = gui.theme.or_available();
+        let recovery = session::load_unclean();
+        gui.today_base = history::total_for(gui.today);
+        gui.recent_sessions = history::recent(gui.config.recent_sessions);
+        session::mark_running();
+        let countdowns = if gui.config.resume_countdowns.enabled { session::load_countdowns() } else { None };
+        let stopwatch = if gui.config.resume_stopwatch.enabled { session::load_stopwatch() } else { None };
+        gui.resume(recovery, countdowns, stopwatch);
+        if let Some(mode) = startup.mode {
+            gui.mode = mode;
+        }
+        if startup.pomodoro {
+            gui.interval.use_plan(gui.config.pomodoro.plan(), true);
+        }
+        match startup.countdown {
+            Some(length) if startup.autostart => gui.push_countdown("", length),
+            Some(length) => gui.push_ready_countdown("", length),
+            None if startup.autostart && gui.mode == Mode::Interval => {
+                let _ = gui.update(Message::ToggleInterval);
+            },
+            // 異常終了前の状態を復元するか確認する場合は、ストップウォッチを開始しない
+            None if startup.autostart && gui.recovery.is_none() => {
+                let _ = gui.update(Message::Start);
+            },
+            None => {},
+        }
+        // --load で指定したファイルがあれば、起動時に読み込んでカウントダウンの画面を表示する
+        if let Some(path) = args.load {
+            gui.timers_path = path;
+            gui.import_countdowns();
+            gui.mode = Mode::Countdown;
+        }
+
+        (gui, Command::none())
+    }
+
+    // title ウィンドウのタイトル。再描画のたびに呼ばれるため、経過時間がそのまま反映される
+    // 計測中は先頭に印を付け、タスクバーなどでも動いていることが分かるようにする
+    fn title(&self) -> String {
+        let title = format_title(
+            &self.config.title_format,
+            &self.label,
+            &self.stopwatch_text(),
+        );
+        match self.stopwatch.tick_state() {
+            TickState::Ticking => format!("{}{}", self.config.running_marker, title),
+            TickState::Init | TickState::Stopped => title,
+        }
+    }
+
+    // update ランタイムシステムからメッセージを受け取り、そのメッセージによってアプリケーションの状態を
+    // 更新するメソッド
+    fn update(&mut self, message: Self::Message) -> Command<Self::Message> {
+        let mut command = Command::none();
+
+        match message {
+            Message::Start => {
+                // Startボタン押下時、状態をTickingに切り替え、最終更新時刻に現在時刻をセット
+                log::debug!("start: total_duration={:?}", self.stopwatch.total_duration());
+                self.stopwatch.start(self.clock.as_ref());
+                self.play_action(Cue::Start, self.config.start_sound);
+            },
+
+            Message::Stop => {
+                self.pause_stopwatch();
+                if self.finishes_on_stop() {
+                    command = self.update(Message::Reset);
+                    self.status = Some(String::from("Session recorded"));
+                } else {
+                    // 一時停止する場合も、停止した時点までの分を記録する。再開した後は、ここから先の分のみを記録する
+                    self.record_session();
+                    self.autosave();
+                }
+            },
+
+            Message::ResetCountdowns => {
+                log::debug!("reset countdowns to {:?}", self.config.countdown_reset);
+                for countdown in &self.countdowns {
+                    self.record_countdown(countdown.id, false);
+                }
+                for countdown in &mut self.countdowns {
+                    countdown.reset_to(self.config.countdown_reset, self.clock.as_ref());
+                }
+            },
+
+            Message::Reset => {
+                // Resetボタン押下時、それまでの計測を履歴に記録し、最終更新時刻・累計経過時間をリセット
+                log::debug!("reset: total_duration={:?}", self.stopwatch.total_duration());
+                if self.stopwatch.total_duration() > Duration::default() {
+                    self.last_session = Some(LastSession {
+                        mode: Mode::Stopwatch,
+                        label: self.label.clone(),
+                        length: None,
+                    });
+                }
+                self.record_session();
+                self.session_offset = Duration::default();
+                self.paused_offset = Duration::default();
+                self.stopwatch.reset(self.clock.as_ref());
+                self.set_laps(Vec::new());
+                self.autosave();
+            },
+
+            Message::ResetPressed => {
+                let hold_ticks = ticks_for(self.config.reset_hold, self.fps());
+                if hold_ticks > 0 && self.press_ticks >= hold_ticks {
+                    log::debug!("reset held for {} ticks", self.press_ticks);
+                    self.confirm_clear_history = true;
+                }
+                command = self.update(Message::Reset);
+            },
+
+            Message::ClearHistory => {
+                history::clear();
+                self.today_base = Duration::default();
+                self.recent_sessions.clear();
+                self.session_offset = self.stopwatch.total_duration();
+                self.confirm_clear_history = false;
+                self.status = Some(String::from("History cleared"));
+            },
+
+            Message::KeepHistory => self.confirm_clear_history = false,
+
+            Message::RestoreDefaults => self.confirm_restore_defaults = true,
+
+            Message::ConfirmRestoreDefaults => {
+                self.confirm_restore_defaults = false;
+                self.status = Some(match Config::reset() {
+                    Ok(backup) => {
+                        self.apply_config(Config::load());
+                        match backup {
+                            Some(backup) => format!("Settings restored to defaults (backup: {})", backup.display()),
+                            None => String::from("Settings restored to defaults"),
+                        }
+                    },
+                    Err(err) => {
+                        log::warn!("failed to reset the config: {}", err);
+                        format!("Failed to restore defaults: {}", err)
+                    },
+                });
+            },
+
+            Message::CancelRestoreDefaults => self.confirm_restore_defaults = false,
+
+            // iced 0.1 にはランタイムを終了させる手段がないため、ウィンドウを閉じた場合と同じ保存をしてから終了する
+            Message::Quit => {
+                if self.has_unsaved_measurement() {
+                    self.confirm_quit = true;
+                } else {
+                    command = self.update(Message::SaveAndQuit);
+                }
+            },
+
+            Message::SaveAndQuit => {
+                log::info!("quit");
+                self.save_on_exit();
+                std::process::exit(0);
+            },
+
+            // 次回の起動時に復元を確認しないよう、正常に終了したものとして記録だけはする
+            Message::QuitWithoutSaving => {
+                log::info!("quit without saving");
+                session::mark_clean();
+                std::process::exit(0);
+            },
+
+            Message::CancelQuit => self.confirm_quit = false,
+
+            Message::Lap => {
+                // 計測中のみ記録する。直前の更新からの分も含めた、押した時点の経過時間を使う
+                if self.stopwatch.is_running() {
+                    let lap = self.stopwatch.elapsed(self.clock.as_ref());
+                    self.push_lap(lap);
+                    self.autosave();
+                }
+            },
+
+            Message::AddTab => {
+                let index = self.tabs.add(self.clock.as_ref());
+                command = self.update(Message::SelectTab(index));
+            },
+
+            Message::SelectTab(index) => {
+                let current = self.take_measurement(Measurement::new(self.clock.as_ref()));
+                let next = self.tabs.select(index, current);
+                self.take_measurement(next);
+                self.autosave();
+            },
+
+            // 閉じるタブの計測は、Reset と同じく履歴に記録してから捨てる
+            Message::CloseTab => {
+                if self.tabs.len() > 1 {
+                    self.record_session();
+                    if let Some(next) = self.tabs.close_active() {
+                        self.take_measurement(next);
+                    }
+                    self.autosave();
+                }
+            },
+
+            Message::EditLap(index) => {
+                self.editing_lap = Some(index);
+                self.set_focus(Some(Focus::LapNote));
+            },
+
+            Message::LapNoteChanged(note) => {
+                if let Some(index) = self.editing_lap() {
+                    self.laps.set_note(index, note);
+                    self.autosave();
+                }
+            },
+
+            Message::ElapsedInputChanged(input) => self.elapsed_input = input,
+
+            // 負の値や解釈できない入力は計測に反映せず、状態の表示で知らせる
+            Message::SubmitElapsed => match parse_duration(&self.elapsed_input) {
+                Some(elapsed) => command = self.update(Message::SetElapsed(elapsed)),
+                None => self.status = Some(format!("Invalid elapsed time: {}", self.elapsed_input)),
+            },
+
+            Message::SetElapsed(elapsed) => {
+                self.set_elapsed(elapsed);
+                self.elapsed_input.clear();
+                self.status = Some(format!("Elapsed time set to {}", format_duration(elapsed, &self.format)));
+            },
+
+            Message::Update => {
+                if let Some(jump) = self.jump_detector.check(self.clock.as_ref()) {
+                    self.on_clock_jump(jump);
+                }
+
+                if self.mouse_pressed {
+                    self.press_ticks += 1;
+                }
+                if self.is_idle() {
+                    self.idle_ticks += 1;
+                } else {
+                    self.idle_ticks = 0;
+                }
+
+                let date = local_date(self.clock.as_ref());
+                if date != self.today {
+                    self.on_new_day(date);
+                }
+
+                // 表示していないタブも計測を続け、自動のラップを記録する
+                self.tabs.advance(self.clock.now(), self.config.auto_lap);
+
+                // 時間計測時、状態がTickingの場合のみ、
+                // 累計経過時間を現在時刻と最終更新時刻の差分をセットした後、最終更新時刻を現在時刻に更新
+                if self.stopwatch.is_running() {
+                    let now_update = self.clock.now();
+                    self.advance_stopwatch(now_update);
+
+                    if self.stopwatch.stop_at(self.config.max_duration, self.clock.as_ref()) {
+                        self.on_max_duration();
+                    }
+
+                    if now_update - self.last_autosave >= AUTOSAVE_INTERVAL {
+                        self.autosave();
+                    }
+                }
+                self.menu_bar.set_text(&self.stopwatch_text());
+                self.rpc_server.publish(TimerState {
+                    running: self.stopwatch.is_running(),
+                    elapsed: self.stopwatch.total_duration(),
+                    countdowns: self.countdowns.len(),
+                    next_remaining: countdown::next_to_finish(self.countdowns.iter().map(CountdownRow::countdown)).map(Countdown::remaining),
+                });
+
+                // カウントダウンはそれぞれの計時で現在時刻まで進め、秒読みやインターバルなどは前回の更新からの差分で進める
+                let now_update = self.clock.now();
+                let delta = now_update - self.last_tick;
+                self.last_tick = now_update;
+
+                self.talk.tick();
+                match self.lead_in.as_mut().and_then(|lead_in| lead_in.advance(delta)) {
+                    Some(LeadInEvent::Count) => self.play(Cue::Count),
+                    Some(LeadInEvent::Finished) => {
+                        self.play(Cue::Go);
+                        if let Some(lead_in) = self.lead_in.take() {
+                            self.start_lead_in_target(lead_in.target);
+                        }
+                    },
+                    None => {},
+                }
+
+                if let Some(next) = self.chain.as_mut().and_then(|chain| chain.advance(delta)) {
+                    self.start_chained(next);
+                }
+
+                let warning_thresholds = &self.config.warning_thresholds;
+                let events: Vec<(usize, String, CountdownEvent)> = self
+                    .countdowns
+                    .iter_mut()
+                    .flat_map(|countdown| {
+                        let (id, label) = (countdown.id, countdown.label.clone());
+                        countdown
+                            .advance(now_update, warning_thresholds)
+                            .into_iter()
+                            .map(move |event| (id, label.clone(), event))
+                    })
+                    .collect();
+                for (id, label, event) in events {
+                    match event {
+                        CountdownEvent::Warning => {
+                            log::debug!("countdown warning: {}", label);
+                            if !self.is_countdown_muted(id) {
+                                self.player.play(Cue::Warning);
+                            }
+                        },
+                        CountdownEvent::Minute => {
+                            log::trace!("countdown minute: {}", label);
+                            if !self.is_countdown_muted(id) {
+                                self.play_action(Cue::Chime, self.config.minute_chime);
+                            }
+                        },
+                        CountdownEvent::Finished => self.on_countdown_finished(id, &label),
+                    }
+                }
+
+                // 区間の切り替わりと自動リセットのコマンドは、上書きせずにまとめて返す
+                let mut commands = Vec::new();
+
+                // 更新の間隔が空いて複数の区間が終わった場合も、切り替わりごとに順に音を鳴らす
+                for event in self.interval.advance(delta) {
+                    match event {
+                        IntervalEvent::PhaseChanged(phase) => commands.push(self.update(Message::PhaseChanged(phase))),
+                        IntervalEvent::AwaitingBreak(phase) => {
+                            log::debug!("interval awaiting {:?}", phase);
+                            self.status = Some(String::from("Work complete: start or skip the break"));
+                            self.play(Cue::Warning);
+                        },
+                        IntervalEvent::Finished => {
+                            log::debug!("interval finished");
+                            self.status = Some(String::from("Interval finished"));
+                            self.last_session = Some(LastSession {
+                                mode: Mode::Interval,
+                                label: self.label.clone(),
+                                length: None,
+                            });
+                            self.play(Cue::Warning);
+                            self.show_banner("Interval", true);
+                        },
+                    }
+                }
+
+                let repeat_sound = self.config.finished_banner.repeat_sound;
+                if self.banner.as_mut().map(|banner| banner.advance(delta, repeat_sound)) == Some(true) {
+                    self.play(Cue::Warning);
+                }
+
+                // 終了後、設定した時間が経過したカウントダウンにリセットのメッセージを送る
+                // 利用者が先に Restart や削除を行った場合は終了状態ではなくなるため、自動リセットも行われない
+                // 終了の表示と音が途中で消えないよう、終了直後の保持時間より前にはリセットしない
+                let auto_reset_delay = match self.config.auto_reset_delay {
+                    delay if delay > Duration::default() => delay.max(self.config.completion_hold),
+                    delay => delay,
+                };
+                if auto_reset_delay > Duration::default() {
+                    let now = self.clock.now();
+                    commands.extend(self.countdowns.iter_mut().filter_map(|countdown| {
+                        if !countdown.take_auto_reset(now, auto_reset_delay) {
+                            return None;
+                        }
+
+                        let id = countdown.id;
+                        Some(Command::perform(async {}, move |_| Message::ResetCountdown(id)))
+                    }));
+                }
+                command = Command::batch(commands);
+            },
+
+            Message::ToggleWords(in_words) => {
+                self.in_words = in_words;
+            },
+
+            Message::ExportChart => {
+                let path = chart::default_path();
+                self.status = Some(match chart::render_lap_chart(&self.lap_bars(), &path) {
+                    Ok(()) => {
+                        log::info!("lap chart saved to {}", path.display());
+                        format!("Chart saved to {}", path.display())
+                    },
+                    Err(err) => {
+                        log::warn!("failed to export lap chart: {}", err);
+                        format!("Failed to export chart: {}", err)
+                    },
+                });
+            },
+
+            Message::ExportLaps => {
+                let path = lap_export::default_path();
+                self.status = Some(match lap_export::write(&self.laps, &self.lap_format(), &path) {
+                    Ok(markdown_path) => {
+                        log::info!("exported {} laps to {} and {}", self.laps.len(), path.display(), markdown_path.display());
+                        format!("Laps saved to {} and {}", path.display(), markdown_path.display())
+                    },
+                    Err(err) => {
+                        log::warn!("failed to export laps to {}: {}", path.display(), err);
+                        format!("Failed to export laps: {}", err)
+                    },
+                });
+            },
+
+            Message::ExportHistory => {
+                let path = self.config.history_export_path().unwrap_or_else(history::default_export_path);
+                self.status = Some(match history::export(&path) {
+                    Ok(count) => {
+                        log::info!("exported {} history records to {}", count, path.display());
+                        format!("Exported {} sessions to {}", count, path.display())
+                    },
+                    Err(err) => {
+                        log::warn!("failed to export history to {}: {}", path.display(), err);
+                        format!("Failed to export history: {}", err)
+                    },
+                });
+            },
+
+            Message::ShareSession => {
+                let path = chart::default_card_path();
+                self.status = Some(match chart::render_summary_card(&self.session_summary(), &path) {
+                    Ok(()) => {
+                        log::info!("session card saved to {}", path.display());
+                        format!("Card saved to {}", path.display())
+                    },
+                    Err(err) => {
+                        log::warn!("failed to save session card: {}", err);
+                        format!("Failed to save card: {}", err)
+                    },
+                });
+            },
+
+            // ストップウォッチとカウントダウンは切り替えた後も裏で動き続ける
+            // カウントダウンに切り替えた場合は、すぐに時間を入力できるよう入力欄にフォーカスする
+            Message::SetMode(mode) => {
+                if mode != self.mode {
+                    log::debug!("mode: {:?}", mode);
+                    self.mode = mode;
+                    self.status = None;
+                    self.set_focus(match mode {
+                        Mode::Countdown => Some(Focus::CountdownLength),
+                        Mode::Since => Some(Focus::SinceInput),
+                        Mode::Stopwatch | Mode::Interval | Mode::Talk | Mode::Clock => None,
+                    });
+                    if let Ok(value) = toml::Value::try_from(mode) {
+                        Config::store("mode", value);
+                    }
+                }
+            },
+
+            Message::RepeatLastSession => self.repeat_last_session(),
+
+            // 更新の間隔が変わるとTimerのハッシュも変わるため、サブスクリプションは作り直される
+            Message::NextRefresh => {
+                self.refresh = self.refresh.next();
+                log::debug!("refresh: {:?} ({} fps)", self.refresh, self.fps());
+            },
+
+            // 個別の設定は Custom のときのみ使い、ほかのプリセットでは上書きする
+            Message::NextFormatPreset => {
+                self.format_preset = self.format_preset.next();
+                let (format, in_words) = self.format_preset.apply(self.config.format, self.config.in_words);
+                self.format = format;
+                self.in_words = in_words;
+                log::debug!("format preset: {:?}", self.format_preset);
+                if let Ok(value) = toml::Value::try_from(self.format_preset) {
+                    Config::store("format_preset", value);
+                }
+            },
+
+            Message::NextTheme => {
+                self.theme = self.theme.next();
+                log::debug!("theme: {:?}", self.theme);
+                // 選んだテーマは次回の起動時にも使う
+                if let Ok(value) = toml::Value::try_from(self.theme) {
+                    Config::store("theme", value);
+                }
+            },
+
+            Message::CountdownLabelChanged(label) => self.countdown_label = label,
+
+            Message::CountdownLengthChanged(length) => self.countdown_length = length,
+
+            Message::AddCountdown => self.add_countdown(),
+
+            Message::ImportCountdowns => self.import_countdowns(),
+
+            // 最初から始める場合のみ秒読みをする。秒読みの途中に押した場合はすぐに開始する
+            Message::ToggleCountdown(id) => {
+                let lead_in = self.config.lead_in.enabled;
+                if self.lead_in.as_ref().map(|lead_in| lead_in.target) == Some(LeadInTarget::Countdown(id)) {
+                    self.skip_lead_in();
+                } else if let Some(countdown) = self.countdowns.iter_mut().find(|c| c.id == id) {
+                    if lead_in && countdown.starts_over() {
+                        countdown.reset(self.clock.as_ref());
+                        self.begin_lead_in(LeadInTarget::Countdown(id));
+                    } else {
+                        countdown.toggle(self.clock.as_ref());
+                    }
+                }
+            },
+
+            // 外部からの操作は、現在の状態で意味のあるものだけボタンと同じメッセージとして処理する
+            // Start・Stop・Reset は表示中のモードによらず、常にストップウォッチを操作する
+            Message::Control(control) => {
+                let ticking = self.stopwatch.is_running();
+                let message = match control {
+                    ControlCommand::Start if !ticking => Some(Message::Start),
+                    ControlCommand::Stop if ticking => Some(Message::Stop),
+                    ControlCommand::Reset => Some(Message::Reset),
+                    ControlCommand::AddCountdown { label, length } => {
+                        self.push_countdown(&label, length);
+                        None
+                    },
+                    ControlCommand::TogglePauseAll => {
+                        self.toggle_pause_all();
+                        None
+                    },
+                    ControlCommand::Quit => {
+                        log::info!("quit from the menu bar");
+                        Some(Message::Quit)
+                    },
+                    ControlCommand::RestartCountdown(id) => self
+                        .countdowns
+                        .iter()
+                        .find(|countdown| countdown.id == id && countdown.is_finished())
+                        .map(|_| Message::ToggleCountdown(id)),
+                    _ => None,
+                };
+                if let Some(message) = message {
+                    command = self.update(message);
+                }
+            },
+
+            Message::Event(event) => {
+                if let Some(message) = self.handle_event(event) {
+                    command = self.update(message);
+                }
+            },
+
+            #[cfg(all(feature = "debug-clock", debug_assertions))]
+            Message::ShiftClock(backwards) => {
+                self.clock_shift.shift(DEBUG_CLOCK_STEP, backwards);
+                let direction = if backwards { "Wall clock rewound" } else { "Clock advanced" };
+                log::debug!("{} by {:?}", direction, DEBUG_CLOCK_STEP);
+                self.status = Some(format!("{} by {}s", direction, DEBUG_CLOCK_STEP.as_secs()));
+            },
+
+            Message::DumpState => match serde_json::to_string_pretty(&self.dump_state()) {
+                Ok(json) => {
+                    eprintln!("{}", json);
+                    self.status = Some(String::from("State dumped to stderr"));
+                },
+                Err(err) => log::warn!("failed to dump the state: {}", err),
+            },
+
+            // 復元した場合は停止した状態から再開できるようにする
+            Message::RestoreSession => {
+                if let Some(snapshot) = self.recovery.take() {
+                    log::info!("restored session: total_duration={:?}", snapshot.total_duration());
+                    let started_at = snapshot.started_at().map(|secs| std::time::UNIX_EPOCH + Duration::from_secs(secs));
+                    let (elapsed, paused) = (snapshot.total_duration(), snapshot.paused_duration());
+                    self.stopwatch.restore(elapsed, paused, started_at, false, self.clock.as_ref());
+                    self.session_offset = Duration::default();
+                    self.paused_offset = Duration::default();
+                    self.set_laps(snapshot.laps());
+                    self.mode = snapshot.mode().unwrap_or(self.mode);
+                    self.set_focus(None);
+                }
+            },
+
+            Message::DiscardSession => {
+                log::info!("discarded previous session");
+                self.recovery = None;
+                session::discard();
+                self.set_focus(None);
+            },
+
+            Message::SinceInputChanged(input) => self.since.set_input(input),
+
+            Message::SetSince => match self.since.apply(self.clock.now_local()) {
+                Ok(anchor) => {
+                    self.status = None;
+                    Config::store("since", toml::Value::String(anchor));
+                },
+                Err(err) => self.status = Some(err),
+            },
+
+            Message::ToggleInterval => {
+                if self.lead_in.as_ref().map(|lead_in| lead_in.target) == Some(LeadInTarget::Interval) {
+                    self.skip_lead_in();
+                } else if self.config.lead_in.enabled && self.interval.starts_over() {
+                    self.interval.reset();
+                    self.begin_lead_in(LeadInTarget::Interval);
+                } else {
+                    self.interval.toggle();
+                }
+            },
+
+            Message::SkipLeadIn => self.skip_lead_in(),
+            Message::DismissBanner => {
+                self.banner = None;
+                self.focus = None;
+                self.stop_alarm();
+            },
+
+            Message::ResetInterval => self.interval.reset(),
+
+            Message::JumpToRound(round) => self.interval.jump_to(round),
+
+            Message::StartBreak => {
+                self.interval.start_break();
+                self.status = None;
+            },
+
+            Message::SkipBreak => {
+                self.interval.skip_break();
+                self.status = None;
+            },
+
+            // 選んだ設定は次回の起動時にも使う
+            Message::PhaseChanged(phase) => {
+                log::debug!("interval phase: {:?}", phase);
+                // 長い休憩は、通常の切り替わりと聞き分けられるよう別の音にする
+                self.play(if phase == Phase::LongRest { Cue::LongRest } else { Cue::Warning });
+                let kind = if self.interval.is_pomodoro() { "Pomodoros" } else { "Rounds" };
+                self.status = Some(format!("{} next  {} done: {}", phase.name(), kind, self.interval.completed_rounds()));
+            },
+
+            Message::TogglePomodoro(pomodoro) => {
+                let plan = if pomodoro { self.config.pomodoro.plan() } else { self.config.interval };
+                self.interval.use_plan(plan, pomodoro);
+                self.status = None;
+            },
+
+            Message::ToggleManualAdvance(manual_advance) => {
+                self.config.manual_phase_advance = manual_advance;
+                self.interval.set_manual_advance(manual_advance);
+                Config::store("manual_phase_advance", toml::Value::Boolean(manual_advance));
+            },
+
+            Message::ToggleCountdownMute(id) => {
+                if let Some(countdown) = self.countdowns.iter_mut().find(|c| c.id == id) {
+                    countdown.toggle_mute();
+                    log::debug!("countdown {} muted: {}", id, countdown.is_muted());
+                }
+            },
+
+            Message::ToggleMuteAll(mute_all) => {
+                self.mute_all = mute_all;
+                if mute_all {
+                    self.stop_alarm();
+                }
+                log::debug!("mute all: {}", mute_all);
+            },
+
+            Message::ResetCountdown(id) => {
+                self.record_countdown(id, false);
+                if let Some(countdown) = self.countdowns.iter_mut().find(|c| c.id == id) {
+                    log::debug!("countdown {} reset", id);
+                    countdown.reset_to(self.config.countdown_reset, self.clock.as_ref());
+                }
+            },
+
+            Message::RemoveCountdown(id) => {
+                log::debug!("countdown {} removed", id);
+                self.record_countdown(id, false);
+                self.countdowns.retain(|c| c.id != id);
+                if let Some(chain) = self.chain.as_mut() {
+                    chain.remove(id);
+                    if chain.is_empty() {
+                        self.chain = None;
+                    }
+                }
+                if let Some(
+                    Focus::CountdownToggle(focused) | Focus::CountdownMute(focused) | Focus::CountdownRemove(focused)
+                ) = self.focus
+                {
+                    if focused == id {
+                        self.focus = None;
+                    }
+                }
+            },
+        }
+
+        // 計測中のみ画面のスリープを抑止する
+        let ticking = self.stopwatch.is_running() || self.tabs.any_running() || self.interval.is_running();
+        self.awake_guard.set_active(self.config.keep_awake && ticking);
+
+        command
+    }
+
+    // view ウィンドウに表示するウィジェットを設定するためのメソッド
+    fn view(&mut self) -> Element<'_, Self::Message> {
+        if self.recovery.is_some() {
+            return self.recovery_view();
+        }
+        // 終了の表示は、確認するまで通常の表示の代わりに出し続ける
+        if self.banner.is_some() {
+            return self.banner_view();
+        }
+        if self.is_ambient() {
+            let content = ambient::view(
+                self.config.ambient_display,
+                self.clock.now_local(),
+                self.stopwatch.total_duration(),
+                &self.format,
+                self.theme,
+            );
+            return Container::new(content)
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .center_y()
+                .style(style::Root(self.theme))
+                .into();
+        }
+
+        // display texts
+        let clock_time = self.clock.now_local();
+        let clock_text = clock_time.format("%H:%M:%S").to_string();
+        let duration_text = if self.format_preset == FormatPreset::Clock {
+            clock_text.clone()
+        } else if self.in_words {
+            match self.config.start_offset.checked_sub(self.stopwatch.total_duration()) {
+                Some(before) if before > Duration::default() => format!("minus {}", humanize_duration(before)),
+                _ => humanize_duration(self.stopwatch.total_duration() - self.config.start_offset),
+            }
+        } else {
+            self.stopwatch_text()
+        };
+        let racing = self.racing_parts();
+        // カーソルがウィンドウの上にある間は、経過時間を秒・分の合計と ISO 8601 の形式でも表示する
+        // 表示しない間も同じ高さを確保し、カーソルの出入りで画面の配置が動かないようにする
+        let units_text = if self.hovered {
+            let elapsed = self.stopwatch.total_duration();
+            format!(
+                "{:.3} s  ·  {:.2} min  ·  {}",
+                elapsed.as_secs_f64(),
+                elapsed.as_secs_f64() / 60.0,
+                iso8601_duration(elapsed)
+            )
+        } else {
+            String::from(" ")
+        };
+        let readout_size = self.readout_size(&duration_text, self.theme);
+        let clock_size = self.readout_size(&clock_text, self.theme);
+        let editing_lap = self.editing_lap();
+        let chain_text = self.chain_text(&self.format);
+
+        // ラップを記録している場合は、スプリットの合計が経過時間と一致しているかを控えめに示す
+        // 比べる経過時間は、更新ごとの差分の累計とは別に、計測を始めた時刻と一時停止していた時間から求める
+        let reference = self.stopwatch.reference_elapsed(self.clock.as_ref());
+        let consistency_text = match reference.filter(|_| !self.laps.is_empty()) {
+            Some(reference) => {
+                let format = FormatOptions { rounding: Rounding::Off, style: FormatStyle::Adaptive, precision: 3, ..self.format };
+                let elapsed = self.stopwatch.elapsed(self.clock.as_ref());
+                Some(match lap::discrepancy(&lap::times(&self.laps), elapsed, reference) {
+                    None => (String::from("Splits sum = elapsed"), self.theme.subtle()),
+                    Some(LapDelta::Slower(amount)) => {
+                        (format!("Splits sum ≠ elapsed (+{})", format_duration(amount, &format)), self.theme.flash())
+                    },
+                    Some(LapDelta::Faster(amount)) => {
+                        (format!("Splits sum ≠ elapsed (-{})", format_duration(amount, &format)), self.theme.flash())
+                    },
+                })
+            },
+            None => None,
+        };
+
+        // 残り時間は桁を抑えて、1秒単位で表示する
+        let next_text = match self.next_event() {
+            Some(event) => {
+                let format = FormatOptions { rounding: Rounding::Second, style: FormatStyle::Adaptive, precision: 0, ..self.format };
+                format!("Next: {} in {}", event.label, format_duration(event.remaining, &format))
+            },
+            None => String::from("Nothing scheduled"),
+        };
+        let finish_on_stop = self.finishes_on_stop();
+        let start_stop_text = match self.stopwatch.tick_state() {
+            TickState::Init => Text::new("Start"),
+            TickState::Stopped => Text::new("Restart"),
+            TickState::Ticking if finish_on_stop => Text::new("Finish"),
+            TickState::Ticking => Text::new("Stop"),
+        }
+            .horizontal_alignment(HorizontalAlignment::Center)
+            .font(font())
+            .size(self.config.button_size().text_size);
+
+        let start_stop_message = match self.stopwatch.tick_state() {
+            TickState::Init | TickState::Stopped => Message::Start,
+            TickState::Ticking => Message::Stop,
+        };
+
+        let daily_text = format!(
+            "Today {}  Session {}",
+            format_duration(self.today_total(), &self.format),
+            format_duration(self.stopwatch.total_duration(), &self.format)
+        );
+        let pause_text = format!(
+            "Active {}, Paused {}",
+            format_duration(self.stopwatch.total_duration(), &self.format),
+            format_duration(self.paused_total(), &self.format)
+        );
+        let started_text = self
+            .stopwatch
+            .started_at()
+            .map(|started| format!("Started {}", DateTime::<Local>::from(started).format("%H:%M")));
+        let recent_format = FormatOptions { rounding: Rounding::Second, ..self.format };
+        let recent_texts: Vec<String> = self
+            .recent_sessions
+            .iter()
+            .map(|entry| {
+                let duration = format_duration(entry.duration, &recent_format);
+                if entry.label.is_empty() {
+                    format!("{}  {}", entry.date, duration)
+                } else {
+                    format!("{}  {}  {}", entry.date, entry.label, duration)
+                }
+            })
+            .collect();
+
+        // Base widgets
+        let theme = self.theme;
+        let focus = self.focus;
+        let format = self.format;
+        let (spacing, padding) = (self.config.spacing(), self.config.padding());
+        let button_size = self.config.button_size();
+        let lap_rounding = self.config.lap_rounding.unwrap_or(format.rounding);
+        let vertical = self.config.button_layout.is_vertical(self.window_size);
+
+        // 現在のモードと状態を、ボタンの文言とは別に控えめな文字色で表示する
+        let state_badge = Text::new(format!("{} · {}", self.mode.name(), self.state_name()))
+            .font(font())
+            .size(16)
+            .color(theme.subtle());
+
+        let mode_row = Mode::ALL.iter().fold(Row::new().spacing(spacing), |row, &mode| {
+            row.push(
+                Radio::new(mode, mode.name(), Some(self.mode), Message::SetMode)
+                    .style(style::Radio { theme, focused: focus == Some(Focus::Mode(mode)) })
+            )
+        });
+
+        let theme_button = Button::new(
+            &mut self.theme_button_state,
+            Text::new(format!("Theme: {}", theme.name()))
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .on_press(Message::NextTheme)
+            .style(style::Button { theme, focused: focus == Some(Focus::Theme) });
+
+        // 繰り返す計測がない場合は押せないようにする
+        let mut repeat_button = Button::new(
+            &mut self.repeat_button_state,
+            Text::new("Repeat")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .style(style::Button { theme, focused: focus == Some(Focus::Repeat) });
+        if self.last_session.is_some() {
+            repeat_button = repeat_button.on_press(Message::RepeatLastSession);
+        }
+
+        let refresh_button = Button::new(
+            &mut self.refresh_button_state,
+            Text::new(format!("Refresh: {}", self.refresh.name()))
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .on_press(Message::NextRefresh)
+            .style(style::Button { theme, focused: focus == Some(Focus::Refresh) });
+
+        let format_preset_button = Button::new(
+            &mut self.format_preset_button_state,
+            Text::new(format!("Format: {}", self.format_preset.name()))
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .on_press(Message::NextFormatPreset)
+            .style(style::Button { theme, focused: focus == Some(Focus::FormatPreset) });
+
+
+        // Layout widgets
+        let mut content = Column::new().push(mode_row).push(state_badge);
+        // 終了の表示を使わない設定でも、アラームを止めるボタンを出す
+        if self.alarm {
+            let dismiss_button = Button::new(
+                &mut self.dismiss_alarm_button_state,
+                Text::new("Dismiss")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::DismissBanner)
+                .style(style::Button { theme, focused: focus == Some(Focus::DismissBanner) });
+            content = content.push(Row::new()
+                .push(Text::new("TIME'S UP").font(font()).color(theme.flash()))
+                .push(dismiss_button)
+                .spacing(spacing)
+                .align_items(Align::Center)
+            );
+        }
+        if let Some(lead_in) = self.lead_in.as_mut() {
+            content = content.push(lead_in.view(focus, theme));
+        }
+
+        match self.mode {
+            Mode::Stopwatch => {
+                let tab_bar = self.tabs.view(self.stopwatch.is_running(), &self.config.running_marker, focus, theme);
+
+                // 文章表示は数字より長くなるため、文字サイズを小さくしてウィンドウに収める
+                let tick_size = if self.in_words {
+                    theme.readout_size() / 2
+                } else {
+                    readout_size
+                };
+                let heat = style::heat_color(&self.config.heat, self.stopwatch.total_duration());
+                let colored = |text: Text| match heat {
+                    Some(color) => text.color(color),
+                    None => text,
+                };
+                // レース表示では秒を大きく、1/10秒を小さく並べ、下端をそろえる
+                let tick_text: Element<Message> = match racing {
+                    Some((seconds, tenths)) => Row::new()
+                        .push(colored(Text::new(seconds).font(font()).size(theme.readout_size() * 3 / 2)))
+                        .push(colored(Text::new(tenths).font(font()).size(theme.readout_size() * 2 / 3)))
+                        .align_items(Align::End)
+                        .into(),
+                    None => colored(Text::new(duration_text).font(font()).size(tick_size)).into(),
+                };
+
+                let start_stop_button = Button::new(
+                    &mut self.start_stop_button_state, start_stop_text
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .on_press(start_stop_message)
+                    .style(style::Button { theme, focused: focus == Some(Focus::StartStop) });
+
+                let reset_button = Button::new(
+                    &mut self.reset_button_state,
+                    Text::new("Reset")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                        .size(button_size.text_size)
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .on_press(Message::ResetPressed)
+                    .style(style::Button { theme, focused: focus == Some(Focus::Reset) });
+
+                let lap_button = Button::new(
+                    &mut self.lap_button_state,
+                    Text::new("Lap")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                        .size(button_size.text_size)
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .on_press(Message::Lap)
+                    .style(style::Button { theme, focused: focus == Some(Focus::Lap) });
+
+                // ラップがない場合は書き出すものがないため押せないようにする
+                let mut export_chart_button = Button::new(
+                    &mut self.export_chart_button_state,
+                    Text::new("Chart")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                        .size(button_size.text_size)
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .style(style::Button { theme, focused: focus == Some(Focus::ExportChart) });
+                if !self.laps.is_empty() {
+                    export_chart_button = export_chart_button.on_press(Message::ExportChart);
+                }
+                let mut export_laps_button = Button::new(
+                    &mut self.export_laps_button_state,
+                    Text::new("CSV")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                        .size(button_size.text_size)
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .style(style::Button { theme, focused: focus == Some(Focus::ExportLaps) });
+                if !self.laps.is_empty() {
+                    export_laps_button = export_laps_button.on_press(Message::ExportLaps);
+                }
+
+                // 計測していない場合は共有するものがないため押せないようにする
+                let mut share_button = Button::new(
+                    &mut self.share_button_state,
+                    Text::new("Share")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                        .size(button_size.text_size)
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .style(style::Button { theme, focused: focus == Some(Focus::Share) });
+                if self.stopwatch.total_duration() > Duration::default() {
+                    share_button = share_button.on_press(Message::ShareSession);
+                }
+
+                // 長押しでリセットした場合のみ、履歴も削除するか確認する
+                let clear_history_row = if self.confirm_clear_history {
+                    let clear_button = Button::new(
+                        &mut self.clear_history_button_state,
+                        Text::new("Clear history")
+                            .horizontal_alignment(HorizontalAlignment::Center)
+                            .font(font())
+                    )
+                        .on_press(Message::ClearHistory)
+                        .style(style::Button { theme, focused: focus == Some(Focus::ClearHistory) });
+                    let keep_button = Button::new(
+                        &mut self.keep_history_button_state,
+                        Text::new("Keep")
+                            .horizontal_alignment(HorizontalAlignment::Center)
+                            .font(font())
+                    )
+                        .on_press(Message::KeepHistory)
+                        .style(style::Button { theme, focused: focus == Some(Focus::KeepHistory) });
+
+                    Some(Row::new()
+                        .push(Text::new("Also clear history?").font(font()))
+                        .push(clear_button)
+                        .push(keep_button)
+                        .spacing(spacing)
+                        .align_items(Align::Center)
+                    )
+                } else {
+                    None
+                };
+
+                // 始め忘れた場合などに、経過時間を直接指定して補正する
+                let elapsed_input = TextInput::new(
+                    &mut self.elapsed_input_state,
+                    "Set elapsed: 5m, 0:05:00, ...",
+                    &self.elapsed_input,
+                    Message::ElapsedInputChanged,
+                )
+                    .font(font())
+                    .padding(5)
+                    .on_submit(Message::SubmitElapsed)
+                    .style(style::TextInput(theme));
+                let set_elapsed_button = Button::new(
+                    &mut self.set_elapsed_button_state,
+                    Text::new("Set")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                )
+                    .on_press(Message::SubmitElapsed)
+                    .style(style::Button { theme, focused: focus == Some(Focus::SetElapsed) });
+
+                // 履歴がある場合のみ、最近の記録と書き出しのボタンを表示する
+                let recent_list = if recent_texts.is_empty() {
+                    None
+                } else {
+                    let export_history_button = Button::new(
+                        &mut self.export_history_button_state,
+                        Text::new("Export CSV")
+                            .horizontal_alignment(HorizontalAlignment::Center)
+                            .font(font())
+                    )
+                        .on_press(Message::ExportHistory)
+                        .style(style::Button { theme, focused: focus == Some(Focus::ExportHistory) });
+                    let mut list = Column::new()
+                        .push(Row::new()
+                            .push(Text::new("Recent sessions").font(font()))
+                            .push(export_history_button)
+                            .spacing(spacing)
+                            .align_items(Align::Center)
+                        )
+                        .spacing(5)
+                        .align_items(Align::Center);
+                    for text in recent_texts {
+                        list = list.push(Text::new(text).font(font()).size(16).color(theme.subtle()));
+                    }
+                    Some(list)
+                };
+
+                // 文章表示の切り替えは、個別に表示形式を選ぶ Custom のプリセットでのみ出す
+                let words_checkbox = if self.format_preset == FormatPreset::Custom {
+                    Some(Checkbox::new(self.in_words, "In words", Message::ToggleWords)
+                        .style(style::Checkbox { theme, focused: focus == Some(Focus::Words) }))
+                } else {
+                    None
+                };
+
+                // 目標時間が設定されている場合のみ、経過時間の進捗バーを表示する
+                let total_duration = self.stopwatch.total_duration();
+                let target_bar = self
+                    .config
+                    .target
+                    .filter(|target| *target > Duration::default())
+                    .map(|target| {
+                        let ratio = total_duration.as_secs_f32() / target.as_secs_f32();
+                        ProgressBar::new(0.0..=1.0, ratio.min(1.0))
+                            .height(Length::Units(10))
+                            .style(style::TargetProgress { theme, exceeded: ratio > 1.0 })
+                    });
+
+                content = content
+                    .push(tab_bar)
+                    .push(tick_text)
+                    .push(Text::new(units_text).font(font()).size(16).color(theme.subtle()));
+                if let Some(target_bar) = target_bar {
+                    content = content.push(target_bar);
+                }
+                content = content
+                    .push(Text::new(daily_text).font(font()))
+                    .push(Text::new(pause_text).font(font()).color(theme.subtle()));
+                if let Some(started_text) = started_text {
+                    content = content.push(Text::new(started_text).font(font()).color(theme.subtle()));
+                }
+                content = content.push(layout::buttons(vertical, vec![
+                    start_stop_button.into(),
+                    reset_button.into(),
+                    lap_button.into(),
+                    export_chart_button.into(),
+                    export_laps_button.into(),
+                    share_button.into(),
+                ]));
+                if let Some(clear_history_row) = clear_history_row {
+                    content = content.push(clear_history_row);
+                }
+                if let Some(recent_list) = recent_list {
+                    content = content.push(recent_list);
+                }
+                if let Some(words_checkbox) = words_checkbox {
+                    content = content.push(words_checkbox);
+                }
+                content = content.push(Row::new()
+                    .push(elapsed_input)
+                    .push(set_elapsed_button)
+                    .spacing(spacing)
+                    .align_items(Align::Center)
+                );
+                if let Some(index) = editing_lap {
+                    let note_input = TextInput::new(
+                        &mut self.lap_note_state,
+                        &format!("Note for lap {}", index + 1),
+                        &self.laps[index].note,
+                        Message::LapNoteChanged,
+                    )
+                        .font(font())
+                        .padding(5)
+                        .style(style::TextInput(theme));
+                    content = content.push(note_input);
+                }
+                if let Some((text, color)) = consistency_text {
+                    content = content.push(Text::new(text).font(font()).size(16).color(color));
+                }
+                content = content.push(lap_list::view(
+                    &mut self.lap_list_state,
+                    &mut self.lap_row_states,
+                    &self.laps,
+                    editing_lap,
+                    lap_rounding,
+                    &format,
+                    theme,
+                ));
+            },
+
+            Mode::Countdown => {
+                let summary_text = match countdown::next_to_finish(self.countdowns.iter().map(CountdownRow::countdown)) {
+                    Some(next) => format!(
+                        "Next: {} in {}",
+                        next.label,
+                        format_duration(next.remaining(), &format)
+                    ),
+                    None => String::from("No countdowns running"),
+                };
+
+                let countdown_label_input = TextInput::new(
+                    &mut self.countdown_label_state,
+                    "Label",
+                    &self.countdown_label,
+                    Message::CountdownLabelChanged,
+                )
+                    .font(font())
+                    .padding(5)
+                    .on_submit(Message::AddCountdown)
+                    .style(style::TextInput(theme));
+
+                let countdown_length_input = TextInput::new(
+                    &mut self.countdown_length_state,
+                    "5m, 1:30, ...",
+                    &self.countdown_length,
+                    Message::CountdownLengthChanged,
+                )
+                    .font(font())
+                    .padding(5)
+                    .on_submit(Message::AddCountdown)
+                    .style(style::TextInput(theme));
+
+                let add_countdown_button = Button::new(
+                    &mut self.add_countdown_button_state,
+                    Text::new("Add")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                )
+                    .on_press(Message::AddCountdown)
+                    .style(style::Button { theme, focused: focus == Some(Focus::AddCountdown) });
+
+                let import_button = Button::new(
+                    &mut self.import_button_state,
+                    Text::new("Import")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                )
+                    .on_press(Message::ImportCountdowns)
+                    .style(style::Button { theme, focused: focus == Some(Focus::ImportCountdowns) });
+
+                let mute_all_checkbox = Checkbox::new(self.mute_all, "Mute all", Message::ToggleMuteAll)
+                    .style(style::Checkbox { theme, focused: focus == Some(Focus::MuteAll) });
+
+                let split_view = self.config.split_view;
+                let countdown_rows = self
+                    .countdowns
+                    .iter_mut()
+                    .fold(Column::new().spacing(5), |column, countdown| {
+                        column.push(countdown.view(&format, split_view, focus, theme))
+                    });
+
+                content = content
+                    .push(Text::new(summary_text).font(font()));
+                if let Some(chain_text) = chain_text {
+                    content = content.push(Text::new(chain_text).font(font()).color(theme.subtle()));
+                }
+                content = content
+                    .push(countdown_rows)
+                    .push(Row::new()
+                        .push(countdown_label_input)
+                        .push(countdown_length_input)
+                        .push(add_countdown_button)
+                        .push(import_button)
+                        .spacing(spacing)
+                        .align_items(Align::Center)
+                    )
+                    .push(mute_all_checkbox);
+            },
+
+            Mode::Interval => {
+                content = content.push(self.interval.view(&format, vertical, button_size, focus, theme));
+            },
+
+            Mode::Since => {
+                content = content.push(self.since.view(clock_time, focus, theme));
+            },
+
+            Mode::Talk => {
+                let (status_text, status_color) =
+                    self.talk.status(self.stopwatch.total_duration(), self.config.talk.limit, &self.format, theme);
+                let mut status_display = Text::new(status_text).font(font()).size(theme.readout_size() / 2);
+                if let Some(color) = status_color {
+                    status_display = status_display.color(color);
+                }
+
+                let start_stop_button = Button::new(
+                    &mut self.start_stop_button_state, start_stop_text
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .on_press(start_stop_message)
+                    .style(style::Button { theme, focused: focus == Some(Focus::StartStop) });
+                let reset_button = Button::new(
+                    &mut self.reset_button_state,
+                    Text::new("Reset")
+                        .horizontal_alignment(HorizontalAlignment::Center)
+                        .font(font())
+                        .size(button_size.text_size)
+                )
+                    .min_width(button_size.min_width)
+                    .padding(button_size.padding)
+                    .on_press(Message::Reset)
+                    .style(style::Button { theme, focused: focus == Some(Focus::Reset) });
+
+                content = content
+                    .push(Text::new(duration_text).font(font()).size(readout_size))
+                    .push(status_display)
+                    .push(layout::buttons(vertical, vec![start_stop_button.into(), reset_button.into()]));
+            },
+
+            Mode::Clock => {
+                content = content
+                    .push(Text::new(clock_text).font(font()).size(clock_size))
+                    .push(Text::new(clock_time.format("%Y-%m-%d (%a)").to_string()).font(font()));
+            },
+        }
+
+        let restore_defaults_button = Button::new(
+            &mut self.restore_defaults_button_state,
+            Text::new("Defaults")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .on_press(Message::RestoreDefaults)
+            .style(style::Button { theme, focused: focus == Some(Focus::RestoreDefaults) });
+
+        let quit_button = Button::new(
+            &mut self.quit_button_state,
+            Text::new("Quit")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .on_press(Message::Quit)
+            .style(style::Button { theme, focused: focus == Some(Focus::Quit) });
+
+        content = content.push(Row::new()
+            .push(theme_button)
+            .push(refresh_button)
+            .push(format_preset_button)
+            .push(repeat_button)
+            .push(restore_defaults_button)
+            .push(quit_button)
+            .spacing(spacing)
+            .align_items(Align::Center)
+        );
+
+        // 設定ファイルを書き換えるため、戻す前に確認する
+        if self.confirm_restore_defaults {
+            let confirm_button = Button::new(
+                &mut self.confirm_defaults_button_state,
+                Text::new("Restore")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::ConfirmRestoreDefaults)
+                .style(style::Button { theme, focused: focus == Some(Focus::ConfirmDefaults) });
+            let cancel_button = Button::new(
+                &mut self.cancel_defaults_button_state,
+                Text::new("Cancel")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::CancelRestoreDefaults)
+                .style(style::Button { theme, focused: focus == Some(Focus::CancelDefaults) });
+
+            content = content.push(Row::new()
+                .push(Text::new("Restore default settings?").font(font()))
+                .push(confirm_button)
+                .push(cancel_button)
+                .spacing(spacing)
+                .align_items(Align::Center)
+            );
+        }
+
+        if self.confirm_quit {
+            let save_button = Button::new(
+                &mut self.save_and_quit_button_state,
+                Text::new("Save")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::SaveAndQuit)
+                .style(style::Button { theme, focused: focus == Some(Focus::SaveAndQuit) });
+            let quit_button = Button::new(
+                &mut self.quit_without_saving_button_state,
+                Text::new("Quit")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::QuitWithoutSaving)
+                .style(style::Button { theme, focused: focus == Some(Focus::QuitWithoutSaving) });
+            let cancel_button = Button::new(
+                &mut self.cancel_quit_button_state,
+                Text::new("Cancel")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::CancelQuit)
+                .style(style::Button { theme, focused: focus == Some(Focus::CancelQuit) });
+
+            content = content.push(Row::new()
+                .push(Text::new("Timer still running — quit anyway?").font(font()))
+                .push(save_button)
+                .push(quit_button)
+                .push(cancel_button)
+                .spacing(spacing)
+                .align_items(Align::Center)
+            );
+        }
+
+        content = content.push(Text::new(next_text).font(font()).color(theme.subtle()));
+
+        if let Some(status) = &self.status {
+            content = content.push(Text::new(status.clone()).font(font()));
+        }
+
+        let content = content
+            .spacing(spacing)
+            .padding(padding)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_items(Align::Center);
+
+        // 背景色と文字色はテーマに合わせてウィンドウ全体に適用する
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .style(style::Root(theme))
+            .into()
+    }
+
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let timer = Timer::new(Duration::from_millis(MILLISEC / self.fps()));
+        let controls = Controls::new(self.control_receiver.clone());
+
+        Subscription::batch(vec![
+            iced::Subscription::from_recipe(timer).map(|_| Message::Update),
+            iced::Subscription::from_recipe(controls).map(Message::Control),
+            iced_native::subscription::events().map(Message::Event),
+        ])
+    }
+}
+
+impl GUI {
+    // 不具合の報告に添付できるよう、計測の状態と設定をまとめる。時間は秒で表す
+    fn dump_state(&self) -> serde_json::Value {
+        let countdowns: Vec<serde_json::Value> = self
+            .countdowns
+            .iter()
+            .map(|countdown| {
+                serde_json::json!({
+                    "label": countdown.label,
+                    "length": countdown.length().as_secs_f64(),
+                    "remaining": countdown.remaining().as_secs_f64(),
+                    "running": countdown.is_running(),
+                })
+            })
+            .collect();
+
+        serde_json::json!({
+            "mode": self.mode,
+            "tick_state": self.stopwatch.tick_state().name(),
+            "total_duration": self.stopwatch.total_duration().as_secs_f64(),
+            "paused_duration": self.paused_total().as_secs_f64(),
+            "started_at": self.stopwatch.started_at().map(|started| DateTime::<Local>::from(started).to_rfc3339()),
+            "laps": self.laps.iter().map(|lap| lap.time.as_secs_f64()).collect::<Vec<_>>(),
+            "lap_notes": self.laps.iter().map(|lap| lap.note.as_str()).collect::<Vec<_>>(),
+            "countdowns": countdowns,
+            "interval": self.interval.state_name(),
+            "refresh": self.refresh.name(),
+            "fps": self.fps(),
+            "config": Config::dump(),
+        })
+    }
+
+    // すべてのモードを通して、次に起きる予定の出来事
+    fn next_event(&self) -> Option<Upcoming> {
+        let mut events = Vec::new();
+        if let Some(lead_in) = &self.lead_in {
+            let target = match lead_in.target {
+                LeadInTarget::Countdown(_) => "Countdown",
+                LeadInTarget::Interval => "Interval",
+            };
+            events.push(Upcoming::new(format!("{} starts", target), lead_in.remaining()));
+        }
+        events.extend(self.countdowns.iter().filter(|c| c.is_running()).map(|countdown| {
+            Upcoming::new(format!("{} finishes", countdown.label), countdown.remaining())
+        }));
+        if let Some((name, remaining)) = self.interval.upcoming() {
+            events.push(Upcoming::new(format!("Interval: {}", name), remaining));
+        }
+        if self.stopwatch.is_running() {
+            let elapsed = self.stopwatch.total_duration();
+            let stopwatch = [
+                (self.config.start_offset, "Stopwatch reaches zero"),
+                (self.config.target.unwrap_or_default(), "Target reached"),
+                (self.config.max_duration, "Stopwatch stops"),
+            ];
+            events.extend(stopwatch
+                .iter()
+                .filter(|(at, _)| *at > elapsed)
+                .map(|&(at, label)| Upcoming::new(label, at - elapsed)));
+        }
+        schedule::soonest(events)
+    }
+
+    // 表示中のモードの状態の名前。時計には状態がないため表示しているとだけ示す
+    fn state_name(&self) -> &'static str {
+        match self.mode {
+            Mode::Stopwatch | Mode::Talk => self.stopwatch.tick_state().name(),
+            Mode::Countdown if self.countdowns.iter().any(|countdown| countdown.is_running()) => "Running",
+            Mode::Countdown if self.countdowns.is_empty() => "Ready",
+            Mode::Countdown => "Paused",
+            Mode::Interval => self.interval.state_name(),
+            Mode::Since | Mode::Clock => "Showing",
+        }
+    }
+
+    // 確認待ちの終了の表示だけを出す画面
+    fn banner_view(&mut self) -> Element<'_, Message> {
+        let (focus, theme) = (self.focus, self.theme);
+        let (spacing, padding) = (self.config.spacing(), self.config.padding());
+        let button_size = self.config.button_size();
+        match self.banner.as_mut() {
+            Some(banner) => banner.view(focus, theme, spacing, padding, button_size),
+            None => Column::new().into(),
+        }
+    }
+
+    // 異常終了前の状態を復元するか否かを確認する画面
+    fn recovery_view(&mut self) -> Element<'_, Message> {
+        let theme = self.theme;
+        let focus = self.focus;
+        let (spacing, padding) = (self.config.spacing(), self.config.padding());
+        let button_size = self.config.button_size();
+        let total_duration = self
+            .recovery
+            .as_ref()
+            .map(Snapshot::total_duration)
+            .unwrap_or_default();
+
+        let restore_button = Button::new(
+            &mut self.restore_button_state,
+            Text::new("Restore")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+                .size(button_size.text_size)
+        )
+            .min_width(button_size.min_width)
+            .padding(button_size.padding)
+            .on_press(Message::RestoreSession)
+            .style(style::Button { theme, focused: focus == Some(Focus::Restore) });
+
+        let discard_button = Button::new(
+            &mut self.discard_button_state,
+            Text::new("Discard")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+                .size(button_size.text_size)
+        )
+            .min_width(button_size.min_width)
+            .padding(button_size.padding)
+            .on_press(Message::DiscardSession)
+            .style(style::Button { theme, focused: focus == Some(Focus::Discard) });
+
+        let content = Column::new()
+            .push(Text::new(format!(
+                "Recover previous session? {}",
+                format_duration(total_duration, &self.format)
+            )).font(font()))
+            .push(Row::new()
+                .push(restore_button)
+                .push(discard_button)
+                .spacing(spacing)
+            )
+            .spacing(spacing)
+            .padding(padding)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_items(Align::Center);
+
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .center_y()
+            .style(style::Root(theme))
+            .into()
+    }
+}
+
+// ウィンドウを閉じて正常に終了した際に、異常終了と区別するためのマーカーを削除する
+// パニックによるアンワインド中は異常終了として扱い、マーカーを残す。
+// 復元の確認中に閉じた場合も、次回の起動時に改めて確認するためマーカーを残す
+impl Drop for GUI {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        self.save_on_exit();
+    }
+}
+
+// 時計の壁時計の時刻から、ローカルのタイムゾーンでの日付を求める(夏時間の切り替えもchronoが考慮する)
+fn local_date(clock: &dyn Clock) -> NaiveDate {
+    clock.now_local().date_naive()
+}
+
+// 壁時計の時刻をUNIX時間の秒にする。1970年より前の時刻は0とする
+fn unix_seconds(time: std::time::SystemTime) -> u64 {
+    time.duration_since(std::time::UNIX_EPOCH).map_or(0, |since| since.as_secs())
+}
+
+// 指定した時間が、サブスクリプションの何ティック分にあたるかを返す
+fn ticks_for(duration: Duration, fps: u64) -> u64 {
+    duration.as_millis() as u64 / (MILLISEC / fps)
+}
+
+// ログ出力を初期化する。通常はGUIの妨げにならないよう警告以上のみを出力し、
+// RUST_LOG 環境変数か --verbose フラグで詳細なログを出力できるようにする
+fn init_logger(verbose: bool) {
+    let default_filter = if verbose { "simple_timer=debug" } else { "warn" };
+
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(default_filter))
+        .init();
+}
+
+fn main() {
+    // 不正な引数の場合は、clap がエラーを出力して終了する
+    let args = Args::parse();
+    init_logger(args.verbose);
+
+    // --report が指定された場合は、履歴を集計して出力するのみで終了する
+    if args.report {
+        report::print_week(report::Options { json: args.json }, local_date(&SystemClock));
+        return;
+    }
+
+    // --selftest が指定された場合は、計時の誤差を測って出力するのみで終了する
+    if let Some(length) = args.selftest {
+        let length = length.unwrap_or(selftest::DEFAULT_LENGTH);
+        if !selftest::run(selftest::Options { length }) {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    // --no-gui が指定された場合は、ウィンドウを開かずに端末でカウントダウンする
+    if let Some(length) = args.no_gui {
+        if let Err(err) = cli::run(cli::Options { length, quiet: args.quiet }) {
+            eprintln!("simple_timer: {}", err);
+            std::process::exit(2);
+        }
+        return;
+    }
+
+    // --reset-config が指定された場合は、設定ファイルを既定値に戻してから起動する
+    if args.reset_config {
+        match Config::reset() {
+            Ok(Some(backup)) => eprintln!("simple_timer: restored the default settings (backup: {})", backup.display()),
+            Ok(None) => eprintln!("simple_timer: restored the default settings"),
+            Err(err) => {
+                eprintln!("simple_timer: {}", err);
+                std::process::exit(2);
+            },
+        }
+    }
+
+    // --touch が指定された場合は、設定ファイルによらずタッチ操作向けの表示にする
+    let mut config = Config::load();
+    if args.touch {
+        config.touch_mode = true;
+    }
+
+    if let Some(path) = config.font_path() {
+        load_font_file(&path);
+    }
+    let size = window_size(&config);
+    let mut settings = Settings::with_flags(Flags { config, args });
+    settings.window.size = size; // ウィンドウサイズを固定
+
+    GUI::run(settings);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+    use std::rc::Rc;
+    use std::sync::Once;
+    use std::time::SystemTime;
+
+    // 手動で進める時計。GUI に渡した後もテストから進められるよう、経過時間を共有する
+    // 壁時計だけをずらして、時刻の変更やNTPによる補正で壁時計が飛んだ状態も作れる
+    #[derive(Clone)]
+    struct FakeClock {
+        start: Instant,
+        elapsed: Rc<Cell<Duration>>,
+        wall_shift: Rc<Cell<i64>>,
+    }
+
+    impl FakeClock {
+        fn new() -> FakeClock {
+            FakeClock {
+                start: Instant::now(),
+                elapsed: Rc::new(Cell::new(Duration::default())),
+                wall_shift: Rc::new(Cell::new(0)),
+            }
+        }
+
+        fn advance(&self, step: Duration) {
+            self.elapsed.set(self.elapsed.get() + step);
+        }
+
+        // 壁時計のみを seconds 秒ずらす。負の値は戻す
+        fn jump_wall(&self, seconds: i64) {
+            self.wall_shift.set(self.wall_shift.get() + seconds);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+
+        fn system_now(&self) -> SystemTime {
+            let wall = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000) + self.elapsed.get();
+            let shift = Duration::from_secs(self.wall_shift.get().unsigned_abs());
+            if self.wall_shift.get() < 0 { wall - shift } else { wall + shift }
+        }
+    }
+
+    // 時計を差し替えた GUI。自動保存や履歴の記録、モードなどの設定の保存がユーザーのファイルを書き換えないよう、
+    // データと設定ファイルの保存先を一時ディレクトリに向ける
+    fn test_gui(mut config: Config, clock: &FakeClock) -> GUI {
+        static ISOLATE: Once = Once::new();
+        ISOLATE.call_once(|| {
+            let dir = std::env::temp_dir().join(format!("simple_timer-test-{}", std::process::id()));
+            std::env::set_var("XDG_DATA_HOME", dir.join("data"));
+            std::env::set_var("XDG_CONFIG_HOME", dir.join("config"));
+        });
+        config.grpc_address = String::new();
+        GUI::with_clock(config, Box::new(clock.clone()))
+    }
+
+    #[test]
+    fn start_stop_and_update_accumulate_total_duration() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(Config::default(), &clock);
+
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(5));
+        gui.update(Message::Update);
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(5));
+
+        // 停止した時点までの分は、更新を待たずに加える
+        clock.advance(Duration::from_secs(2));
+        gui.update(Message::Stop);
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(7));
+
+        // 停止中の更新では進まない
+        clock.advance(Duration::from_secs(10));
+        gui.update(Message::Update);
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(7));
+
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(3));
+        gui.update(Message::Update);
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(10));
+        assert_eq!(gui.paused_total(), Duration::from_secs(10));
+    }
+
+    #[test]
+    fn stops_at_the_maximum_duration_and_shows_the_banner() {
+        let clock = FakeClock::new();
+        let mut config = Config::default();
+        config.max_duration = Duration::from_secs(10);
+        config.finished_banner.enabled = true;
+        let mut gui = test_gui(config, &clock);
+
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(9));
+        gui.update(Message::Update);
+        assert!(gui.stopwatch.is_running());
+        assert!(gui.banner.is_none());
+
+        clock.advance(Duration::from_secs(2));
+        gui.update(Message::Update);
+        assert_eq!(gui.stopwatch.tick_state(), TickState::Stopped);
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(10));
+        assert!(gui.banner.is_some());
+    }
+
+    #[test]
+    fn font_falls_back_to_the_default_for_invalid_bytes() {
+        assert!(matches!(select_font("broken", b"not a font"), Font::Default));
+        assert!(matches!(select_font("broken", &[]), Font::Default));
+        assert!(matches!(select_font("PixelMplus12-Regular", FONT_BYTES), Font::External { name: "PixelMplus12-Regular", .. }));
+    }
+
+    #[test]
+    fn auto_laps_once_per_boundary_at_high_fps() {
+        let clock = FakeClock::new();
+        let mut config = Config::default();
+        config.auto_lap = Duration::from_secs(1);
+        let mut gui = test_gui(config, &clock);
+
+        gui.update(Message::Start);
+        // 1/1000秒の表示と同じ60Hzで5秒間更新し、途中で手動のラップも記録する
+        let step = Duration::from_nanos(1_000_000_000 / FINE_FPS);
+        let mut ticks = 0;
+        while gui.stopwatch.total_duration() < Duration::from_secs(5) {
+            clock.advance(step);
+            gui.update(Message::Update);
+            ticks += 1;
+            if ticks == 90 {
+                gui.update(Message::Lap);
+            }
+        }
+
+        // 自動のラップは区切りごとにちょうど1つずつ、区切りの時刻で記録する
+        let (auto, manual): (Vec<Duration>, Vec<Duration>) =
+            lap::times(&gui.laps).into_iter().partition(|time| time.subsec_nanos() == 0);
+        assert_eq!(auto, (1..=5).map(Duration::from_secs).collect::<Vec<_>>());
+        assert_eq!(manual.len(), 1);
+        assert!(manual[0] > Duration::from_secs(1) && manual[0] < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn tracks_active_and_paused_totals_separately() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(Config::default(), &clock);
+
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(30));
+        gui.update(Message::Stop);
+        clock.advance(Duration::from_secs(5));
+        // 一時停止中も、停止してからの分を一時停止していた時間に含める
+        assert_eq!(gui.paused_total(), Duration::from_secs(5));
+
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(12));
+        gui.update(Message::Stop);
+        clock.advance(Duration::from_secs(3));
+        gui.update(Message::Start);
+        gui.update(Message::Update);
+
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(42));
+        assert_eq!(gui.paused_total(), Duration::from_secs(8));
+        assert_eq!(gui.stopwatch.paused_duration(), Duration::from_secs(8));
+    }
+
+
+    #[test]
+    fn external_reset_targets_the_stopwatch_in_every_mode() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(Config::default(), &clock);
+        gui.update(Message::Start);
+        gui.update(Message::SetMode(Mode::Countdown));
+        gui.push_countdown("Tea", Duration::from_secs(180));
+        clock.advance(Duration::from_secs(60));
+        gui.update(Message::Update);
+
+        // メニューバーや gRPC からの Reset は、カウントダウンの画面でもストップウォッチをリセットする
+        gui.update(Message::Control(ControlCommand::Reset));
+        assert_eq!(gui.stopwatch.tick_state(), TickState::Init);
+        assert_eq!(gui.countdowns[0].remaining(), Duration::from_secs(120));
+
+        // リセットのキーは表示中のカウントダウンをリセットする
+        let message = gui.reset_shortcut().unwrap();
+        gui.update(message);
+        assert_eq!(gui.countdowns[0].remaining(), Duration::from_secs(180));
+        assert!(!gui.countdowns[0].is_running());
+    }
+
+
+    #[test]
+    fn shifts_wall_clock_times_by_a_detected_jump() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(Config::default(), &clock);
+        gui.label = String::from("jump-test");
+        gui.update(Message::SinceInputChanged(String::from("2023-11-01 09:00")));
+        gui.update(Message::SetSince);
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(60));
+        gui.update(Message::Update);
+        let started = gui.stopwatch.started_at().unwrap();
+        let since = gui.since.offset(clock.now_local()).unwrap();
+
+        // 壁時計が1時間進んでも、開始日時と Since の表示はジャンプの分だけずれて経過時間と食い違わない
+        clock.advance(Duration::from_secs(1));
+        clock.jump_wall(3600);
+        gui.update(Message::Update);
+        assert_eq!(gui.stopwatch.started_at(), Some(started + Duration::from_secs(3600)));
+        assert_eq!(gui.since.offset(clock.now_local()), Some(since + chrono::Duration::seconds(1)));
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(61));
+
+        // 戻った場合も同じ
+        clock.advance(Duration::from_secs(1));
+        clock.jump_wall(-7200);
+        gui.update(Message::Update);
+        let shifted = started - Duration::from_secs(3600);
+        assert_eq!(gui.stopwatch.started_at(), Some(shifted));
+        assert_eq!(gui.since.offset(clock.now_local()), Some(since + chrono::Duration::seconds(2)));
+
+        // 履歴にはずらした開始日時と、ジャンプ後の壁時計での記録日時を書く
+        gui.update(Message::Stop);
+        gui.update(Message::Reset);
+        let text = std::fs::read_to_string(history::path().unwrap()).unwrap();
+        let line = text.lines().rfind(|line| line.contains(",jump-test,")).unwrap();
+        let format = |time: SystemTime| DateTime::<Local>::from(time).format("%Y-%m-%dT%H:%M:%S%:z").to_string();
+        assert!(line.contains(&format!(",{},{},stopwatch", format(shifted), format(clock.system_now()))), "{}", line);
+    }
+
+
+    // 閉じる10分前に保存した、60秒計測して5秒一時停止していたストップウォッチ
+    fn saved_stopwatch(clock: &FakeClock, running: bool) -> SavedStopwatch {
+        SavedStopwatch {
+            saved_at: unix_seconds(clock.system_now()) - 600,
+            running,
+            snapshot: Snapshot::new(Duration::from_secs(60), Duration::from_secs(5), &[], Mode::Stopwatch, None),
+        }
+    }
+
+    fn resuming_config(auto_resume: bool, count_while_closed: bool) -> Config {
+        let mut config = Config::default();
+        config.resume_stopwatch = countdown::ResumeConfig { enabled: true, auto_resume, count_while_closed };
+        config
+    }
+
+    #[test]
+    fn counts_the_closed_time_only_if_the_stopwatch_was_running() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(resuming_config(false, true), &clock);
+        gui.resume(None, None, Some(saved_stopwatch(&clock, true)));
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(660));
+        // auto_resume が無効なら、計測中に閉じた場合も一時停止した状態で復元する
+        assert_eq!(gui.stopwatch.tick_state(), TickState::Stopped);
+
+        let mut gui = test_gui(resuming_config(false, true), &clock);
+        gui.resume(None, None, Some(saved_stopwatch(&clock, false)));
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(60));
+
+        let mut gui = test_gui(resuming_config(true, false), &clock);
+        gui.resume(None, None, Some(saved_stopwatch(&clock, true)));
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(60));
+        assert!(gui.stopwatch.is_running());
+    }
+
+    #[test]
+    fn records_only_the_time_after_resuming() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(resuming_config(false, false), &clock);
+        gui.label = String::from("resume-test");
+        gui.resume(None, None, Some(saved_stopwatch(&clock, false)));
+        assert_eq!(gui.paused_total(), Duration::from_secs(5));
+
+        clock.advance(Duration::from_secs(2));
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(30));
+        gui.update(Message::Update);
+        gui.update(Message::Reset);
+
+        // 終了時までの60秒と5秒の一時停止は前回の実行で記録済みのため、再開してからの分のみを記録する
+        let text = std::fs::read_to_string(history::path().unwrap()).unwrap();
+        let line = text.lines().rfind(|line| line.contains(",resume-test,")).unwrap();
+        assert!(line.contains(",resume-test,30.000,2.000,"), "{}", line);
+    }
+
+    #[test]
+    fn crash_recovery_takes_precedence_over_the_saved_stopwatch() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(resuming_config(true, true), &clock);
+        let recovery = Snapshot::new(Duration::from_secs(42), Duration::default(), &[], Mode::Stopwatch, None);
+        gui.resume(Some(recovery), None, Some(saved_stopwatch(&clock, true)));
+        assert!(gui.recovery.is_some());
+        assert_eq!(gui.stopwatch.tick_state(), TickState::Init);
+
+        gui.update(Message::RestoreSession);
+        assert_eq!(gui.stopwatch.total_duration(), Duration::from_secs(42));
+    }
+
+
+    #[test]
+    fn stop_records_the_session_without_counting_it_twice() {
+        let clock = FakeClock::new();
+        let mut gui = test_gui(Config::default(), &clock);
+        gui.label = String::from("stop-test");
+        let recorded = || -> Vec<String> {
+            let text = std::fs::read_to_string(history::path().unwrap()).unwrap_or_default();
+            text.lines()
+                .filter(|line| line.contains(",stop-test,"))
+                .map(|line| line.split(',').skip(2).take(2).collect::<Vec<_>>().join(","))
+                .collect()
+        };
+
+        // 既定の stop_action = "pause" でも、Stop した時点までを記録する
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(20));
+        gui.update(Message::Stop);
+        assert_eq!(gui.stopwatch.tick_state(), TickState::Stopped);
+        assert_eq!(recorded(), vec!["20.000,0.000"]);
+
+        // 再開した後の Stop と Reset は、前回の記録より後の分のみを記録する
+        clock.advance(Duration::from_secs(5));
+        gui.update(Message::Start);
+        clock.advance(Duration::from_secs(10));
+        gui.update(Message::Stop);
+        gui.update(Message::Reset);
+        assert_eq!(recorded(), vec!["20.000,0.000", "10.000,5.000"]);
+        assert_eq!(gui.today_total(), Duration::from_secs(30));
+    }
+
+
+    // コマンドが送るメッセージを順に取り出す
+    fn messages(command: Command<Message>) -> Vec<Message> {
+        command.futures().into_iter().map(async_std::task::block_on).collect()
+    }
+
+    #[test]
+    fn auto_resets_a_finished_countdown_after_the_delay_even_with_sparse_updates() {
+        let clock = FakeClock::new();
+        let mut config = Config::default();
+        config.auto_reset_delay = Duration::from_secs(3);
+        let mut gui = test_gui(config, &clock);
+        gui.push_countdown("auto-reset-test", Duration::from_secs(5));
+        let id = gui.countdowns[0].id;
+
+        clock.advance(Duration::from_secs(5));
+        assert!(messages(gui.update(Message::Update)).is_empty());
+        assert!(gui.countdowns[0].is_finished());
+
+        // 更新が2回しか来なくても、終了から3秒を過ぎた時点で1度だけリセットを送る
+        clock.advance(Duration::from_secs(2));
+        assert!(messages(gui.update(Message::Update)).is_empty());
+        clock.advance(Duration::from_secs(2));
+        let sent = messages(gui.update(Message::Update));
+        assert!(matches!(sent.as_slice(), [Message::ResetCountdown(reset)] if *reset == id));
+        clock.advance(Duration::from_secs(1));
+        assert!(messages(gui.update(Message::Update)).is_empty());
+
+        gui.update(Message::ResetCountdown(id));
+        assert!(!gui.countdowns[0].is_finished());
+    }
 }
@@ -0,0 +1,119 @@
+// macOSのメニューバー・Windowsの通知領域に経過時間を表示し、Start/Stop/Reset/Quitの操作を受け付けるための構造体
+//
+// menubar フィーチャーを有効にしてmacOSかWindows向けにビルドした場合のみ表示され、それ以外の環境では何もしない。
+// Windowsの通知領域には文字を表示できないため、アイコンを表示して経過時間はツールチップに出す。
+// メニューの操作はチャンネル経由でicedのランタイムへ送られる。
+#[cfg(all(feature = "menubar", any(target_os = "macos", windows)))]
+mod platform {
+    use async_std::channel::Sender;
+    use tray_icon::menu::{Menu, MenuEvent, MenuItem};
+    use tray_icon::{TrayIcon, TrayIconBuilder};
+
+    use crate::control::ControlCommand;
+
+    pub struct MenuBar {
+        tray: Option<TrayIcon>,
+        text: String,
+    }
+
+    impl MenuBar {
+        pub fn new(sender: Sender<ControlCommand>) -> MenuBar {
+            MenuBar {
+                tray: build(sender),
+                text: String::new(),
+            }
+        }
+
+        // メニューバーの表示とツールチップを更新する。変化がない場合は何もしない
+        pub fn set_text(&mut self, text: &str) {
+            if let Some(tray) = &self.tray {
+                if self.text != text {
+                    tray.set_title(Some(text));
+                    if let Err(err) = tray.set_tooltip(Some(format!("simple_timer {}", text))) {
+                        log::debug!("failed to update the menu bar tooltip: {}", err);
+                    }
+                    self.text = text.to_string();
+                }
+            }
+        }
+    }
+
+    fn build(sender: Sender<ControlCommand>) -> Option<TrayIcon> {
+        let start = MenuItem::new("Start", true, None);
+        let stop = MenuItem::new("Stop", true, None);
+        let reset = MenuItem::new("Reset", true, None);
+        let quit = MenuItem::new("Quit", true, None);
+
+        let menu = Menu::new();
+        if let Err(err) = menu.append_items(&[&start, &stop, &reset, &quit]) {
+            log::warn!("failed to build the menu bar menu: {}", err);
+            return None;
+        }
+
+        let commands = [
+            (start.id().clone(), ControlCommand::Start),
+            (stop.id().clone(), ControlCommand::Stop),
+            (reset.id().clone(), ControlCommand::Reset),
+            (quit.id().clone(), ControlCommand::Quit),
+        ];
+        MenuEvent::set_event_handler(Some(move |event: MenuEvent| {
+            if let Some((_, command)) = commands.iter().find(|(id, _)| *id == event.id) {
+                let _ = sender.try_send(command.clone());
+            }
+        }));
+
+        let builder = TrayIconBuilder::new().with_menu(Box::new(menu)).with_title("00:00:00").with_tooltip("simple_timer");
+        #[cfg(windows)]
+        let builder = match icon() {
+            Some(icon) => builder.with_icon(icon),
+            None => builder,
+        };
+        match builder.build() {
+            Ok(tray) => Some(tray),
+            Err(err) => {
+                log::warn!("failed to create the menu bar item: {}", err);
+                None
+            },
+        }
+    }
+
+    // 通知領域に表示する、16x16の塗りつぶした円のアイコン
+    #[cfg(windows)]
+    fn icon() -> Option<tray_icon::Icon> {
+        const SIZE: u32 = 16;
+        let center = (SIZE as f32 - 1.0) / 2.0;
+        let rgba = (0..SIZE * SIZE)
+            .flat_map(|index| {
+                let (x, y) = ((index % SIZE) as f32 - center, (index / SIZE) as f32 - center);
+                let alpha = if x * x + y * y <= center * center { 0xFF } else { 0x00 };
+                [0x1E, 0x88, 0xE5, alpha]
+            })
+            .collect();
+        match tray_icon::Icon::from_rgba(rgba, SIZE, SIZE) {
+            Ok(icon) => Some(icon),
+            Err(err) => {
+                log::warn!("failed to build the tray icon: {}", err);
+                None
+            },
+        }
+    }
+}
+
+#[cfg(not(all(feature = "menubar", any(target_os = "macos", windows))))]
+mod platform {
+    use async_std::channel::Sender;
+
+    use crate::control::ControlCommand;
+
+    pub struct MenuBar;
+
+    impl MenuBar {
+        pub fn new(_sender: Sender<ControlCommand>) -> MenuBar {
+            MenuBar
+        }
+
+        pub fn set_text(&mut self, _text: &str) {}
+    }
+}
+
+pub use platform::MenuBar;
@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+// 画面に表示する機能の切り替え
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    Stopwatch, // 経過時間の計測
+    Countdown, // 指定した時間からのカウントダウン
+    Clock,     // 現在時刻の表示
+    Interval,  // 運動と休憩を繰り返すインターバル
+    Since,     // 過去の日時からの経過時間の表示
+    Talk,      // 発表用の、経過時間と制限時間までの残り時間の表示
+}
+
+impl Mode {
+    // 切り替えの選択肢として表示する順番
+    pub const ALL: [Mode; 6] = [Mode::Stopwatch, Mode::Countdown, Mode::Interval, Mode::Talk, Mode::Since, Mode::Clock];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Mode::Stopwatch => "Stopwatch",
+            Mode::Countdown => "Countdown",
+            Mode::Clock => "Clock",
+            Mode::Interval => "Interval",
+            Mode::Since => "Since",
+            Mode::Talk => "Talk",
+        }
+    }
+}
+
+// ストップウォッチのモードで Stop ボタンを押した際の動作
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StopAction {
+    Pause,  // 計測を一時停止する。Restart で続きから計測できる
+    Finish, // 計測を終え、履歴に記録してから次の計測を始められる状態に戻す(Reset と同じ)
+}
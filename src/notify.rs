@@ -0,0 +1,294 @@
+use std::time::Duration;
+
+use async_std::channel::Sender;
+use serde::Deserialize;
+
+use crate::format::deserialize_duration;
+use crate::control::ControlCommand;
+
+// ストップウォッチを止め忘れた場合に、計測中であることを一定間隔で知らせる設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ReminderConfig {
+    // 知らせる間隔。0の場合は知らせない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub interval: Duration,
+    pub channel: ReminderChannel,
+}
+
+impl Default for ReminderConfig {
+    fn default() -> ReminderConfig {
+        ReminderConfig {
+            interval: Duration::default(),
+            channel: ReminderChannel::Notification,
+        }
+    }
+}
+
+// 計測中であることを知らせる方法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReminderChannel {
+    Notification, // デスクトップ通知
+    Sound,        // 控えめな効果音
+    Both,         // 通知と効果音の両方
+}
+
+impl ReminderChannel {
+    pub fn notifies(self) -> bool {
+        self != ReminderChannel::Sound
+    }
+
+    pub fn sounds(self) -> bool {
+        self != ReminderChannel::Notification
+    }
+}
+
+// カウントダウンの終了をデスクトップ通知で知らせるための構造体
+//
+// windows-toast フィーチャーを有効にしたWindowsでは、再開ボタン付きのトースト通知を表示する。
+// トーストを表示できなかった場合や他のOSでは、notifications フィーチャーによる汎用の通知を使う。
+// どちらのフィーチャーも無効な場合は何もしない。
+pub struct Notifier {
+    #[cfg_attr(not(all(windows, feature = "windows-toast")), allow(dead_code))]
+    sender: Sender<ControlCommand>,
+    #[cfg(all(windows, feature = "windows-toast"))]
+    toasts: toast::Toasts,
+}
+
+impl Notifier {
+    pub fn new(sender: Sender<ControlCommand>) -> Notifier {
+        Notifier {
+            sender,
+            #[cfg(all(windows, feature = "windows-toast"))]
+            toasts: toast::Toasts::default(),
+        }
+    }
+
+    // 指定したカウントダウンが終了したことを通知する
+    pub fn notify_finished(&mut self, id: usize, label: &str) {
+        #[cfg(all(windows, feature = "windows-toast"))]
+        {
+            match self.toasts.show(id, label, self.sender.clone()) {
+                Ok(()) => return,
+                Err(err) => log::warn!("failed to show a toast notification: {}", err),
+            }
+        }
+
+        desktop::show(id, label);
+    }
+
+    // ストップウォッチが上限の時間に達して停止したことを通知する
+    pub fn notify_stopped(&self, label: &str, limit: &str) {
+        desktop::show_stopped(label, limit);
+    }
+
+    // ストップウォッチが計測中のままであることを、経過時間とともに通知する
+    pub fn notify_still_running(&self, elapsed: &str) {
+        desktop::show_reminder(elapsed);
+    }
+}
+
+#[cfg(feature = "notifications")]
+mod desktop {
+    pub fn show(_id: usize, label: &str) {
+        let result = notify_rust::Notification::new()
+            .appname("simple_timer")
+            .summary(&format!("{} finished", label))
+            .body("The countdown has reached zero.")
+            .show();
+
+        if let Err(err) = result {
+            log::warn!("failed to show a notification: {}", err);
+        }
+    }
+
+    pub fn show_stopped(label: &str, limit: &str) {
+        let result = notify_rust::Notification::new()
+            .appname("simple_timer")
+            .summary(&format!("{} stopped", label))
+            .body(&format!("The stopwatch reached the maximum of {}.", limit))
+            .show();
+
+        if let Err(err) = result {
+            log::warn!("failed to show a notification: {}", err);
+        }
+    }
+
+    pub fn show_reminder(elapsed: &str) {
+        let result = notify_rust::Notification::new()
+            .appname("simple_timer")
+            .summary(&format!("Timer still running: {}", elapsed))
+            .body("The stopwatch is still counting.")
+            .show();
+
+        if let Err(err) = result {
+            log::warn!("failed to show a notification: {}", err);
+        }
+    }
+}
+
+#[cfg(not(feature = "notifications"))]
+mod desktop {
+    pub fn show(id: usize, label: &str) {
+        log::debug!("notifications are disabled; countdown {} ({}) finished", id, label);
+    }
+
+    pub fn show_stopped(label: &str, limit: &str) {
+        log::debug!("notifications are disabled; {} stopped at the maximum of {}", label, limit);
+    }
+
+    pub fn show_reminder(elapsed: &str) {
+        log::debug!("notifications are disabled; timer still running: {}", elapsed);
+    }
+}
+
+#[cfg(all(windows, feature = "windows-toast"))]
+mod toast {
+    use std::sync::{Arc, Mutex, Once, Weak};
+
+    use async_std::channel::Sender;
+    use windows::core::{Interface, Result, HSTRING, IInspectable};
+    use windows::Data::Xml::Dom::XmlDocument;
+    use windows::Foundation::TypedEventHandler;
+    use windows::UI::Notifications::{
+        ToastActivatedEventArgs, ToastDismissedEventArgs, ToastFailedEventArgs, ToastNotification,
+        ToastNotificationManager,
+    };
+    use windows::Win32::System::Registry::{RegSetKeyValueW, HKEY_CURRENT_USER, REG_SZ};
+    use windows::Win32::UI::Shell::SetCurrentProcessExplicitAppUserModelID;
+
+    use crate::control::ControlCommand;
+
+    // トーストの送り主として通知センターに表示する、このアプリのAppUserModelID
+    // インストーラーを使わないため、起動するたびにユーザーのレジストリへ表示名を登録する
+    // 登録を受け付けない古いWindowsではトーストを表示できず、notifications フィーチャーの通知にフォールバックする
+    const APP_ID: &str = "radish-miyazaki.SimpleTimer";
+    const DISPLAY_NAME: &str = "Simple Timer";
+
+    // 表示中のトースト。破棄するとボタンの操作を受け取れなくなる
+    struct Toast {
+        id: usize,
+        // 同じカウントダウンのトーストを置き換えた際に、古いトーストの後始末で新しいものを消さないよう区別する番号
+        serial: u64,
+        _notification: ToastNotification,
+    }
+
+    // 表示中のトーストの一覧。トーストを閉じた・操作した・表示に失敗した時点で取り除く
+    #[derive(Default)]
+    pub struct Toasts {
+        toasts: Arc<Mutex<Vec<Toast>>>,
+        next_serial: u64,
+    }
+
+    impl Toasts {
+        // カウントダウンの終了を再開ボタン付きのトーストで知らせる。同じカウントダウンの古いトーストは置き換える
+        pub fn show(&mut self, id: usize, label: &str, sender: Sender<ControlCommand>) -> Result<()> {
+            register();
+            let serial = self.next_serial;
+            self.next_serial += 1;
+
+            let notification = notification(id, label)?;
+            let toasts = Arc::downgrade(&self.toasts);
+            notification.Activated(&TypedEventHandler::new(
+                move |_: windows::core::Ref<ToastNotification>, args: windows::core::Ref<IInspectable>| {
+                    let restart = args
+                        .as_ref()
+                        .and_then(|args| args.cast::<ToastActivatedEventArgs>().ok())
+                        .and_then(|args| args.Arguments().ok())
+                        .is_some_and(|arguments| arguments == "restart");
+                    if restart {
+                        let _ = sender.try_send(ControlCommand::RestartCountdown(id));
+                    }
+                    remove(&toasts, serial);
+                    Ok(())
+                },
+            ))?;
+            let toasts = Arc::downgrade(&self.toasts);
+            notification.Dismissed(&TypedEventHandler::new(
+                move |_: windows::core::Ref<ToastNotification>, _: windows::core::Ref<ToastDismissedEventArgs>| {
+                    remove(&toasts, serial);
+                    Ok(())
+                },
+            ))?;
+            let toasts = Arc::downgrade(&self.toasts);
+            notification.Failed(&TypedEventHandler::new(
+                move |_: windows::core::Ref<ToastNotification>, _: windows::core::Ref<ToastFailedEventArgs>| {
+                    remove(&toasts, serial);
+                    Ok(())
+                },
+            ))?;
+
+            ToastNotificationManager::CreateToastNotifierWithId(&HSTRING::from(APP_ID))?
+                .Show(&notification)?;
+
+            if let Ok(mut toasts) = self.toasts.lock() {
+                toasts.retain(|toast| toast.id != id);
+                toasts.push(Toast {
+                    id,
+                    serial,
+                    _notification: notification,
+                });
+            }
+            Ok(())
+        }
+    }
+
+    fn notification(id: usize, label: &str) -> Result<ToastNotification> {
+        let xml = XmlDocument::new()?;
+        xml.LoadXml(&HSTRING::from(format!(
+            "<toast><visual><binding template=\"ToastGeneric\">\
+             <text>{} finished</text><text>The countdown has reached zero.</text>\
+             </binding></visual>\
+             <actions><action content=\"Restart\" arguments=\"restart\"/></actions></toast>",
+            escape(label)
+        )))?;
+
+        let notification = ToastNotification::CreateToastNotification(&xml)?;
+        // 同じカウントダウンの通知は同じタグで表示し、1回の終了につき1つだけ残るようにする
+        notification.SetTag(&HSTRING::from(format!("countdown-{}", id)))?;
+        Ok(notification)
+    }
+
+    // 閉じたトーストを一覧から取り除く。イベントはトーストの一覧を破棄した後に届くこともある
+    fn remove(toasts: &Weak<Mutex<Vec<Toast>>>, serial: u64) {
+        if let Some(toasts) = toasts.upgrade() {
+            if let Ok(mut toasts) = toasts.lock() {
+                toasts.retain(|toast| toast.serial != serial);
+            }
+        }
+    }
+
+    // このプロセスのAppUserModelIDと、通知センターに表示する名前を登録する。失敗した場合もトーストの表示は試みる
+    fn register() {
+        static REGISTER: Once = Once::new();
+        REGISTER.call_once(|| {
+            let key = HSTRING::from(format!("Software\\Classes\\AppUserModelId\\{}", APP_ID));
+            let name: Vec<u16> = DISPLAY_NAME.encode_utf16().chain(Some(0)).collect();
+            let result = unsafe {
+                RegSetKeyValueW(
+                    HKEY_CURRENT_USER,
+                    &key,
+                    &HSTRING::from("DisplayName"),
+                    REG_SZ.0,
+                    Some(name.as_ptr().cast()),
+                    (name.len() * 2) as u32,
+                )
+            };
+            if let Err(err) = result.ok() {
+                log::warn!("failed to register the AppUserModelID {}: {}", APP_ID, err);
+            }
+            if let Err(err) = unsafe { SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(APP_ID)) } {
+                log::warn!("failed to set the AppUserModelID {}: {}", APP_ID, err);
+            }
+        });
+    }
+
+    // XMLに埋め込むラベルの特殊文字をエスケープする
+    fn escape(text: &str) -> String {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+}
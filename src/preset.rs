@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use crate::format::{FormatOptions, FormatStyle, Rounding};
+
+// 経過時間の表示形式のプリセット
+// 丸め方や欄の出し方などを個別に選ぶ代わりに、よく使う組み合わせをまとめて切り替える
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FormatPreset {
+    Custom,  // 設定ファイルの [format] と in_words をそのまま使い、文章表示を画面で切り替えられる
+    Precise, // 常に HH:MM:SS.cc の形で表示する
+    Simple,  // 1秒単位に丸めて、1時間未満は MM:SS と表示する
+    Verbose, // "3 minutes 5 seconds" のような英語の文章で表示する
+    Clock,   // 経過時間の代わりに、現在の時刻を HH:MM:SS と表示する
+}
+
+impl FormatPreset {
+    pub fn name(self) -> &'static str {
+        match self {
+            FormatPreset::Custom => "Custom",
+            FormatPreset::Precise => "Precise",
+            FormatPreset::Simple => "Simple",
+            FormatPreset::Verbose => "Verbose",
+            FormatPreset::Clock => "Clock",
+        }
+    }
+
+    // 切り替えボタンを押した際の次のプリセット
+    pub fn next(self) -> FormatPreset {
+        match self {
+            FormatPreset::Custom => FormatPreset::Precise,
+            FormatPreset::Precise => FormatPreset::Simple,
+            FormatPreset::Simple => FormatPreset::Verbose,
+            FormatPreset::Verbose => FormatPreset::Clock,
+            FormatPreset::Clock => FormatPreset::Custom,
+        }
+    }
+
+    // プリセットに応じた表示形式と、文章で表示するか否か
+    // Custom の場合は、設定ファイルで指定した値をそのまま返す
+    pub fn apply(self, custom: FormatOptions, custom_words: bool) -> (FormatOptions, bool) {
+        match self {
+            FormatPreset::Custom => (custom, custom_words),
+            FormatPreset::Precise => (
+                FormatOptions { rounding: Rounding::Off, style: FormatStyle::Fixed, precision: 2, ..custom },
+                false,
+            ),
+            FormatPreset::Simple => (
+                FormatOptions { rounding: Rounding::Second, style: FormatStyle::HideHours, precision: 0, ..custom },
+                false,
+            ),
+            FormatPreset::Verbose => (custom, true),
+            // ラップや合計の表示には、1秒単位に丸めた経過時間を使う
+            FormatPreset::Clock => (
+                FormatOptions { rounding: Rounding::Second, style: FormatStyle::Fixed, precision: 0, ..custom },
+                false,
+            ),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+use serde::Deserialize;
+
+use crate::{FINE_FPS, FPS};
+
+// 表示を更新する頻度の方針
+//
+// 経過時間は更新のたびに Instant の差分から求めるため、頻度を下げても計測の精度は変わらず、
+// 表示の滑らかさ(と、一定間隔の音や点滅などの反応の細かさ)だけが変わる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RefreshStrategy {
+    // 30Hz(1/1000秒まで表示する場合は60Hz)。1/100秒の表示も滑らかに見えるが、CPUの使用量が最も多い
+    HighPrecision,
+    // 10Hz。1/10秒の表示に十分な頻度で、1/100秒の桁は飛び飛びに表示される
+    Balanced,
+    // 1Hz。秒の表示に十分な頻度で、ノートPCのバッテリーの消費を抑えられる
+    PowerSaver,
+}
+
+impl RefreshStrategy {
+    pub fn name(self) -> &'static str {
+        match self {
+            RefreshStrategy::HighPrecision => "High",
+            RefreshStrategy::Balanced => "Balanced",
+            RefreshStrategy::PowerSaver => "Saver",
+        }
+    }
+
+    // 切り替えボタンを押した際の次の方針
+    pub fn next(self) -> RefreshStrategy {
+        match self {
+            RefreshStrategy::HighPrecision => RefreshStrategy::Balanced,
+            RefreshStrategy::Balanced => RefreshStrategy::PowerSaver,
+            RefreshStrategy::PowerSaver => RefreshStrategy::HighPrecision,
+        }
+    }
+
+    // 1秒あたりの更新回数。fine は1/1000秒まで表示するか否か
+    pub fn fps(self, fine: bool) -> u64 {
+        match self {
+            RefreshStrategy::HighPrecision if fine => FINE_FPS,
+            RefreshStrategy::HighPrecision => FPS,
+            RefreshStrategy::Balanced => 10,
+            RefreshStrategy::PowerSaver => 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn speeds_up_only_for_millisecond_precision() {
+        assert_eq!(RefreshStrategy::HighPrecision.fps(false), FPS);
+        assert_eq!(RefreshStrategy::HighPrecision.fps(true), FINE_FPS);
+        assert_eq!(RefreshStrategy::Balanced.fps(true), 10);
+    }
+}
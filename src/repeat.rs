@@ -0,0 +1,24 @@
+use std::time::Duration;
+
+use crate::history;
+use crate::mode::Mode;
+
+// 最後に終えた計測の設定。Repeat ボタンで同じ設定の計測を用意するために使う
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LastSession {
+    pub mode: Mode,
+    pub label: String,
+    // カウントダウンの長さ。ストップウォッチとインターバルではNone
+    pub length: Option<Duration>,
+}
+
+impl LastSession {
+    // 起動直後でまだ計測を終えていない場合は、履歴に最後に記録したストップウォッチの計測を使う
+    pub fn from_history() -> Option<LastSession> {
+        history::last_label().map(|label| LastSession {
+            mode: Mode::Stopwatch,
+            label,
+            length: None,
+        })
+    }
+}
@@ -0,0 +1,89 @@
+use std::collections::BTreeMap;
+
+use chrono::{Duration as DateDuration, NaiveDate};
+
+use crate::format::{format_duration, FormatOptions, FormatStyle, Rounding};
+use crate::history::{self, DaySummary};
+
+// 集計する日数(今日を含む)
+const WEEK_DAYS: i64 = 7;
+
+// 履歴の集計結果を出力する指定
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    // プログラムから読みやすいよう、JSONで出力する
+    pub json: bool,
+}
+
+// 今日までの7日間の記録を日ごとに集計し、標準出力に書き出す
+pub fn print_week(options: Options, today: NaiveDate) {
+    let from = today - DateDuration::days(WEEK_DAYS - 1);
+    let days = history::aggregate_by_day(from, today);
+    let total = DaySummary {
+        total: days.values().map(|day| day.total).sum(),
+        sessions: days.values().map(|day| day.sessions).sum(),
+    };
+
+    if options.json {
+        println!("{}", to_json(&days, total));
+    } else {
+        print!("{}", to_text(&days, total));
+    }
+}
+
+// 表示は1秒単位で十分なため、丸めて HH:MM:SS の形にする
+fn format(duration: std::time::Duration) -> String {
+    let options = FormatOptions {
+        rounding: Rounding::Second,
+        style: FormatStyle::Fixed,
+        precision: 0,
+        ..FormatOptions::default()
+    };
+    format_duration(duration, &options)
+}
+
+// パイプで他のコマンドに渡しやすいよう、1日1行の列揃えのテキストにする
+fn to_text(days: &BTreeMap<NaiveDate, DaySummary>, total: DaySummary) -> String {
+    if total.sessions == 0 {
+        return format!("no sessions recorded in the past {} days\n", WEEK_DAYS);
+    }
+
+    let row = |name: &str, summary: &DaySummary| {
+        format!(
+            "{:<16} {}  {:>3} sessions  avg {}\n",
+            name,
+            format(summary.total),
+            summary.sessions,
+            format(summary.average()),
+        )
+    };
+    let mut text = String::new();
+    for (date, summary) in days {
+        text.push_str(&row(&date.format("%Y-%m-%d %a").to_string(), summary));
+    }
+    text.push_str(&row("total", &total));
+    text
+}
+
+// 時間はミリ秒の整数で出力する
+fn to_json(days: &BTreeMap<NaiveDate, DaySummary>, total: DaySummary) -> serde_json::Value {
+    let summary = |summary: &DaySummary| {
+        serde_json::json!({
+            "total_millis": summary.total.as_millis() as u64,
+            "sessions": summary.sessions,
+            "average_millis": summary.average().as_millis() as u64,
+        })
+    };
+    let days: Vec<serde_json::Value> = days
+        .iter()
+        .map(|(date, day)| {
+            let mut value = summary(day);
+            value["date"] = serde_json::Value::String(date.to_string());
+            value
+        })
+        .collect();
+
+    let mut value = summary(&total);
+    value["days"] = serde_json::Value::Array(days);
+    value
+}
@@ -0,0 +1,175 @@
+use std::time::Duration;
+
+pub use platform::RpcServer;
+
+// gRPCで外部のプログラムに通知する現在の状態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TimerState {
+    pub running: bool,
+    pub elapsed: Duration,
+    pub countdowns: usize,
+    pub next_remaining: Option<Duration>,
+}
+
+// tonic のサービスは Result<_, Status> を返す決まりのため、Status の大きさに関する警告は抑止する
+#[cfg(feature = "grpc")]
+#[allow(clippy::result_large_err)]
+mod platform {
+    use std::net::SocketAddr;
+    use std::pin::Pin;
+    use std::time::Duration;
+
+    use async_std::channel::Sender;
+    use tokio::sync::watch;
+    use tokio_stream::{wrappers::WatchStream, Stream, StreamExt};
+    use tonic::{Request, Response, Status};
+
+    use super::TimerState;
+    use crate::control::ControlCommand;
+
+    mod proto {
+        tonic::include_proto!("simple_timer");
+    }
+
+    use proto::timer_server::{Timer, TimerServer};
+    use proto::{Empty, SetCountdownRequest, State};
+
+    // gRPCのサーバーを専用のスレッドで動かし、状態を配信するための構造体
+    //
+    // iced のランタイムは async-std で動いているため、tonic 用に別途 tokio のランタイムを用意する。
+    // 受け取った操作はメニューバーなどと同じ ControlCommand のチャンネルに送る。
+    pub struct RpcServer {
+        state: Option<watch::Sender<TimerState>>,
+    }
+
+    impl RpcServer {
+        // 設定したアドレスでサーバーを起動する
+        pub fn start(sender: Sender<ControlCommand>, address: &str) -> RpcServer {
+            let address: SocketAddr = match address.parse() {
+                Ok(address) => address,
+                Err(err) => {
+                    log::warn!("invalid gRPC address {}: {}", address, err);
+                    return RpcServer { state: None };
+                },
+            };
+
+            let (state_sender, state_receiver) = watch::channel(TimerState::default());
+            let service = Service { sender, state: state_receiver };
+
+            let result = std::thread::Builder::new().name(String::from("grpc")).spawn(move || {
+                let runtime = match tokio::runtime::Runtime::new() {
+                    Ok(runtime) => runtime,
+                    Err(err) => {
+                        log::warn!("failed to start the gRPC runtime: {}", err);
+                        return;
+                    },
+                };
+
+                log::info!("gRPC server listening on {}", address);
+                let server = tonic::transport::Server::builder()
+                    .add_service(TimerServer::new(service))
+                    .serve(address);
+                if let Err(err) = runtime.block_on(server) {
+                    log::warn!("gRPC server stopped: {}", err);
+                }
+            });
+            if let Err(err) = result {
+                log::warn!("failed to spawn the gRPC thread: {}", err);
+                return RpcServer { state: None };
+            }
+
+            RpcServer { state: Some(state_sender) }
+        }
+
+        // 状態が変わった場合のみ、WatchState の購読者に配信する
+        pub fn publish(&self, state: TimerState) {
+            if let Some(sender) = &self.state {
+                sender.send_if_modified(|current| {
+                    let modified = *current != state;
+                    *current = state;
+                    modified
+                });
+            }
+        }
+    }
+
+    struct Service {
+        sender: Sender<ControlCommand>,
+        state: watch::Receiver<TimerState>,
+    }
+
+    impl Service {
+        fn send(&self, command: ControlCommand) -> Result<Response<Empty>, Status> {
+            self.sender
+                .try_send(command)
+                .map(|_| Response::new(Empty {}))
+                .map_err(|_| Status::unavailable("the timer is not running"))
+        }
+    }
+
+    #[tonic::async_trait]
+    impl Timer for Service {
+        async fn start(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+            self.send(ControlCommand::Start)
+        }
+
+        async fn stop(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+            self.send(ControlCommand::Stop)
+        }
+
+        async fn reset(&self, _request: Request<Empty>) -> Result<Response<Empty>, Status> {
+            self.send(ControlCommand::Reset)
+        }
+
+        async fn set_countdown(
+            &self,
+            request: Request<SetCountdownRequest>,
+        ) -> Result<Response<Empty>, Status> {
+            let request = request.into_inner();
+            if request.length_millis == 0 {
+                return Err(Status::invalid_argument("length_millis must be positive"));
+            }
+
+            self.send(ControlCommand::AddCountdown {
+                label: request.label,
+                length: Duration::from_millis(request.length_millis),
+            })
+        }
+
+        type WatchStateStream = Pin<Box<dyn Stream<Item = Result<State, Status>> + Send>>;
+
+        async fn watch_state(
+            &self,
+            _request: Request<Empty>,
+        ) -> Result<Response<Self::WatchStateStream>, Status> {
+            let stream = WatchStream::new(self.state.clone()).map(|state| {
+                Ok(State {
+                    running: state.running,
+                    elapsed_millis: state.elapsed.as_millis() as u64,
+                    countdowns: state.countdowns as u32,
+                    next_remaining_millis: state.next_remaining.map_or(0, |remaining| remaining.as_millis() as u64),
+                })
+            });
+            Ok(Response::new(Box::pin(stream)))
+        }
+    }
+}
+
+// grpc フィーチャーが無効な場合は何もしない
+#[cfg(not(feature = "grpc"))]
+mod platform {
+    use async_std::channel::Sender;
+
+    use super::TimerState;
+    use crate::control::ControlCommand;
+
+    pub struct RpcServer;
+
+    impl RpcServer {
+        pub fn start(_sender: Sender<ControlCommand>, _address: &str) -> RpcServer {
+            RpcServer
+        }
+
+        pub fn publish(&self, _state: TimerState) {}
+    }
+}
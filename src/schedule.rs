@@ -0,0 +1,23 @@
+use std::time::Duration;
+
+// 各モードのタイマーで、次に起きる予定の出来事
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Upcoming {
+    pub label: String,
+    // 出来事が起きるまでの時間
+    pub remaining: Duration,
+}
+
+impl Upcoming {
+    pub fn new<S: Into<String>>(label: S, remaining: Duration) -> Upcoming {
+        Upcoming { label: label.into(), remaining }
+    }
+}
+
+// 予定の中から最も早く起きるものを選ぶ。同時の場合は先に挙げたものを優先する
+pub fn soonest<I: IntoIterator<Item = Upcoming>>(events: I) -> Option<Upcoming> {
+    events.into_iter().fold(None, |soonest: Option<Upcoming>, event| match soonest {
+        Some(soonest) if soonest.remaining <= event.remaining => Some(soonest),
+        _ => Some(event),
+    })
+}
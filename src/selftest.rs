@@ -0,0 +1,204 @@
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use crate::clock::{Clock, SystemClock};
+use crate::stopwatch::Stopwatch;
+use crate::FPS;
+
+// 長さを指定しなかった場合に計測を続ける時間
+pub const DEFAULT_LENGTH: Duration = Duration::from_secs(5);
+// ストップウォッチの経過時間と、別に測った経過時間のずれがこれを超えた場合は失敗とする
+const TOLERANCE: Duration = Duration::from_millis(1);
+// 途中で一時停止する間に行う更新の回数
+const PAUSE_TICKS: u32 = 10;
+
+// 計時の精度を確かめる自己診断の指定
+#[derive(Debug, Clone, Copy)]
+pub struct Options {
+    pub length: Duration,
+}
+
+// 自己診断で測った値
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Report {
+    // 経過時間を進めた回数
+    pub updates: u64,
+    // 壁時計で測った、一時停止していた時間
+    pub paused: Duration,
+    // 壁時計で測った、計測していた時間(全体から一時停止していた時間を除いたもの)
+    pub reference: Duration,
+    // ストップウォッチの累計経過時間
+    pub measured: Duration,
+    // 更新の間隔を固定値で足し上げた時間
+    pub nominal: Duration,
+}
+
+impl Report {
+    // ストップウォッチの経過時間と壁時計で測った時間のずれが許容量に収まっているか否か
+    pub fn passed(&self) -> bool {
+        signed_difference(self.measured, self.reference).0 <= TOLERANCE
+    }
+}
+
+// OSの時計を使い、画面の更新と同じ間隔で実際に待ちながら測って結果を出力する。誤差が許容量を超えた場合は false を返す
+pub fn run(options: Options) -> bool {
+    println!("self-test: measuring for {:?} at {} updates per second", options.length, FPS);
+    let report = measure(options, &SystemClock, &mut thread::sleep);
+
+    // 差分を足し上げる方式なら誤差はほぼ0になり、固定値を足し上げる方式では待ちの遅れの分だけずれる
+    println!("updates:           {} (paused for {:?})", report.updates, report.paused);
+    println!("reference elapsed: {:?}", report.reference);
+    println!("stopwatch elapsed: {:?} (error {})", report.measured, signed_difference(report.measured, report.reference).1);
+    println!("sum of intervals:  {:?} (error {})", report.nominal, signed_difference(report.nominal, report.reference).1);
+
+    let passed = report.passed();
+    println!("{}", if passed { "ok" } else { "FAILED: accumulated error exceeds 1ms" });
+    passed
+}
+
+// 画面の更新の間隔だけ wait で待つたびに、GUI と同じようにストップウォッチの advance で経過時間を進める
+// 途中で一度一時停止・再開し、ストップウォッチの累計経過時間(Instant の差分の合計)と、
+// それとは別に壁時計で測った、計測していた時間を返す
+pub fn measure(options: Options, clock: &dyn Clock, wait: &mut dyn FnMut(Duration)) -> Report {
+    let period = Duration::from_nanos(1_000_000_000 / FPS);
+    let mut stopwatch = Stopwatch::new(clock);
+    let start = clock.system_now();
+    stopwatch.start(clock);
+    let mut report = Report {
+        updates: 0,
+        paused: Duration::default(),
+        reference: Duration::default(),
+        measured: Duration::default(),
+        nominal: Duration::default(),
+    };
+    let mut paused_once = false;
+    while stopwatch.total_duration() < options.length {
+        wait(period);
+        stopwatch.advance(clock.now());
+        report.nominal += period;
+        report.updates += 1;
+
+        // 半分を過ぎたところで、更新数回分だけ一時停止する
+        if !paused_once && stopwatch.total_duration() >= options.length / 2 {
+            paused_once = true;
+            let paused_at = clock.system_now();
+            stopwatch.pause(clock.now());
+            for _ in 0..PAUSE_TICKS {
+                wait(period);
+                stopwatch.advance(clock.now());
+            }
+            stopwatch.start(clock);
+            report.paused = wall_elapsed(paused_at, clock);
+        }
+    }
+    report.measured = stopwatch.total_duration();
+    report.reference = wall_elapsed(start, clock).checked_sub(report.paused).unwrap_or_default();
+    report
+}
+
+// 壁時計で測った since からの経過時間。壁時計が戻った場合は0
+fn wall_elapsed(since: SystemTime, clock: &dyn Clock) -> Duration {
+    clock.system_now().duration_since(since).unwrap_or_default()
+}
+
+// 2つの時間の差の大きさと、符号を付けた表示用の文字列
+fn signed_difference(value: Duration, reference: Duration) -> (Duration, String) {
+    if value >= reference {
+        let difference = value - reference;
+        (difference, format!("+{:?}", difference))
+    } else {
+        let difference = reference - value;
+        (difference, format!("-{:?}", difference))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::{Cell, RefCell};
+    use std::time::Instant;
+
+    // 待つたびに、指定された間隔からばらつかせた時間だけ進む時計
+    // 壁時計は、Instant が1秒進むごとに drift_micros マイクロ秒だけ多く進む
+    struct JitterClock {
+        start: Instant,
+        elapsed: Cell<Duration>,
+        wall: Cell<Duration>,
+        drift_micros: u32,
+        steps: RefCell<Vec<Duration>>,
+    }
+
+    impl JitterClock {
+        fn new(drift_micros: u32) -> JitterClock {
+            JitterClock {
+                start: Instant::now(),
+                elapsed: Cell::new(Duration::default()),
+                wall: Cell::new(Duration::default()),
+                drift_micros,
+                steps: RefCell::new(Vec::new()),
+            }
+        }
+
+        // 待つ間隔に、0〜12ミリ秒の不規則な遅れと、ときどき250ミリ秒の詰まりを加えて進める
+        fn wait(&self, period: Duration) {
+            let count = self.steps.borrow().len() as u64;
+            let mut step = period + Duration::from_micros(count * 7919 % 12_000);
+            if count % 37 == 36 {
+                step += Duration::from_millis(250);
+            }
+            self.elapsed.set(self.elapsed.get() + step);
+            self.wall.set(self.wall.get() + step + step * self.drift_micros / 1_000_000);
+            self.steps.borrow_mut().push(step);
+        }
+
+        // 一時停止していた更新を除いた、進めた時間の合計。半分を過ぎた更新の直後の PAUSE_TICKS 回が一時停止にあたる
+        fn ticking_total(&self, length: Duration) -> Duration {
+            let steps = self.steps.borrow();
+            let mut total = Duration::default();
+            let mut index = 0;
+            while index < steps.len() {
+                total += steps[index];
+                index += 1;
+                if total >= length / 2 {
+                    break;
+                }
+            }
+            total + steps.iter().skip(index + PAUSE_TICKS as usize).sum::<Duration>()
+        }
+    }
+
+    impl Clock for JitterClock {
+        fn now(&self) -> Instant {
+            self.start + self.elapsed.get()
+        }
+
+        fn system_now(&self) -> SystemTime {
+            SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000) + self.wall.get()
+        }
+    }
+
+    #[test]
+    fn measures_uneven_ticks_against_a_known_total() {
+        let options = Options { length: Duration::from_secs(5) };
+        let clock = JitterClock::new(0);
+        let report = measure(options, &clock, &mut |period| clock.wait(period));
+
+        assert_eq!(report.measured, clock.ticking_total(options.length));
+        assert_eq!(report.reference, report.measured);
+        assert_eq!(report.updates as usize, clock.steps.borrow().len() - PAUSE_TICKS as usize);
+        assert!(report.passed());
+        // 固定値を足し上げた場合は、遅れの分だけ短くなる
+        assert!(report.nominal + Duration::from_secs(1) < report.measured);
+    }
+
+    #[test]
+    fn fails_when_the_wall_clock_disagrees() {
+        let options = Options { length: Duration::from_secs(5) };
+        let clock = JitterClock::new(1_000);
+        let report = measure(options, &clock, &mut |period| clock.wait(period));
+
+        assert_eq!(report.measured, clock.ticking_total(options.length));
+        assert!(report.reference > report.measured + TOLERANCE);
+        assert!(!report.passed());
+    }
+}
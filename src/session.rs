@@ -0,0 +1,278 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::lap::Lap;
+use crate::mode::Mode;
+
+// 計測中の状態を自動保存し、異常終了した場合に次回の起動時に復元できるようにする
+//
+// 起動時に「実行中」を表すマーカーファイルを作成し、正常に終了した場合のみ削除する。
+// 次回の起動時にマーカーが残っていれば、前回は異常終了したとみなして自動保存した状態の復元を提案する。
+
+// 自動保存する計測の状態。時間はミリ秒で保存する
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Snapshot {
+    total_millis: u64,
+    lap_millis: Vec<u64>,
+    // 保存した時点のモード。古い自動保存にはないためNoneになりうる
+    mode: Option<Mode>,
+    // 一時停止していた時間の合計
+    paused_millis: u64,
+    // ラップごとのメモ。古い自動保存にはないため、足りない分は空とみなす
+    lap_notes: Vec<String>,
+    // 計測を開始した時刻(UNIX時間の秒)。古い自動保存にはないためNoneになりうる
+    started_at: Option<u64>,
+}
+
+impl Snapshot {
+    pub fn new(total_duration: Duration, paused: Duration, laps: &[Lap], mode: Mode, started_at: Option<u64>) -> Snapshot {
+        Snapshot {
+            total_millis: total_duration.as_millis() as u64,
+            lap_millis: laps.iter().map(|lap| lap.time.as_millis() as u64).collect(),
+            mode: Some(mode),
+            paused_millis: paused.as_millis() as u64,
+            lap_notes: laps.iter().map(|lap| lap.note.clone()).collect(),
+            started_at,
+        }
+    }
+
+    pub fn started_at(&self) -> Option<u64> {
+        self.started_at
+    }
+
+    pub fn mode(&self) -> Option<Mode> {
+        self.mode
+    }
+
+    // 復元する意味のある計測結果が含まれているか否か
+    fn is_empty(&self) -> bool {
+        self.total_millis == 0 && self.lap_millis.is_empty()
+    }
+
+    pub fn total_duration(&self) -> Duration {
+        Duration::from_millis(self.total_millis)
+    }
+
+    pub fn paused_duration(&self) -> Duration {
+        Duration::from_millis(self.paused_millis)
+    }
+
+    pub fn laps(&self) -> Vec<Lap> {
+        self.lap_millis
+            .iter()
+            .enumerate()
+            .map(|(index, &millis)| Lap {
+                time: Duration::from_millis(millis),
+                note: self.lap_notes.get(index).cloned().unwrap_or_default(),
+            })
+            .collect()
+    }
+}
+
+// 終了時に保存する、終わっていないカウントダウンの1件分
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedCountdown {
+    pub label: String,
+    pub length_millis: u64,
+    pub remaining_millis: u64,
+    pub running: bool,
+}
+
+// 終了時に保存したカウントダウンの一覧と、保存した時刻(UNIX時間の秒)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedCountdowns {
+    pub saved_at: u64,
+    pub countdowns: Vec<SavedCountdown>,
+}
+
+// 終了時に保存するストップウォッチの計測と、保存した時点で計測中だったか否か・保存した時刻(UNIX時間の秒)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SavedStopwatch {
+    pub saved_at: u64,
+    pub running: bool,
+    pub snapshot: Snapshot,
+}
+
+// 自動保存などのファイルを置くディレクトリ(~/.local/share/simple_timer など、OSごとのデータディレクトリ配下)
+pub fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join("simple_timer"))
+}
+
+fn autosave_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("autosave.toml"))
+}
+
+fn countdowns_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("countdowns.toml"))
+}
+
+fn stopwatch_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("stopwatch.toml"))
+}
+
+fn marker_path() -> Option<PathBuf> {
+    data_dir().map(|dir| dir.join("running"))
+}
+
+// 前回が異常終了していた場合に、自動保存されていた状態を返す
+pub fn load_unclean() -> Option<Snapshot> {
+    if !marker_path()?.exists() {
+        return None;
+    }
+
+    let path = autosave_path()?;
+    let text = fs::read_to_string(&path).ok()?;
+    match toml::from_str::<Snapshot>(&text) {
+        Ok(snapshot) if !snapshot.is_empty() => {
+            log::info!("found autosave from an unclean shutdown: {}", path.display());
+            Some(snapshot)
+        },
+        Ok(_) => None,
+        Err(err) => {
+            log::warn!("failed to parse {}: {}", path.display(), err);
+            None
+        },
+    }
+}
+
+// 実行中のマーカーを作成する
+pub fn mark_running() {
+    if let (Some(dir), Some(path)) = (data_dir(), marker_path()) {
+        if let Err(err) = fs::create_dir_all(&dir).and_then(|_| fs::write(&path, "")) {
+            log::warn!("failed to write {}: {}", path.display(), err);
+        }
+    }
+}
+
+// 正常に終了したことを示すため、実行中のマーカーを削除する
+pub fn mark_clean() {
+    if let Some(path) = marker_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+// 値をTOMLにしてファイルに書き込む。失敗した場合は警告のログを出力する
+fn write_toml<T: Serialize>(path: Option<PathBuf>, value: &T) {
+    let path = match path {
+        Some(path) => path,
+        None => return,
+    };
+
+    let result = toml::to_string(value)
+        .map_err(|err| err.to_string())
+        .and_then(|text| {
+            path.parent()
+                .map_or(Ok(()), fs::create_dir_all)
+                .and_then(|_| fs::write(&path, text))
+                .map_err(|err| err.to_string())
+        });
+    if let Err(err) = result {
+        log::warn!("failed to write {}: {}", path.display(), err);
+    }
+}
+
+// write_toml で書き込んだファイルを読み込む。ファイルがない場合や解釈できない場合はNone
+fn read_toml<T: DeserializeOwned>(path: Option<PathBuf>) -> Option<T> {
+    let path = path?;
+    let text = fs::read_to_string(&path).ok()?;
+    match toml::from_str(&text) {
+        Ok(value) => Some(value),
+        Err(err) => {
+            log::warn!("failed to parse {}: {}", path.display(), err);
+            None
+        },
+    }
+}
+
+// 現在の状態を自動保存する
+pub fn save(snapshot: &Snapshot) {
+    write_toml(autosave_path(), snapshot);
+}
+
+// 終了時のカウントダウンを保存する。終わっていないものがない場合はファイルを削除する
+pub fn save_countdowns(saved: &SavedCountdowns) {
+    if saved.countdowns.is_empty() {
+        if let Some(path) = countdowns_path() {
+            let _ = fs::remove_file(path);
+        }
+        return;
+    }
+    write_toml(countdowns_path(), saved);
+}
+
+// 前回の終了時に保存したカウントダウンを読み込む
+pub fn load_countdowns() -> Option<SavedCountdowns> {
+    read_toml(countdowns_path())
+}
+
+// 終了時のストップウォッチの計測を保存する。計測していない場合はファイルを削除する
+pub fn save_stopwatch(saved: &SavedStopwatch) {
+    if saved.snapshot.is_empty() {
+        if let Some(path) = stopwatch_path() {
+            let _ = fs::remove_file(path);
+        }
+        return;
+    }
+    write_toml(stopwatch_path(), saved);
+}
+
+// 前回の終了時に保存したストップウォッチの計測を読み込む
+pub fn load_stopwatch() -> Option<SavedStopwatch> {
+    read_toml(stopwatch_path())
+}
+
+// 自動保存した状態を破棄する
+pub fn discard() {
+    if let Some(path) = autosave_path() {
+        let _ = fs::remove_file(path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_stopwatch_and_countdowns_round_trip() {
+        let dir = std::env::temp_dir().join(format!("simple_timer-session-{}", std::process::id()));
+        let laps = [
+            Lap { time: Duration::from_millis(1_250), note: String::from("warm-up") },
+            Lap { time: Duration::from_millis(4_000), note: String::new() },
+        ];
+        let stopwatch = SavedStopwatch {
+            saved_at: 1_700_000_000,
+            running: true,
+            snapshot: Snapshot::new(Duration::from_millis(4_321), Duration::from_secs(5), &laps, Mode::Stopwatch, Some(1_699_999_000)),
+        };
+        let path = dir.join("stopwatch.toml");
+        write_toml(Some(path.clone()), &stopwatch);
+        let loaded: SavedStopwatch = read_toml(Some(path)).unwrap();
+        assert_eq!(loaded, stopwatch);
+        assert_eq!(loaded.snapshot.laps(), laps.to_vec());
+        assert_eq!(loaded.snapshot.total_duration(), Duration::from_millis(4_321));
+
+        let countdowns = SavedCountdowns {
+            saved_at: 1_700_000_000,
+            countdowns: vec![SavedCountdown { label: String::from("Tea"), length_millis: 180_000, remaining_millis: 42_000, running: false }],
+        };
+        let path = dir.join("countdowns.toml");
+        write_toml(Some(path.clone()), &countdowns);
+        assert_eq!(read_toml::<SavedCountdowns>(Some(path)), Some(countdowns));
+
+        // 古い自動保存(一時停止やメモの列がないもの)も読める
+        let path = dir.join("old.toml");
+        fs::write(&path, "total_millis = 1500\nlap_millis = [1000]\n").unwrap();
+        let old: Snapshot = read_toml(Some(path)).unwrap();
+        assert_eq!((old.paused_duration(), old.mode(), old.started_at()), (Duration::default(), None, None));
+        assert_eq!(old.laps()[0].note, "");
+        assert!(read_toml::<Snapshot>(Some(dir.join("missing.toml"))).is_none());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
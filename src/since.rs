@@ -0,0 +1,143 @@
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use iced::{button, text_input, Align, Button, Column, Element, HorizontalAlignment, Row, Text, TextInput};
+
+use crate::clock::ClockJump;
+use crate::focus::Focus;
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// 過去の日時からの経過時間を数え上げて表示する
+// 起点の日時は設定ファイルに書き込み、次回の起動時にも引き継ぐ
+pub struct Since {
+    anchor: Option<DateTime<Local>>,
+    input: String,
+    pub input_state: text_input::State,
+    set_button_state: button::State,
+}
+
+impl Since {
+    // 設定ファイルに RFC 3339 の形式で保存された起点を読み込む
+    pub fn new(anchor: Option<&str>) -> Since {
+        let anchor = anchor.and_then(|text| match DateTime::parse_from_rfc3339(text) {
+            Ok(anchor) => Some(anchor.with_timezone(&Local)),
+            Err(err) => {
+                log::warn!("invalid since {:?}: {}", text, err);
+                None
+            },
+        });
+
+        Since {
+            anchor,
+            input: String::new(),
+            input_state: text_input::State::new(),
+            set_button_state: button::State::new(),
+        }
+    }
+
+    pub fn set_input(&mut self, input: String) {
+        self.input = input;
+    }
+
+    // 入力された日時を起点にし、保存用の文字列を返す。解釈できない場合はエラーの文言を返す
+    pub fn apply(&mut self, now: DateTime<Local>) -> Result<String, String> {
+        let anchor = parse_anchor(&self.input, now.date_naive()).ok_or_else(|| format!("Invalid date/time: {}", self.input))?;
+        log::debug!("since: {}", anchor);
+        self.anchor = Some(anchor);
+        self.input.clear();
+        Ok(anchor.to_rfc3339())
+    }
+
+    // 壁時計が飛んだ際に、起点を同じだけずらして経過時間の表示が飛ばないようにし、保存用の文字列を返す。起点がなければNone
+    pub fn shift(&mut self, jump: ClockJump) -> Option<String> {
+        let anchor = DateTime::<Local>::from(jump.shift(self.anchor?.into()));
+        log::debug!("since shifted to {}", anchor);
+        self.anchor = Some(anchor);
+        Some(anchor.to_rfc3339())
+    }
+
+    // 起点から now までの経過時間。起点が未来の場合は負の値になる
+    pub fn offset(&self, now: DateTime<Local>) -> Option<chrono::Duration> {
+        self.anchor.map(|anchor| now.signed_duration_since(anchor))
+    }
+
+    // 起点からの経過時間と、起点の日時を表示する。起点が未来の場合は残り時間として表示する
+    pub fn view(&mut self, now: DateTime<Local>, focus: Option<Focus>, theme: Theme) -> Element<'_, Message> {
+        let (readout, caption) = match (self.anchor, self.offset(now)) {
+            (Some(anchor), Some(offset)) => {
+                let when = anchor.format("%Y-%m-%d %H:%M:%S").to_string();
+                if offset < chrono::Duration::zero() {
+                    (format!("-{}", format_offset(-offset)), format!("{} is in the future", when))
+                } else {
+                    (format_offset(offset), format!("since {}", when))
+                }
+            },
+            _ => (String::from("--:--:--"), String::from("Enter a past date/time")),
+        };
+
+        let input = TextInput::new(
+            &mut self.input_state,
+            "2024-01-31 09:00",
+            &self.input,
+            Message::SinceInputChanged,
+        )
+            .font(font())
+            .padding(5)
+            .on_submit(Message::SetSince)
+            .style(style::TextInput(theme));
+
+        let set_button = Button::new(
+            &mut self.set_button_state,
+            Text::new("Set")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font()),
+        )
+            .on_press(Message::SetSince)
+            .style(style::Button { theme, focused: focus == Some(Focus::SetSince) });
+
+        Column::new()
+            .push(Text::new(readout).font(font()).size(theme.readout_size()))
+            .push(Text::new(caption).font(font()))
+            .push(Row::new()
+                .push(input)
+                .push(set_button)
+                .spacing(10)
+                .align_items(Align::Center)
+            )
+            .spacing(10)
+            .align_items(Align::Center)
+            .into()
+    }
+}
+
+// "2024-01-31 09:00:00"・"2024-01-31 09:00"・"2024-01-31"・"09:00" のような文字列をローカルの日時に変換する
+// 時刻のみの場合は today の日付、日付のみの場合はその日の0時とみなす
+pub fn parse_anchor(text: &str, today: NaiveDate) -> Option<DateTime<Local>> {
+    let text = text.trim();
+    let naive = ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(text, "%Y-%m-%d")
+                .ok()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+        .or_else(|| {
+            NaiveTime::parse_from_str(text, "%H:%M")
+                .ok()
+                .map(|time| today.and_time(time))
+        })?;
+
+    // 夏時間の切り替えで同じ時刻が2度ある場合は早い方を使う
+    Local.from_local_datetime(&naive).earliest()
+}
+
+// 経過時間を HH:MM:SS の形式にする。1日以上の場合は日数を前に付ける
+fn format_offset(offset: chrono::Duration) -> String {
+    let seconds = offset.num_seconds();
+    let (days, hours, minutes, secs) = (seconds / 86400, seconds % 86400 / 3600, seconds % 3600 / 60, seconds % 60);
+    if days > 0 {
+        format!("{}d {:0>2}:{:0>2}:{:0>2}", days, hours, minutes, secs)
+    } else {
+        format!("{:0>2}:{:0>2}:{:0>2}", hours, minutes, secs)
+    }
+}
@@ -0,0 +1,251 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+// カウントダウンの終了を知らせるアラームの音。フォントと同じく実行ファイルに埋め込む
+#[cfg(feature = "sound")]
+const ALARM: &[u8] = include_bytes!("../rsc/alarm.wav");
+
+// 再生する効果音の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    Warning,         // カウントダウンの終了が近いことを知らせる音
+    Beep(BeepSound), // ストップウォッチの計測中に一定間隔で鳴らす音
+    Count,           // 開始前の秒読みで1秒ごとに鳴らす音
+    Go,              // 秒読みが終わり、タイマーが始まったことを知らせる音
+    LongRest,        // インターバルの長い休憩に入ったことを知らせる音
+    Start,           // ストップウォッチを開始した際の短い音
+    Stop,            // ストップウォッチを停止した際の短い音
+    Tick,            // 計測中に1秒ごとに鳴らす、機械式の時計のような短い音
+    Chime,           // カウントダウンの残り時間が分の区切りを下回るごとに鳴らす、控えめな音
+}
+
+// 開始・停止の操作ごとの音や1秒ごとの音など、個別に有効にして音量を指定できる音の設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct ActionSound {
+    pub enabled: bool,
+    // 音量(0.0〜1.0)
+    pub volume: f32,
+}
+
+impl Default for ActionSound {
+    fn default() -> ActionSound {
+        ActionSound {
+            enabled: false,
+            volume: 0.3,
+        }
+    }
+}
+
+// 一定間隔で鳴らす音の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BeepSound {
+    Tick,  // 短く高い音
+    Click, // ごく短い低い音
+    Chime, // 高さの違う2音を続けて鳴らす音
+}
+
+impl Cue {
+    // 効果音を構成する (周波数[Hz], 長さ[ms]) の並び。周波数が0の区間は無音
+    fn tones(self) -> &'static [(f32, u64)] {
+        match self {
+            Cue::Warning => &[(880.0, 120), (0.0, 80), (880.0, 120)],
+            Cue::Beep(BeepSound::Tick) => &[(1320.0, 40)],
+            Cue::Beep(BeepSound::Click) => &[(440.0, 15)],
+            Cue::Beep(BeepSound::Chime) => &[(660.0, 80), (990.0, 120)],
+            Cue::Count => &[(660.0, 100)],
+            Cue::Go => &[(1320.0, 400)],
+            Cue::LongRest => &[(990.0, 160), (0.0, 60), (660.0, 160), (0.0, 60), (440.0, 400)],
+            Cue::Start => &[(1040.0, 50)],
+            Cue::Stop => &[(520.0, 50)],
+            Cue::Tick => &[(2400.0, 6), (0.0, 4), (1800.0, 4)],
+            Cue::Chime => &[(784.0, 150), (523.0, 250)],
+        }
+    }
+}
+
+// 効果音を鳴らすための構造体
+//
+// sound フィーチャーを有効にしてビルドした場合のみrodioで再生し、無効な場合は何もしない。
+// 出力デバイスが開けない環境でも、音が鳴らないだけで動作は継続する。
+pub struct Player {
+    #[cfg(feature = "sound")]
+    output: Option<platform::Output>,
+}
+
+impl Player {
+    pub fn new() -> Player {
+        Player {
+            #[cfg(feature = "sound")]
+            output: platform::Output::open(),
+        }
+    }
+
+    #[cfg(feature = "sound")]
+    pub fn play(&self, cue: Cue) {
+        if let Some(output) = &self.output {
+            for &(frequency, millis) in cue.tones() {
+                output.append_tone(frequency, Duration::from_millis(millis));
+            }
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn play(&self, cue: Cue) {
+        let length: u64 = cue.tones().iter().map(|&(_, millis)| millis).sum();
+        log::debug!("sound is disabled; skipped {:?} ({:?})", cue, Duration::from_millis(length));
+    }
+
+    // 操作ごとの音を、指定した音量で鳴らす
+    // ほかの効果音とは別に再生し、素早く操作した場合は前の音を止めてから鳴らすため、音が重ならない
+    #[cfg(feature = "sound")]
+    pub fn play_action(&self, cue: Cue, volume: f32) {
+        if let Some(output) = &self.output {
+            output.replace_action(cue.tones(), volume.clamp(0.0, 1.0));
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn play_action(&self, cue: Cue, volume: f32) {
+        log::debug!("sound is disabled; skipped {:?} (volume {})", cue, volume);
+    }
+
+    // 1秒ごとの音を、指定した音量で鳴らす
+    // ほかの効果音の再生を待たずに鳴らせるよう、別に再生する
+    #[cfg(feature = "sound")]
+    pub fn play_tick(&self, volume: f32) {
+        if let Some(output) = &self.output {
+            output.append_tick(Cue::Tick.tones(), volume.clamp(0.0, 1.0));
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn play_tick(&self, volume: f32) {
+        log::trace!("sound is disabled; skipped {:?} (volume {})", Cue::Tick, volume);
+    }
+
+    // 埋め込んだアラームの音を、stop_alarm を呼ぶまで繰り返し鳴らす。鳴っている場合は最初から鳴らし直す
+    #[cfg(feature = "sound")]
+    pub fn start_alarm(&self) {
+        if let Some(output) = &self.output {
+            output.loop_alarm(ALARM);
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn start_alarm(&self) {
+        log::debug!("sound is disabled; skipped the alarm");
+    }
+
+    #[cfg(feature = "sound")]
+    pub fn stop_alarm(&self) {
+        if let Some(output) = &self.output {
+            output.stop_alarm();
+        }
+    }
+
+    #[cfg(not(feature = "sound"))]
+    pub fn stop_alarm(&self) {}
+}
+
+#[cfg(feature = "sound")]
+mod platform {
+    use std::io::Cursor;
+    use std::time::Duration;
+
+    use rodio::source::{SineWave, Source, Zero};
+    use rodio::{Decoder, OutputStream, Sink};
+
+    const SAMPLE_RATE: u32 = 44100;
+    const VOLUME: f32 = 0.3;
+
+    // 途中で止めた際に音が途切れてノイズにならないよう、操作ごとの音の立ち上がりを滑らかにする長さ
+    const ACTION_FADE: Duration = Duration::from_millis(5);
+
+    // 出力デバイスと、効果音を順番に再生するためのSink
+    // 操作ごとの音と1秒ごとの音、アラームは、ほかの効果音の再生を待たずに鳴らせるよう別のSinkで再生する
+    pub struct Output {
+        _stream: OutputStream,
+        sink: Sink,
+        action_sink: Sink,
+        tick_sink: Sink,
+        alarm_sink: Sink,
+    }
+
+    impl Output {
+        pub fn open() -> Option<Output> {
+            let result = OutputStream::try_default()
+                .map_err(|err| err.to_string())
+                .and_then(|(stream, handle)| {
+                    let sink = Sink::try_new(&handle).map_err(|err| err.to_string())?;
+                    let action_sink = Sink::try_new(&handle).map_err(|err| err.to_string())?;
+                    let tick_sink = Sink::try_new(&handle).map_err(|err| err.to_string())?;
+                    let alarm_sink = Sink::try_new(&handle).map_err(|err| err.to_string())?;
+                    Ok(Output { _stream: stream, sink, action_sink, tick_sink, alarm_sink })
+                });
+
+            match result {
+                Ok(output) => Some(output),
+                Err(err) => {
+                    log::warn!("failed to open the audio output: {}", err);
+                    None
+                },
+            }
+        }
+
+        pub fn append_tone(&self, frequency: f32, length: Duration) {
+            if frequency > 0.0 {
+                self.sink.append(SineWave::new(frequency).take_duration(length).amplify(VOLUME));
+            } else {
+                self.sink.append(Zero::<f32>::new(1, SAMPLE_RATE).take_duration(length));
+            }
+        }
+
+        // 再生中・再生待ちの操作ごとの音を破棄してから、新しい音を再生する
+        pub fn replace_action(&self, tones: &[(f32, u64)], volume: f32) {
+            self.action_sink.clear();
+            for &(frequency, millis) in tones {
+                let length = Duration::from_millis(millis);
+                if frequency > 0.0 {
+                    self.action_sink
+                        .append(SineWave::new(frequency).take_duration(length).fade_in(ACTION_FADE).amplify(volume));
+                } else {
+                    self.action_sink.append(Zero::<f32>::new(1, SAMPLE_RATE).take_duration(length));
+                }
+            }
+            // clear で一時停止の状態になるため、再生を再開する
+            self.action_sink.play();
+        }
+
+        // 1秒ごとの音を再生する。音は1秒より十分短いため、前の音と重なることはない
+        pub fn append_tick(&self, tones: &[(f32, u64)], volume: f32) {
+            for &(frequency, millis) in tones {
+                let length = Duration::from_millis(millis);
+                if frequency > 0.0 {
+                    self.tick_sink.append(SineWave::new(frequency).take_duration(length).amplify(volume));
+                } else {
+                    self.tick_sink.append(Zero::<f32>::new(1, SAMPLE_RATE).take_duration(length));
+                }
+            }
+        }
+
+        // WAVの音を繰り返し再生する。読み込めない場合は警告のログを出力して鳴らさない
+        pub fn loop_alarm(&self, wav: &'static [u8]) {
+            match Decoder::new_looped(Cursor::new(wav)) {
+                Ok(source) => {
+                    self.alarm_sink.clear();
+                    self.alarm_sink.append(source);
+                    // clear で一時停止の状態になるため、再生を再開する
+                    self.alarm_sink.play();
+                },
+                Err(err) => log::warn!("failed to decode the alarm sound: {}", err),
+            }
+        }
+
+        pub fn stop_alarm(&self) {
+            self.alarm_sink.clear();
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::clock::{Clock, ClockJump};
+use crate::lap;
+
+// 測定中か否かを管理するための条件
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickState {
+    Init,
+    Stopped,
+    Ticking,
+}
+
+impl TickState {
+    pub fn name(self) -> &'static str {
+        match self {
+            TickState::Init => "Ready",
+            TickState::Stopped => "Paused",
+            TickState::Ticking => "Running",
+        }
+    }
+}
+
+// 経過時間が進んだ際に知らせる、一定間隔の区切りの種類。同じ時刻の区切りはこの順に知らせる
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StopwatchCue {
+    Tick,     // 1秒ごとの音
+    Beep,     // beep_interval ごとの音
+    Reminder, // 止め忘れていないかの知らせ
+    AutoLap,  // 自動のラップ
+}
+
+// 区切りの種類ごとの間隔。0の区切りは知らせない
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CueIntervals {
+    pub tick: Duration,
+    pub beep: Duration,
+    pub reminder: Duration,
+    pub auto_lap: Duration,
+}
+
+// 経過時間が before から after に進んだ間に越えた区切りと、その時刻を時刻の順にすべて返す
+// 更新の間隔が空いて1回の更新で複数の区切りを越えた場合も、区切りごとに1つずつ返す
+pub fn crossed_cues(before: Duration, after: Duration, intervals: &CueIntervals) -> Vec<(Duration, StopwatchCue)> {
+    let kinds = [
+        (intervals.tick, StopwatchCue::Tick),
+        (intervals.beep, StopwatchCue::Beep),
+        (intervals.reminder, StopwatchCue::Reminder),
+        (intervals.auto_lap, StopwatchCue::AutoLap),
+    ];
+    let mut cues: Vec<(Duration, StopwatchCue)> = kinds
+        .iter()
+        .flat_map(|&(interval, cue)| lap::boundaries(before, after, interval).into_iter().map(move |time| (time, cue)))
+        .collect();
+    cues.sort();
+    cues
+}
+
+// 開始・停止・リセットと経過時間の累計を受け持つ計時の中心部分
+// ストップウォッチはこの経過時間をそのまま表示し、カウントダウンは長さからこの経過時間を引いて残り時間とする
+pub struct Stopwatch {
+    tick_state: TickState,
+    // 最終更新時刻までの累計経過時間
+    total_duration: Duration,
+    last_update: Instant,
+    // 一時停止していた時間の合計。停止中の分は再開した時点で加える
+    paused_duration: Duration,
+    // 一時停止した時刻。計測前や計測中はNone
+    paused_since: Option<Instant>,
+    // リセットした状態から計測を始めた壁時計の時刻。一時停止・再開しても変わらず、リセットでNoneに戻す
+    started_at: Option<SystemTime>,
+    // 計測を始めた時刻。累計経過時間とは別に経過時間を求め、累計が正しく足されているかを確かめるのに使う
+    started: Option<Instant>,
+}
+
+impl Stopwatch {
+    pub fn new(clock: &dyn Clock) -> Stopwatch {
+        Stopwatch {
+            tick_state: TickState::Init,
+            total_duration: Duration::default(),
+            last_update: clock.now(),
+            paused_duration: Duration::default(),
+            paused_since: None,
+            started_at: None,
+            started: None,
+        }
+    }
+
+    pub fn tick_state(&self) -> TickState {
+        self.tick_state
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.tick_state == TickState::Ticking
+    }
+
+    // 最終更新時刻までの累計経過時間
+    pub fn total_duration(&self) -> Duration {
+        self.total_duration
+    }
+
+    // 現在までの経過時間。計測中であれば、最終更新時刻から現在までの分も含める
+    pub fn elapsed(&self, clock: &dyn Clock) -> Duration {
+        match self.tick_state {
+            TickState::Ticking => self.total_duration + (clock.now() - self.last_update),
+            TickState::Init | TickState::Stopped => self.total_duration,
+        }
+    }
+
+    pub fn started_at(&self) -> Option<SystemTime> {
+        self.started_at
+    }
+
+    // 再開するまでに一時停止していた時間の合計
+    pub fn paused_duration(&self) -> Duration {
+        self.paused_duration
+    }
+
+    // 一時停止していた時間の合計。一時停止中であれば、停止してから現在までの分も含める
+    pub fn paused_total(&self, clock: &dyn Clock) -> Duration {
+        self.paused_duration + self.paused_since.map_or_else(Duration::default, |since| clock.now() - since)
+    }
+
+    // 累計経過時間とは別に、計測を始めた時刻から一時停止していた時間を除いて求めた経過時間
+    // 累計経過時間は更新ごとの差分を足したものであるため、両者が食い違う場合は足し方に誤りがある。計測前はNone
+    pub fn reference_elapsed(&self, clock: &dyn Clock) -> Option<Duration> {
+        (clock.now() - self.started?).checked_sub(self.paused_total(clock))
+    }
+
+    // 計測を始める。一時停止中であれば、停止していた時間を加えてから続きを数える
+    pub fn start(&mut self, clock: &dyn Clock) {
+        if self.tick_state == TickState::Init {
+            self.started_at = Some(clock.system_now());
+            self.started = Some(clock.now());
+        }
+        self.tick_state = TickState::Ticking;
+        self.last_update = clock.now();
+        if let Some(since) = self.paused_since.take() {
+            self.paused_duration += self.last_update - since;
+        }
+    }
+
+    // 累計経過時間に最終更新時刻からの差分を加え、加える前と後の累計経過時間を返す
+    // 計測中でなければ何も加えず、最終更新時刻のみを進める
+    pub fn advance(&mut self, now: Instant) -> (Duration, Duration) {
+        let before = self.total_duration;
+        if self.tick_state == TickState::Ticking {
+            self.total_duration += now - self.last_update;
+        }
+        self.last_update = now;
+        (before, self.total_duration)
+    }
+
+    // 停止した時点までの分を加えてから一時停止し、加える前と後の累計経過時間を返す
+    pub fn pause(&mut self, now: Instant) -> (Duration, Duration) {
+        let advanced = self.advance(now);
+        if self.tick_state == TickState::Ticking {
+            self.tick_state = TickState::Stopped;
+            self.paused_since = Some(now);
+        }
+        advanced
+    }
+
+    // 計測中に累計経過時間が limit に達していれば、limit ちょうどで一時停止して true を返す。limit が0の場合は止めない
+    pub fn stop_at(&mut self, limit: Duration, clock: &dyn Clock) -> bool {
+        if limit == Duration::default() || !self.is_running() || self.total_duration < limit {
+            return false;
+        }
+        self.pause(clock.now());
+        self.set_elapsed(limit, clock);
+        true
+    }
+
+    // 壁時計が飛んだ際に、計測を始めた壁時計の時刻を同じだけずらす。経過時間は Instant で数えているため変わらない
+    pub fn shift_wall_clock(&mut self, jump: ClockJump) {
+        self.started_at = self.started_at.map(|started_at| jump.shift(started_at));
+    }
+
+    // 経過時間と一時停止していた時間を0に戻し、計測前の状態にする
+    pub fn reset(&mut self, clock: &dyn Clock) {
+        *self = Stopwatch::new(clock);
+    }
+
+    // 経過時間を指定した値に置き換え、その時点から数え続ける
+    // 計測前に指定した場合は、その時間だけ前に始めて一時停止している状態とみなす
+    pub fn set_elapsed(&mut self, elapsed: Duration, clock: &dyn Clock) {
+        let now = clock.now();
+        if self.tick_state == TickState::Init {
+            self.started_at = clock.system_now().checked_sub(elapsed);
+            self.paused_since = Some(now);
+            self.tick_state = TickState::Stopped;
+        }
+        self.total_duration = elapsed;
+        self.last_update = now;
+        self.started = now.checked_sub(elapsed + self.paused_total(clock));
+    }
+
+    // 保存しておいた計測を、running にしたがって計測中か一時停止した状態で復元する
+    pub fn restore(&mut self, elapsed: Duration, paused: Duration, started_at: Option<SystemTime>, running: bool, clock: &dyn Clock) {
+        let now = clock.now();
+        self.total_duration = elapsed;
+        self.paused_duration = paused;
+        self.started_at = started_at;
+        self.last_update = now;
+        if running {
+            self.tick_state = TickState::Ticking;
+            self.paused_since = None;
+        } else {
+            self.tick_state = TickState::Stopped;
+            self.paused_since = Some(now);
+        }
+        self.started = now.checked_sub(elapsed + self.paused_total(clock));
+    }
+}
@@ -0,0 +1,593 @@
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+use std::time::Duration;
+
+use iced::{button, checkbox, container, progress_bar, radio, text_input, Background, Color, Vector};
+use serde::{Deserialize, Serialize};
+
+// 配色ファイルから読み込んだ配色。起動時に1度だけ設定する
+static CUSTOM: OnceLock<Palette> = OnceLock::new();
+
+// 画面全体の配色
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    Light,        // 標準の配色
+    Dark,         // 濃い灰色を背景にした暗い配色
+    HighContrast, // 黒地に白の、コントラストの高い配色
+    Solarized,    // Solarized の明るい配色
+    Nord,         // Nord の暗い配色
+    Mono,         // 白地に黒と灰色だけを使う配色
+    Custom,       // 配色ファイル(theme_file)で指定した配色
+}
+
+impl Theme {
+    // テーマの切り替えボタンを押した際の次のテーマ
+    pub fn next(self) -> Theme {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::HighContrast,
+            Theme::HighContrast => Theme::Solarized,
+            Theme::Solarized => Theme::Nord,
+            Theme::Nord => Theme::Mono,
+            Theme::Mono if CUSTOM.get().is_some() => Theme::Custom,
+            Theme::Mono | Theme::Custom => Theme::Light,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Theme::Light => "Light",
+            Theme::Dark => "Dark",
+            Theme::HighContrast => "High contrast",
+            Theme::Solarized => "Solarized",
+            Theme::Nord => "Nord",
+            Theme::Mono => "Mono",
+            Theme::Custom => "Custom",
+        }
+    }
+
+    // 経過時間の表示の文字サイズ。高コントラストでは読みやすいよう大きくする
+    pub fn readout_size(self) -> u16 {
+        match self {
+            Theme::Light | Theme::Dark | Theme::Solarized | Theme::Nord | Theme::Mono | Theme::Custom => 60,
+            Theme::HighContrast => 72,
+        }
+    }
+
+    // 警告時などに点滅させる文字色。どのテーマでも背景色に対して十分なコントラストを持たせる
+    pub fn flash(self) -> Color {
+        self.palette().flash
+    }
+
+    // ラップの差の文字色。前のラップより速ければ緑、遅ければ赤で表示する
+    pub fn faster(self) -> Color {
+        self.palette().faster
+    }
+
+    pub fn slower(self) -> Color {
+        self.palette().slower
+    }
+
+    // モードと状態の表示など、補足の文字に使う控えめな文字色
+    pub fn subtle(self) -> Color {
+        self.palette().subtle
+    }
+
+    fn palette(self) -> Palette {
+        match self {
+            Theme::Light => LIGHT,
+            Theme::Dark => DARK,
+            Theme::HighContrast => HIGH_CONTRAST,
+            Theme::Solarized => SOLARIZED,
+            Theme::Nord => NORD,
+            Theme::Mono => MONO,
+            // 配色ファイルを読み込めていない場合は標準の配色を使う
+            Theme::Custom => CUSTOM.get().copied().unwrap_or(LIGHT),
+        }
+    }
+
+    // 配色ファイルを読み込めている場合のみ Custom を使えるようにする
+    pub fn or_available(self) -> Theme {
+        match self {
+            Theme::Custom if CUSTOM.get().is_none() => Theme::Light,
+            theme => theme,
+        }
+    }
+}
+
+// 配色ファイルの内容。各色は #RRGGBB か #RRGGBBAA の形式で書く
+#[derive(Debug, Deserialize)]
+struct ThemeFile {
+    background: String,
+    text: String,
+    button: String,
+    accent: String,
+}
+
+// 配色ファイルを読み込み、Theme::Custom の配色として設定する
+// 指定されていないそのほかの色は、4色を混ぜ合わせて作る
+pub fn load_custom(path: &Path) -> Result<(), String> {
+    let text = fs::read_to_string(path).map_err(|err| format!("{}: {}", path.display(), err))?;
+    let file: ThemeFile = toml::from_str(&text).map_err(|err| format!("{}: {}", path.display(), err))?;
+
+    let background = parse_hex_color(&file.background)?;
+    let text = parse_hex_color(&file.text)?;
+    let button = parse_hex_color(&file.button)?;
+    let accent = parse_hex_color(&file.accent)?;
+
+    let palette = Palette {
+        background: Some(background),
+        text: Some(text),
+        surface: button,
+        surface_hovered: mix(button, text, 0.1),
+        border: mix(background, text, 0.5),
+        border_width: 1,
+        focus: accent,
+        flash: accent,
+        faster: LIGHT.faster,
+        slower: LIGHT.slower,
+        subtle: mix(background, text, 0.65),
+        bar_background: mix(background, text, 0.3),
+        bar: accent,
+        bar_exceeded: LIGHT.bar_exceeded,
+        input_background: background,
+        placeholder: mix(background, text, 0.5),
+        value: text,
+        selection: Color { a: 0.4, ..accent },
+    };
+    CUSTOM.set(palette).map_err(|_| String::from("custom theme is already loaded"))
+}
+
+// "#RRGGBB" か "#RRGGBBAA" の形式の文字列を色に変換する
+pub fn parse_hex_color(text: &str) -> Result<Color, String> {
+    let invalid = || format!("invalid color {:?}: expected #RRGGBB or #RRGGBBAA", text);
+
+    let hex = text.trim().strip_prefix('#').ok_or_else(invalid)?;
+    if !(hex.len() == 6 || hex.len() == 8) || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(invalid());
+    }
+
+    let channel = |index: usize| {
+        u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16)
+            .map(|value| f32::from(value) / 255.0)
+            .map_err(|_| invalid())
+    };
+    let alpha = if hex.len() == 8 { channel(3)? } else { 1.0 };
+
+    Ok(Color { r: channel(0)?, g: channel(1)?, b: channel(2)?, a: alpha })
+}
+
+// 経過時間に応じた文字色。経過時間が達している段階のうち、最も遅いものの色を使う
+// steps は時間の昇順に並んでいる必要がある。どの段階にも達していなければ None
+pub fn heat_color(steps: &[(Duration, Color)], elapsed: Duration) -> Option<Color> {
+    steps.iter().rev().find(|&&(from, _)| elapsed >= from).map(|&(_, color)| color)
+}
+
+// 2色を ratio の割合(0.0〜1.0)で混ぜ合わせる
+fn mix(from: Color, to: Color, ratio: f32) -> Color {
+    Color {
+        r: from.r + (to.r - from.r) * ratio,
+        g: from.g + (to.g - from.g) * ratio,
+        b: from.b + (to.b - from.b) * ratio,
+        a: from.a + (to.a - from.a) * ratio,
+    }
+}
+
+// テーマごとの色の組み合わせ
+#[derive(Debug, Clone, Copy)]
+struct Palette {
+    background: Option<Color>,
+    text: Option<Color>,
+    surface: Color,
+    surface_hovered: Color,
+    border: Color,
+    border_width: u16,
+    focus: Color,
+    flash: Color,
+    faster: Color,
+    slower: Color,
+    subtle: Color,
+    bar_background: Color,
+    bar: Color,
+    bar_exceeded: Color,
+    input_background: Color,
+    placeholder: Color,
+    value: Color,
+    selection: Color,
+}
+
+// icedの標準のウィジェットに合わせた配色
+const LIGHT: Palette = Palette {
+    background: None,
+    text: None,
+    surface: Color::from_rgb(0.87, 0.87, 0.87),
+    surface_hovered: Color::from_rgb(0.90, 0.90, 0.90),
+    border: Color::from_rgb(0.7, 0.7, 0.7),
+    border_width: 1,
+    focus: Color::from_rgb(0.2, 0.5, 1.0),
+    flash: Color::from_rgb(0.8, 0.3, 0.0),
+    faster: Color::from_rgb(0.0, 0.55, 0.0),
+    slower: Color::from_rgb(0.8, 0.0, 0.0),
+    subtle: Color::from_rgb(0.45, 0.45, 0.45),
+    bar_background: Color::from_rgb(0.6, 0.6, 0.6),
+    bar: Color::from_rgb(0.3, 0.9, 0.3),
+    bar_exceeded: Color::from_rgb(0.9, 0.3, 0.3),
+    input_background: Color::WHITE,
+    placeholder: Color::from_rgb(0.7, 0.7, 0.7),
+    value: Color::from_rgb(0.3, 0.3, 0.3),
+    selection: Color::from_rgb(0.8, 0.8, 1.0),
+};
+
+// 標準の配色の明暗を反転させ、強調色は暗い背景でも読みやすい明るさにした配色
+const DARK: Palette = Palette {
+    background: Some(Color::from_rgb(0.13, 0.13, 0.14)),
+    text: Some(Color::from_rgb(0.90, 0.90, 0.90)),
+    surface: Color::from_rgb(0.22, 0.22, 0.24),
+    surface_hovered: Color::from_rgb(0.28, 0.28, 0.30),
+    border: Color::from_rgb(0.38, 0.38, 0.40),
+    border_width: 1,
+    focus: Color::from_rgb(0.4, 0.65, 1.0),
+    flash: Color::from_rgb(1.0, 0.6, 0.2),
+    faster: Color::from_rgb(0.4, 0.85, 0.4),
+    slower: Color::from_rgb(1.0, 0.45, 0.45),
+    subtle: Color::from_rgb(0.62, 0.62, 0.62),
+    bar_background: Color::from_rgb(0.28, 0.28, 0.30),
+    bar: Color::from_rgb(0.3, 0.8, 0.3),
+    bar_exceeded: Color::from_rgb(0.9, 0.35, 0.35),
+    input_background: Color::from_rgb(0.18, 0.18, 0.19),
+    placeholder: Color::from_rgb(0.5, 0.5, 0.5),
+    value: Color::from_rgb(0.92, 0.92, 0.92),
+    selection: Color::from_rgb(0.25, 0.35, 0.6),
+};
+
+// 黒と白を基本に、強調色には黄色を使う配色
+const HIGH_CONTRAST: Palette = Palette {
+    background: Some(Color::BLACK),
+    text: Some(Color::WHITE),
+    surface: Color::BLACK,
+    surface_hovered: Color::from_rgb(0.2, 0.2, 0.2),
+    border: Color::WHITE,
+    border_width: 2,
+    focus: Color::from_rgb(1.0, 1.0, 0.0),
+    flash: Color::from_rgb(1.0, 1.0, 0.0),
+    faster: Color::from_rgb(0.3, 1.0, 0.3),
+    slower: Color::from_rgb(1.0, 0.45, 0.45),
+    subtle: Color::from_rgb(0.8, 0.8, 0.8),
+    bar_background: Color::from_rgb(0.3, 0.3, 0.3),
+    bar: Color::WHITE,
+    bar_exceeded: Color::from_rgb(1.0, 1.0, 0.0),
+    input_background: Color::BLACK,
+    placeholder: Color::from_rgb(0.7, 0.7, 0.7),
+    value: Color::WHITE,
+    selection: Color::from_rgb(0.0, 0.3, 0.8),
+};
+
+// Solarized の base3 を背景、base00 を文字色にし、強調色に青とオレンジを使う配色
+const SOLARIZED: Palette = Palette {
+    background: Some(Color::from_rgb(0.992, 0.965, 0.890)),
+    text: Some(Color::from_rgb(0.396, 0.482, 0.514)),
+    surface: Color::from_rgb(0.933, 0.910, 0.835),
+    surface_hovered: Color::from_rgb(0.965, 0.937, 0.863),
+    border: Color::from_rgb(0.576, 0.631, 0.631),
+    border_width: 1,
+    focus: Color::from_rgb(0.149, 0.545, 0.824),
+    flash: Color::from_rgb(0.796, 0.294, 0.086),
+    faster: Color::from_rgb(0.522, 0.600, 0.0),
+    slower: Color::from_rgb(0.863, 0.196, 0.184),
+    subtle: Color::from_rgb(0.576, 0.631, 0.631),
+    bar_background: Color::from_rgb(0.933, 0.910, 0.835),
+    bar: Color::from_rgb(0.149, 0.545, 0.824),
+    bar_exceeded: Color::from_rgb(0.863, 0.196, 0.184),
+    input_background: Color::from_rgb(0.992, 0.965, 0.890),
+    placeholder: Color::from_rgb(0.576, 0.631, 0.631),
+    value: Color::from_rgb(0.345, 0.431, 0.459),
+    selection: Color::from_rgb(0.933, 0.910, 0.835),
+};
+
+// Nord の Polar Night を背景、Snow Storm を文字色にし、強調色に Frost の水色を使う配色
+const NORD: Palette = Palette {
+    background: Some(Color::from_rgb(0.180, 0.204, 0.251)),
+    text: Some(Color::from_rgb(0.847, 0.871, 0.914)),
+    surface: Color::from_rgb(0.231, 0.259, 0.322),
+    surface_hovered: Color::from_rgb(0.263, 0.298, 0.369),
+    border: Color::from_rgb(0.298, 0.337, 0.416),
+    border_width: 1,
+    focus: Color::from_rgb(0.533, 0.753, 0.816),
+    flash: Color::from_rgb(0.922, 0.796, 0.545),
+    faster: Color::from_rgb(0.639, 0.745, 0.549),
+    slower: Color::from_rgb(0.749, 0.380, 0.416),
+    subtle: Color::from_rgb(0.596, 0.639, 0.702),
+    bar_background: Color::from_rgb(0.263, 0.298, 0.369),
+    bar: Color::from_rgb(0.533, 0.753, 0.816),
+    bar_exceeded: Color::from_rgb(0.749, 0.380, 0.416),
+    input_background: Color::from_rgb(0.231, 0.259, 0.322),
+    placeholder: Color::from_rgb(0.596, 0.639, 0.702),
+    value: Color::from_rgb(0.925, 0.937, 0.957),
+    selection: Color::from_rgb(0.369, 0.506, 0.675),
+};
+
+// 色を使わず、白・黒・灰色だけで表す配色。ラップの差は濃さで区別する
+const MONO: Palette = Palette {
+    background: Some(Color::WHITE),
+    text: Some(Color::BLACK),
+    surface: Color::from_rgb(0.92, 0.92, 0.92),
+    surface_hovered: Color::from_rgb(0.96, 0.96, 0.96),
+    border: Color::BLACK,
+    border_width: 1,
+    focus: Color::BLACK,
+    flash: Color::from_rgb(0.5, 0.5, 0.5),
+    faster: Color::BLACK,
+    slower: Color::from_rgb(0.55, 0.55, 0.55),
+    subtle: Color::from_rgb(0.45, 0.45, 0.45),
+    bar_background: Color::from_rgb(0.85, 0.85, 0.85),
+    bar: Color::from_rgb(0.3, 0.3, 0.3),
+    bar_exceeded: Color::BLACK,
+    input_background: Color::WHITE,
+    placeholder: Color::from_rgb(0.6, 0.6, 0.6),
+    value: Color::BLACK,
+    selection: Color::from_rgb(0.8, 0.8, 0.8),
+};
+
+// ウィンドウ全体の背景色と文字色
+pub struct Root(pub Theme);
+
+impl container::StyleSheet for Root {
+    fn style(&self) -> container::Style {
+        let palette = self.0.palette();
+
+        container::Style {
+            text_color: palette.text,
+            background: palette.background.map(Background::Color),
+            ..container::Style::default()
+        }
+    }
+}
+
+// ボタンのスタイル。キーボードでフォーカスしている場合は枠を強調する
+pub struct Button {
+    pub theme: Theme,
+    pub focused: bool,
+}
+
+impl button::StyleSheet for Button {
+    fn active(&self) -> button::Style {
+        let palette = self.theme.palette();
+        let (border_color, border_width) = if self.focused {
+            (palette.focus, palette.border_width * 2)
+        } else {
+            (palette.border, palette.border_width)
+        };
+
+        button::Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: Some(Background::Color(palette.surface)),
+            border_radius: 2,
+            border_width,
+            border_color,
+            text_color: palette.text.unwrap_or(Color::BLACK),
+        }
+    }
+
+    fn hovered(&self) -> button::Style {
+        button::Style {
+            background: Some(Background::Color(self.theme.palette().surface_hovered)),
+            shadow_offset: Vector::new(0.0, 1.0),
+            ..self.active()
+        }
+    }
+}
+
+// ラップの一覧の行。普段は背景も枠も付けず、選択中の行だけ枠で示す
+pub struct LapRow {
+    pub theme: Theme,
+    pub selected: bool,
+}
+
+impl button::StyleSheet for LapRow {
+    fn active(&self) -> button::Style {
+        let palette = self.theme.palette();
+        button::Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: None,
+            border_radius: 2,
+            border_width: if self.selected { palette.border_width } else { 0 },
+            border_color: palette.focus,
+            text_color: palette.text.unwrap_or(Color::BLACK),
+        }
+    }
+
+    fn hovered(&self) -> button::Style {
+        button::Style {
+            background: Some(Background::Color(self.theme.palette().surface_hovered)),
+            ..self.active()
+        }
+    }
+}
+
+// チェックボックスのスタイル
+pub struct Checkbox {
+    pub theme: Theme,
+    pub focused: bool,
+}
+
+impl checkbox::StyleSheet for Checkbox {
+    fn active(&self, _is_checked: bool) -> checkbox::Style {
+        let palette = self.theme.palette();
+        let (border_color, border_width) = if self.focused {
+            (palette.focus, palette.border_width * 2)
+        } else {
+            (palette.border, palette.border_width)
+        };
+
+        checkbox::Style {
+            background: Background::Color(palette.input_background),
+            checkmark_color: palette.value,
+            border_radius: 5,
+            border_width,
+            border_color,
+        }
+    }
+
+    fn hovered(&self, is_checked: bool) -> checkbox::Style {
+        checkbox::Style {
+            background: Background::Color(self.theme.palette().surface_hovered),
+            ..self.active(is_checked)
+        }
+    }
+}
+
+// ラジオボタンのスタイル
+pub struct Radio {
+    pub theme: Theme,
+    pub focused: bool,
+}
+
+impl radio::StyleSheet for Radio {
+    fn active(&self) -> radio::Style {
+        let palette = self.theme.palette();
+        let (border_color, border_width) = if self.focused {
+            (palette.focus, palette.border_width * 2)
+        } else {
+            (palette.border, palette.border_width)
+        };
+
+        radio::Style {
+            background: Background::Color(palette.input_background),
+            dot_color: palette.value,
+            border_width,
+            border_color,
+        }
+    }
+
+    fn hovered(&self) -> radio::Style {
+        radio::Style {
+            background: Background::Color(self.theme.palette().surface_hovered),
+            ..self.active()
+        }
+    }
+}
+
+// テキスト入力欄のスタイル
+pub struct TextInput(pub Theme);
+
+impl text_input::StyleSheet for TextInput {
+    fn active(&self) -> text_input::Style {
+        let palette = self.0.palette();
+
+        text_input::Style {
+            background: Background::Color(palette.input_background),
+            border_radius: 5,
+            border_width: palette.border_width,
+            border_color: palette.border,
+        }
+    }
+
+    fn focused(&self) -> text_input::Style {
+        text_input::Style {
+            border_color: self.0.palette().focus,
+            ..self.active()
+        }
+    }
+
+    fn placeholder_color(&self) -> Color {
+        self.0.palette().placeholder
+    }
+
+    fn value_color(&self) -> Color {
+        self.0.palette().value
+    }
+
+    fn selection_color(&self) -> Color {
+        self.0.palette().selection
+    }
+}
+
+// 目標時間に対する進捗バーのスタイル。目標時間を超えたらバーの色を切り替える
+pub struct TargetProgress {
+    pub theme: Theme,
+    pub exceeded: bool,
+}
+
+impl progress_bar::StyleSheet for TargetProgress {
+    fn style(&self) -> progress_bar::Style {
+        let palette = self.theme.palette();
+        let bar = if self.exceeded { palette.bar_exceeded } else { palette.bar };
+
+        progress_bar::Style {
+            background: Background::Color(palette.bar_background),
+            bar: Background::Color(bar),
+            border_radius: 5,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_hex_colors_with_and_without_alpha() {
+        assert_eq!(parse_hex_color("#FF8000"), Ok(Color { r: 1.0, g: 128.0 / 255.0, b: 0.0, a: 1.0 }));
+        assert_eq!(parse_hex_color(" #ff800080 "), Ok(Color { r: 1.0, g: 128.0 / 255.0, b: 0.0, a: 128.0 / 255.0 }));
+        for invalid in ["FF8000", "#FF80", "#FF80001", "#GG8000", "#ＦＦ8000", ""].iter() {
+            assert!(parse_hex_color(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn invalid_theme_file_falls_back_to_the_built_in_theme() {
+        let path = std::env::temp_dir().join(format!("simple_timer-theme-{}.toml", std::process::id()));
+        fs::write(&path, "background = \"#000000\"\ntext = \"white\"\nbutton = \"#333333\"\naccent = \"#F38BA8\"\n").unwrap();
+        let err = load_custom(&path).unwrap_err();
+        fs::remove_file(&path).unwrap();
+
+        assert!(err.contains("invalid color \"white\""), "{}", err);
+        assert_eq!(Theme::Custom.or_available(), Theme::Light);
+        assert_eq!(Theme::Mono.next(), Theme::Light);
+    }
+
+    #[test]
+    fn picks_the_latest_heat_step_reached() {
+        let (green, yellow, red) = (LIGHT.faster, LIGHT.flash, LIGHT.slower);
+        let minutes = |minutes: u64| Duration::from_secs(minutes * 60);
+        let steps = [(Duration::default(), green), (minutes(25), yellow), (minutes(45), red)];
+
+        assert_eq!(heat_color(&steps, Duration::default()), Some(green));
+        assert_eq!(heat_color(&steps, minutes(25) - Duration::from_millis(1)), Some(green));
+        assert_eq!(heat_color(&steps, minutes(25)), Some(yellow));
+        assert_eq!(heat_color(&steps, minutes(45)), Some(red));
+        assert_eq!(heat_color(&steps, minutes(600)), Some(red));
+        // 最初の段階に達するまでは、テーマの文字色のまま
+        assert_eq!(heat_color(&steps[1..], minutes(10)), None);
+        assert_eq!(heat_color(&[], minutes(10)), None);
+    }
+
+    // WCAG の相対輝度
+    fn luminance(color: Color) -> f32 {
+        let linear = |value: f32| {
+            if value <= 0.03928 { value / 12.92 } else { ((value + 0.055) / 1.055).powf(2.4) }
+        };
+        0.2126 * linear(color.r) + 0.7152 * linear(color.g) + 0.0722 * linear(color.b)
+    }
+
+    fn contrast(a: Color, b: Color) -> f32 {
+        let (a, b) = (luminance(a), luminance(b));
+        (a.max(b) + 0.05) / (a.min(b) + 0.05)
+    }
+
+    #[test]
+    fn flash_colors_keep_contrast_with_the_background() {
+        let themes = [Theme::Light, Theme::Dark, Theme::HighContrast, Theme::Solarized, Theme::Nord, Theme::Mono];
+        for theme in themes.iter().copied() {
+            // 背景色を指定しないテーマは icedの標準の白い背景
+            let background = theme.palette().background.unwrap_or(Color::WHITE);
+            // 大きな文字に求められる 3:1 以上とする
+            assert!(contrast(theme.flash(), background) >= 3.0, "{:?}", theme);
+        }
+        // 高コントラストのテーマでは、通常の文字に求められる 7:1 以上とする
+        let high_contrast = HIGH_CONTRAST.background.unwrap_or(Color::BLACK);
+        assert!(contrast(Theme::HighContrast.flash(), high_contrast) >= 7.0);
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::{Duration, Instant};
+
+use iced::{button, Button, Element, HorizontalAlignment, Radio, Row, Text};
+
+use crate::clock::{Clock, ClockJump};
+use crate::focus::Focus;
+use crate::lap::{self, Laps};
+use crate::stopwatch::Stopwatch;
+use crate::style::{self, Theme};
+use crate::{font, Message};
+
+// 1つのタブのストップウォッチの計測。表示中のタブの計測は GUI が持ち、表示していないタブの計測はタブが持つ
+pub struct Measurement {
+    pub stopwatch: Stopwatch,
+    pub laps: Laps,
+    // 日付が変わった時点の累計経過時間と、一時停止していた時間のうち履歴に記録済みの分
+    pub session_offset: Duration,
+    pub paused_offset: Duration,
+}
+
+impl Measurement {
+    pub fn new(clock: &dyn Clock) -> Measurement {
+        Measurement {
+            stopwatch: Stopwatch::new(clock),
+            laps: Laps::default(),
+            session_offset: Duration::default(),
+            paused_offset: Duration::default(),
+        }
+    }
+
+    // 表示していない間も経過時間を進め、越えた区切りの自動のラップを記録する
+    fn advance(&mut self, now: Instant, auto_lap: Duration) {
+        let (before, after) = self.stopwatch.advance(now);
+        for time in lap::boundaries(before, after, auto_lap) {
+            self.laps.record(time);
+        }
+    }
+}
+
+struct Tab {
+    name: String,
+    // 表示中のタブではNone
+    measurement: Option<Measurement>,
+}
+
+// それぞれ独立して計測するストップウォッチのタブ
+pub struct Tabs {
+    tabs: Vec<Tab>,
+    active: usize,
+    // タブの名前に付ける番号。閉じたタブの番号は使い回さない
+    next_number: usize,
+    add_button_state: button::State,
+    close_button_state: button::State,
+}
+
+impl Tabs {
+    pub fn new() -> Tabs {
+        Tabs {
+            tabs: vec![Tab { name: String::from("Timer 1"), measurement: None }],
+            active: 0,
+            next_number: 2,
+            add_button_state: button::State::new(),
+            close_button_state: button::State::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.tabs.len()
+    }
+
+    // 計測前の新しいタブを末尾に加え、その位置を返す。表示は切り替えない
+    pub fn add(&mut self, clock: &dyn Clock) -> usize {
+        let name = format!("Timer {}", self.next_number);
+        self.next_number += 1;
+        self.tabs.push(Tab { name, measurement: Some(Measurement::new(clock)) });
+        self.tabs.len() - 1
+    }
+
+    // index のタブを表示し、そのタブの計測を返す。それまで表示していたタブには current を預ける
+    // 表示中のタブや存在しないタブを指定した場合は、current をそのまま返す
+    pub fn select(&mut self, index: usize, current: Measurement) -> Measurement {
+        if index == self.active || index >= self.tabs.len() {
+            return current;
+        }
+        let next = match self.tabs[index].measurement.take() {
+            Some(next) => next,
+            None => return current,
+        };
+        self.tabs[self.active].measurement = Some(current);
+        self.active = index;
+        next
+    }
+
+    // 表示中のタブを閉じて隣のタブを表示し、そのタブの計測を返す。タブが1つしかない場合はNone
+    pub fn close_active(&mut self) -> Option<Measurement> {
+        if self.tabs.len() < 2 {
+            return None;
+        }
+        self.tabs.remove(self.active);
+        self.active = self.active.min(self.tabs.len() - 1);
+        self.tabs[self.active].measurement.take()
+    }
+
+    // 表示していないタブの計測を進める
+    pub fn advance(&mut self, now: Instant, auto_lap: Duration) {
+        for measurement in self.tabs.iter_mut().filter_map(|tab| tab.measurement.as_mut()) {
+            measurement.advance(now, auto_lap);
+        }
+    }
+
+    // 壁時計が飛んだ際に、表示していないタブの計測を始めた壁時計の時刻をずらす
+    pub fn shift_wall_clock(&mut self, jump: ClockJump) {
+        for measurement in self.tabs.iter_mut().filter_map(|tab| tab.measurement.as_mut()) {
+            measurement.stopwatch.shift_wall_clock(jump);
+        }
+    }
+
+    // 表示していないタブに、計測中のものがあるか否か
+    pub fn any_running(&self) -> bool {
+        self.tabs
+            .iter()
+            .filter_map(|tab| tab.measurement.as_ref())
+            .any(|measurement| measurement.stopwatch.is_running())
+    }
+
+    // タブの切り替えと、追加・閉じるボタン。計測中のタブには running_marker を付ける
+    // 表示中のタブが計測中か否かは GUI が持つため、active_running で受け取る
+    pub fn view(&mut self, active_running: bool, running_marker: &str, focus: Option<Focus>, theme: Theme) -> Element<'_, Message> {
+        let active = self.active;
+        let row = self.tabs.iter().enumerate().fold(Row::new().spacing(10), |row, (index, tab)| {
+            let running = match &tab.measurement {
+                Some(measurement) => measurement.stopwatch.is_running(),
+                None => active_running,
+            };
+            let name = if running { format!("{}{}", running_marker, tab.name) } else { tab.name.clone() };
+            row.push(
+                Radio::new(index, name, Some(active), Message::SelectTab)
+                    .style(style::Radio { theme, focused: focus == Some(Focus::Tab(index)) })
+            )
+        });
+
+        let add_button = Button::new(
+            &mut self.add_button_state,
+            Text::new("+ Timer")
+                .horizontal_alignment(HorizontalAlignment::Center)
+                .font(font())
+        )
+            .on_press(Message::AddTab)
+            .style(style::Button { theme, focused: focus == Some(Focus::AddTab) });
+        let mut row = row.push(add_button);
+
+        // 最後の1つは閉じられないようにする
+        if self.tabs.len() > 1 {
+            let close_button = Button::new(
+                &mut self.close_button_state,
+                Text::new("Close")
+                    .horizontal_alignment(HorizontalAlignment::Center)
+                    .font(font())
+            )
+                .on_press(Message::CloseTab)
+                .style(style::Button { theme, focused: focus == Some(Focus::CloseTab) });
+            row = row.push(close_button);
+        }
+        row.into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::SystemClock;
+    use crate::stopwatch::TickState;
+
+    #[test]
+    fn tabs_keep_independent_measurements() {
+        let clock = SystemClock;
+        let mut tabs = Tabs::new();
+        let mut current = Measurement::new(&clock);
+        current.stopwatch.start(&clock);
+        current.laps.record(Duration::from_secs(1));
+
+        let second = tabs.add(&clock);
+        current = tabs.select(second, current);
+        assert_eq!(current.stopwatch.tick_state(), TickState::Init);
+        assert!(current.laps.is_empty());
+        assert!(tabs.any_running());
+
+        current = tabs.select(0, current);
+        assert!(current.stopwatch.is_running());
+        assert_eq!(current.laps.len(), 1);
+        assert!(!tabs.any_running());
+
+        // 表示中のタブを閉じると、残ったタブの計測に切り替わる
+        let remaining = tabs.close_active().unwrap();
+        assert_eq!(remaining.stopwatch.tick_state(), TickState::Init);
+        assert_eq!(tabs.len(), 1);
+        assert!(tabs.close_active().is_none());
+    }
+}
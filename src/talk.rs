@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use iced::Color;
+use serde::Deserialize;
+
+use crate::format::deserialize_duration;
+use crate::format::{format_duration, format_signed, FormatOptions};
+use crate::style::Theme;
+
+// 警告時に表示を点滅させるティック数
+const FLASH_TICKS: u64 = 15;
+
+// 発表用に、経過時間と制限時間までの残り時間を並べて表示するモードの設定
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct TalkConfig {
+    // 発表の制限時間。0の場合は残り時間を表示しない
+    #[serde(deserialize_with = "deserialize_duration")]
+    pub limit: Duration,
+}
+
+impl Default for TalkConfig {
+    fn default() -> TalkConfig {
+        TalkConfig { limit: Duration::from_secs(20 * 60) }
+    }
+}
+
+// 経過時間を進めた結果として起きた出来事
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TalkEvent {
+    Warning,  // 残り時間が警告の閾値を下回った
+    Overtime, // 制限時間を超えた
+}
+
+// 発表のモードの表示の状態。計測そのものはストップウォッチの経過時間を使う
+#[derive(Debug, Default)]
+pub struct Talk {
+    // 警告による点滅の残りティック数
+    flash_ticks: u64,
+}
+
+impl Talk {
+    pub fn new() -> Talk {
+        Talk::default()
+    }
+
+    // 点滅の残りを1ティック分減らす
+    pub fn tick(&mut self) {
+        self.flash_ticks = self.flash_ticks.saturating_sub(1);
+    }
+
+    // 経過時間が before から after に進んだ間に越えた警告の閾値や制限時間を、起きた順に返す
+    // 閾値は残り時間が上から下へ通過したときのみ反応するため、1回の計測につき1度だけ警告される
+    pub fn advance(&mut self, before: Duration, after: Duration, limit: Duration, thresholds: &[Duration]) -> Vec<TalkEvent> {
+        if limit == Duration::default() {
+            return Vec::new();
+        }
+
+        let remaining = |elapsed: Duration| limit.checked_sub(elapsed).unwrap_or_default();
+        let (remaining_before, remaining_after) = (remaining(before), remaining(after));
+        // 残り0の閾値は制限時間を超えた知らせにまとめる
+        let crossed = thresholds
+            .iter()
+            .filter(|&&threshold| {
+                threshold > Duration::default() && remaining_before > threshold && remaining_after <= threshold
+            })
+            .count();
+        let mut events = vec![TalkEvent::Warning; crossed];
+        if before < limit && after >= limit {
+            events.push(TalkEvent::Overtime);
+        }
+        if !events.is_empty() {
+            self.flash_ticks = FLASH_TICKS;
+        }
+        events
+    }
+
+    // 残り時間か超過した時間の表示と、その文字色
+    // 超過後は赤の文字で制限時間からの超過分を数え上げ、警告の直後は点滅させる
+    pub fn status(&self, elapsed: Duration, limit: Duration, format: &FormatOptions, theme: Theme) -> (String, Option<Color>) {
+        if limit == Duration::default() {
+            return (String::from("No limit set"), Some(theme.subtle()));
+        }
+
+        let flash = if self.flash_ticks % 2 == 1 { Some(theme.flash()) } else { None };
+        match elapsed.checked_sub(limit) {
+            Some(over) if over > Duration::default() => {
+                let over = format_signed(over, true, format.overtime_sign, false, format);
+                (format!("Overtime {}", over), flash.or_else(|| Some(theme.slower())))
+            },
+            _ => (
+                format!(
+                    "Remaining {} of {}",
+                    format_duration(limit - elapsed.min(limit), format),
+                    format_duration(limit, format)
+                ),
+                flash,
+            ),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use std::time::{Duration, Instant};
+
+use iced_futures::futures;
+
+// 一定の間隔で現在時刻を送り出すサブスクリプションのレシピ
+pub struct Timer {
+    duration: Duration,
+}
+
+impl Timer {
+    pub fn new(duration: Duration) -> Timer {
+        Timer {duration}
+    }
+}
+
+impl<H, E> iced_native::subscription::Recipe<H, E> for Timer where H: std::hash::Hasher {
+    // Streamから出力される型
+    type Output = Instant;
+
+    // それぞれのSubscriptionをハッシュで比較できるようにするためのメソッド
+    fn hash(&self, state: &mut H) {
+        use std::hash::Hash;
+
+        // ハッシュ計算用の値はなんでもよいので、今回の計測するdurationをセットしている
+        std::any::TypeId::of::<Self>().hash(state);
+        self.duration.hash(state)
+    }
+
+    // Recipeを実行し、Subscriptionのイベントを出力するStreamを作り出すためのメソッド
+    fn stream(self: Box<Self>, _input: futures::stream::BoxStream<E>)
+        -> futures::stream::BoxStream<Self::Output> {
+            use futures::stream::StreamExt;
+
+            // 一定間隔で現在時刻を返す
+            async_std::stream::interval(self.duration)
+                .map(|_| Instant::now())
+                .boxed()
+    }
+}
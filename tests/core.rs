@@ -0,0 +1,215 @@
+// gui フィーチャーを無効にしたライブラリだけで、計時と状態の管理を使えることを確かめる
+// cargo test --no-default-features でも実行できるよう、ライブラリの公開されたものだけを使う
+use std::cell::Cell;
+use std::time::{Duration, Instant, SystemTime};
+
+use simple_timer::clock::{Clock, ClockJump, JumpDetector};
+use simple_timer::countdown::{Countdown, CountdownEvent};
+use simple_timer::format::{format_duration, parse_duration, FormatOptions};
+use simple_timer::interval::{IntervalEvent, IntervalPlan, IntervalTimer, Phase};
+use simple_timer::lap;
+use simple_timer::stopwatch::{crossed_cues, CueIntervals, Stopwatch, StopwatchCue, TickState};
+
+// 手動で進める時計
+struct FakeClock {
+    start: Instant,
+    elapsed: Cell<Duration>,
+    wall: Cell<SystemTime>,
+}
+
+impl FakeClock {
+    fn new() -> FakeClock {
+        FakeClock {
+            start: Instant::now(),
+            elapsed: Cell::new(Duration::default()),
+            wall: Cell::new(SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000)),
+        }
+    }
+
+    fn advance(&self, step: Duration) {
+        self.elapsed.set(self.elapsed.get() + step);
+        self.wall.set(self.wall.get() + step);
+    }
+}
+
+impl Clock for FakeClock {
+    fn now(&self) -> Instant {
+        self.start + self.elapsed.get()
+    }
+
+    fn system_now(&self) -> SystemTime {
+        self.wall.get()
+    }
+}
+
+#[test]
+fn formats_parsed_durations() {
+    let length = parse_duration("1h2m3s").unwrap();
+    assert_eq!(format_duration(length, &FormatOptions::default()), "01:02:03.00");
+    assert_eq!(parse_duration("1:02:03"), Some(length));
+}
+
+#[test]
+fn stopwatch_counts_only_while_ticking() {
+    let clock = FakeClock::new();
+    let mut stopwatch = Stopwatch::new(&clock);
+
+    stopwatch.start(&clock);
+    clock.advance(Duration::from_secs(5));
+    assert_eq!(stopwatch.advance(clock.now()), (Duration::default(), Duration::from_secs(5)));
+
+    clock.advance(Duration::from_secs(2));
+    stopwatch.pause(clock.now());
+    clock.advance(Duration::from_secs(10));
+    stopwatch.advance(clock.now());
+    assert_eq!(stopwatch.total_duration(), Duration::from_secs(7));
+    assert_eq!(stopwatch.paused_total(&clock), Duration::from_secs(10));
+
+    stopwatch.start(&clock);
+    clock.advance(Duration::from_secs(3));
+    assert_eq!(stopwatch.elapsed(&clock), Duration::from_secs(10));
+    assert_eq!(stopwatch.paused_duration(), Duration::from_secs(10));
+
+    stopwatch.reset(&clock);
+    assert_eq!(stopwatch.tick_state(), TickState::Init);
+    assert_eq!(stopwatch.elapsed(&clock), Duration::default());
+}
+
+#[test]
+fn fires_every_cue_crossed_in_one_update() {
+    let clock = FakeClock::new();
+    let mut stopwatch = Stopwatch::new(&clock);
+    let intervals = CueIntervals {
+        beep: Duration::from_secs(10),
+        reminder: Duration::from_secs(15),
+        auto_lap: Duration::from_secs(20),
+        ..CueIntervals::default()
+    };
+
+    stopwatch.start(&clock);
+    clock.advance(Duration::from_secs(5));
+    let (before, after) = stopwatch.advance(clock.now());
+    assert!(crossed_cues(before, after, &intervals).is_empty());
+
+    // 更新が35秒空いた場合も、越えたすべての区切りを時刻の順に返す
+    clock.advance(Duration::from_secs(35));
+    let (before, after) = stopwatch.advance(clock.now());
+    let secs = |secs| Duration::from_secs(secs);
+    assert_eq!(crossed_cues(before, after, &intervals), vec![
+        (secs(10), StopwatchCue::Beep),
+        (secs(15), StopwatchCue::Reminder),
+        (secs(20), StopwatchCue::Beep),
+        (secs(20), StopwatchCue::AutoLap),
+        (secs(30), StopwatchCue::Beep),
+        (secs(30), StopwatchCue::Reminder),
+        (secs(40), StopwatchCue::Beep),
+        (secs(40), StopwatchCue::AutoLap),
+    ]);
+}
+
+#[test]
+fn countdown_warns_then_finishes() {
+    let clock = FakeClock::new();
+    let mut countdown = Countdown::new(0, String::from("Tea"), Duration::from_secs(180), &clock);
+    let thresholds = [Duration::from_secs(30)];
+
+    clock.advance(Duration::from_secs(100));
+    assert!(countdown.advance(clock.now(), &thresholds).contains(&CountdownEvent::Minute));
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(countdown.advance(clock.now(), &thresholds), vec![CountdownEvent::Minute, CountdownEvent::Warning]);
+    clock.advance(Duration::from_secs(60));
+    assert_eq!(countdown.advance(clock.now(), &thresholds), vec![CountdownEvent::Finished]);
+    assert!(countdown.is_finished());
+    assert!(!countdown.is_running());
+}
+
+#[test]
+fn interval_switches_phases() {
+    let plan = IntervalPlan { work: Duration::from_secs(40), rest: Duration::from_secs(20), rounds: 2, ..IntervalPlan::default() };
+    let mut interval = IntervalTimer::new(plan, Duration::default(), false);
+    interval.toggle();
+
+    let events = interval.advance(Duration::from_secs(90));
+    assert_eq!(events, vec![IntervalEvent::PhaseChanged(Phase::Rest), IntervalEvent::PhaseChanged(Phase::Work)]);
+    assert_eq!(interval.advance(Duration::from_secs(10)), vec![IntervalEvent::Finished]);
+}
+
+#[test]
+fn lap_splits_from_cumulative_times() {
+    let cumulative = [Duration::from_secs(10), Duration::from_secs(25), Duration::from_secs(33)];
+    let splits = lap::splits(&cumulative);
+    assert_eq!(splits, vec![Duration::from_secs(10), Duration::from_secs(15), Duration::from_secs(8)]);
+    assert_eq!(lap::stats(&splits).map(|stats| stats.worst), Some(Duration::from_secs(15)));
+}
+
+#[test]
+fn detects_a_wall_clock_step_with_an_injected_clock() {
+    let clock = FakeClock::new();
+    let mut detector = JumpDetector::new(&clock);
+
+    clock.advance(Duration::from_secs(1));
+    assert_eq!(detector.check(&clock), None);
+
+    clock.advance(Duration::from_secs(1));
+    clock.wall.set(clock.wall.get() + Duration::from_secs(3600));
+    assert_eq!(detector.check(&clock), Some(ClockJump::Forward(Duration::from_secs(3600))));
+}
+
+#[test]
+fn reference_elapsed_follows_start_pause_and_overrides() {
+    let clock = FakeClock::new();
+    let mut stopwatch = Stopwatch::new(&clock);
+    assert_eq!(stopwatch.reference_elapsed(&clock), None);
+
+    stopwatch.start(&clock);
+    clock.advance(Duration::from_secs(4));
+    stopwatch.pause(clock.now());
+    clock.advance(Duration::from_secs(6));
+    stopwatch.start(&clock);
+    clock.advance(Duration::from_secs(3));
+    stopwatch.advance(clock.now());
+    assert_eq!(stopwatch.reference_elapsed(&clock), Some(Duration::from_secs(7)));
+
+    // 累計経過時間を置き換えた場合は、その時点から数え直す
+    stopwatch.set_elapsed(Duration::from_secs(60), &clock);
+    clock.advance(Duration::from_secs(2));
+    assert_eq!(stopwatch.reference_elapsed(&clock), Some(stopwatch.elapsed(&clock)));
+
+    // 累計に足し忘れた分は、独立に数えた経過時間との差として現れる
+    let laps = [Duration::from_secs(30)];
+    let reference = stopwatch.reference_elapsed(&clock).unwrap();
+    assert_eq!(lap::discrepancy(&laps, stopwatch.elapsed(&clock), reference), None);
+    assert!(lap::discrepancy(&laps, stopwatch.total_duration(), reference).is_some());
+}
+
+#[test]
+fn stops_exactly_at_the_maximum_duration() {
+    let clock = FakeClock::new();
+    let mut stopwatch = Stopwatch::new(&clock);
+    let limit = Duration::from_secs(8 * 60 * 60);
+
+    stopwatch.start(&clock);
+    clock.advance(limit - Duration::from_secs(1));
+    stopwatch.advance(clock.now());
+    assert!(!stopwatch.stop_at(limit, &clock));
+    assert!(stopwatch.is_running());
+
+    // 更新の間に上限を越えた場合も、上限ちょうどで止める
+    clock.advance(Duration::from_secs(2));
+    stopwatch.advance(clock.now());
+    assert!(stopwatch.stop_at(limit, &clock));
+    assert_eq!(stopwatch.tick_state(), TickState::Stopped);
+    assert_eq!(stopwatch.total_duration(), limit);
+
+    clock.advance(Duration::from_secs(10));
+    stopwatch.advance(clock.now());
+    assert_eq!(stopwatch.elapsed(&clock), limit);
+    assert!(!stopwatch.stop_at(limit, &clock));
+
+    // 0は上限なし
+    let mut unlimited = Stopwatch::new(&clock);
+    unlimited.start(&clock);
+    clock.advance(limit * 2);
+    unlimited.advance(clock.now());
+    assert!(!unlimited.stop_at(Duration::default(), &clock));
+}